@@ -149,6 +149,7 @@ impl<'a, DB: HirDatabase> QualifyPaths<'a, DB> {
             }
             PathResolution::Local(_)
             | PathResolution::TypeParam(_)
+            | PathResolution::ConstParam(_)
             | PathResolution::SelfType(_) => None,
             PathResolution::Macro(_) => None,
             PathResolution::AssocItem(_) => None,
@@ -138,7 +138,7 @@ impl AutoImportAssets {
         let _p = profile("auto_import::search_for_imports");
         let current_crate = self.module_with_name_to_import.krate();
         ImportsLocator::new(db)
-            .find_imports(&self.get_search_query())
+            .find_imports(&self.get_search_query(), self.module_with_name_to_import)
             .into_iter()
             .filter_map(|module_def| match &self.import_candidate {
                 ImportCandidate::TraitAssocItem(assoc_item_type, _) => {
@@ -163,6 +163,7 @@ impl AutoImportAssets {
                             current_crate,
                             &trait_candidates,
                             None,
+                            None,
                             |_, assoc| Self::assoc_to_trait(assoc.container(db)),
                         )
                         .map(ModuleDef::from)
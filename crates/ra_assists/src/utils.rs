@@ -2,10 +2,11 @@
 
 use ra_syntax::{
     ast::{self, make, NameOwner},
-    AstNode, T,
+    AstNode, InsertPosition, SyntaxElement, T,
 };
 
 use hir::db::HirDatabase;
+use ra_fmt::leading_indent;
 use rustc_hash::FxHashSet;
 
 pub fn get_missing_impl_items(
@@ -76,6 +77,58 @@ pub(crate) fn resolve_target_trait(
     }
 }
 
+/// Finds where new items go in `impl_block`: after its last item or, if it is
+/// empty, after the opening brace. Also returns the indentation for them.
+pub fn impl_insert_position(
+    impl_block: &ast::ImplBlock,
+) -> Option<(InsertPosition<SyntaxElement>, String)> {
+    let item_list = impl_block.item_list()?;
+    match item_list.impl_items().last() {
+        Some(last_item) => {
+            let indent = leading_indent(last_item.syntax()).unwrap_or_default().to_string();
+            Some((InsertPosition::After(last_item.syntax().clone().into()), indent))
+        }
+        None => {
+            let l_curly =
+                item_list.syntax().children_with_tokens().find(|it| it.kind() == T!['{'])?;
+            let indent = format!("    {}", leading_indent(impl_block.syntax()).unwrap_or_default());
+            Some((InsertPosition::After(l_curly), indent))
+        }
+    }
+}
+
+/// Builds an expression that can be used as a placeholder value of type `ty`,
+/// like `0` for integers or `String::new()` for `String`. Falls back to
+/// `Default::default()`.
+pub fn default_expr_for_ty(db: &impl HirDatabase, ty: &hir::Type) -> ast::Expr {
+    if let Some(inner) = ty.remove_ref() {
+        if inner.is_str() && !ty.is_mutable_reference() {
+            return make::expr_literal("\"\"");
+        }
+        return make::expr_ref(default_expr_for_ty(db, &inner), ty.is_mutable_reference());
+    }
+    if ty.is_bool() {
+        return make::expr_literal("false");
+    }
+    if ty.is_integral() {
+        return make::expr_literal("0");
+    }
+    if ty.is_float() {
+        return make::expr_literal("0.0");
+    }
+    // FIXME: check that these are the std types and not just namesakes
+    let adt_name = match ty.as_adt() {
+        Some(hir::Adt::Struct(it)) => Some(it.name(db)),
+        Some(hir::Adt::Enum(it)) => Some(it.name(db)),
+        _ => None,
+    };
+    match adt_name.map(|it| it.to_string()).as_ref().map(String::as_str) {
+        Some("String") => make::expr_from_text("String::new()"),
+        Some("Option") => make::expr_path(make::path_from_name_ref(make::name_ref("None"))),
+        _ => make::expr_from_text("Default::default()"),
+    }
+}
+
 pub(crate) fn invert_boolean_expression(expr: ast::Expr) -> ast::Expr {
     if let Some(expr) = invert_special_case(&expr) {
         return expr;
@@ -96,3 +149,81 @@ fn invert_special_case(expr: &ast::Expr) -> Option<ast::Expr> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use hir::InFile;
+    use ra_db::SourceDatabase;
+    use ra_syntax::{ast, AstNode, InsertPosition};
+
+    use super::{default_expr_for_ty, impl_insert_position};
+    use crate::helpers;
+
+    fn check_default_expr(ty: &str, expected: &str) {
+        let text = format!(
+            "struct String; enum Option<T> {{ None, Some(T) }} struct Foo; fn f(x: {}) {{}}",
+            ty
+        );
+        let (db, file_id) = helpers::with_single_file(&text);
+        let file = db.parse(file_id).tree();
+        let pat = file.syntax().descendants().find_map(ast::Param::cast).unwrap().pat().unwrap();
+        let analyzer =
+            hir::SourceAnalyzer::new(&db, InFile::new(file_id.into(), pat.syntax()), None);
+        let ty = analyzer.type_of_pat(&db, &pat).unwrap();
+        assert_eq!(default_expr_for_ty(&db, &ty).syntax().to_string(), expected);
+    }
+
+    #[test]
+    fn default_expr_for_primitives() {
+        check_default_expr("u32", "0");
+        check_default_expr("i64", "0");
+        check_default_expr("f32", "0.0");
+        check_default_expr("bool", "false");
+    }
+
+    #[test]
+    fn default_expr_for_known_adts() {
+        check_default_expr("String", "String::new()");
+        check_default_expr("Option<u8>", "None");
+    }
+
+    #[test]
+    fn default_expr_for_references() {
+        check_default_expr("&u32", "&0");
+        check_default_expr("&mut bool", "&mut false");
+        check_default_expr("&str", "\"\"");
+    }
+
+    #[test]
+    fn default_expr_fallback() {
+        check_default_expr("Foo", "Default::default()");
+        check_default_expr("(u32, bool)", "Default::default()");
+        check_default_expr("char", "Default::default()");
+    }
+
+    fn check_insert_position(text: &str, expected_anchor: &str, expected_indent: &str) {
+        let file = ast::SourceFile::parse(text).tree();
+        let impl_block = file.syntax().descendants().find_map(ast::ImplBlock::cast).unwrap();
+        let (position, indent) = impl_insert_position(&impl_block).unwrap();
+        let anchor = match position {
+            InsertPosition::After(it) => it.to_string(),
+            _ => panic!("expected to insert after an anchor"),
+        };
+        assert_eq!(anchor, expected_anchor);
+        assert_eq!(indent, expected_indent);
+    }
+
+    #[test]
+    fn impl_insert_position_empty_impl() {
+        check_insert_position("mod m {\n    impl Foo {}\n}", "{", "        ");
+    }
+
+    #[test]
+    fn impl_insert_position_after_last_member() {
+        check_insert_position(
+            "impl Foo {\n    fn a() {}\n\n    const B: u32 = 0;\n}",
+            "const B: u32 = 0;",
+            "    ",
+        );
+    }
+}
@@ -8,6 +8,7 @@ use lsp_types::{
     Location, NumberOrString, Position, Range, TextEdit, Url, WorkspaceEdit,
 };
 use std::{
+    collections::HashMap,
     fmt::Write,
     path::{Component, Path, PathBuf, Prefix},
     str::FromStr,
@@ -116,6 +117,27 @@ fn is_deprecated(rd: &RustDiagnostic) -> bool {
     }
 }
 
+/// Determines if diagnostic is about an `#[expect(lint)]` attribute: either
+/// the expectation is unfulfilled, or the expected lint is reported anyway
+/// (e.g. because of `--force-warn`).
+///
+/// The json has no dedicated field for the lint level, so we look for the
+/// "lint level is defined here" note pointing at an `expect` attribute.
+fn is_lint_expectation(rd: &RustDiagnostic) -> bool {
+    if let Some(code) = &rd.code {
+        if code.code == "unfulfilled_lint_expectations" {
+            return true;
+        }
+    }
+    rd.children
+        .iter()
+        .filter(|child| child.message.contains("lint level"))
+        .flat_map(|child| child.spans.iter())
+        .filter(|span| span.is_primary)
+        .flat_map(|span| span.text.iter())
+        .any(|line| line.text.contains("expect("))
+}
+
 enum MappedRustChildDiagnostic {
     Related(DiagnosticRelatedInformation),
     SuggestedFix(CodeAction),
@@ -148,7 +170,7 @@ fn map_rust_child_diagnostic(
 
         let edit = {
             let edits = vec![TextEdit::new(location.range, suggested_replacement.clone())];
-            let mut edit_map = std::collections::HashMap::new();
+            let mut edit_map = HashMap::new();
             edit_map.insert(location.uri, edits);
             WorkspaceEdit::new(edit_map)
         };
@@ -174,6 +196,8 @@ pub(crate) struct MappedRustDiagnostic {
     pub location: Location,
     pub diagnostic: Diagnostic,
     pub fixes: Vec<CodeAction>,
+    /// `rustc`'s own rendering of the diagnostic, without ANSI color codes.
+    pub rendered: Option<String>,
 }
 
 /// Converts a Rust root diagnostic to LSP form
@@ -185,7 +209,8 @@ pub(crate) struct MappedRustDiagnostic {
 /// 3. Categorising child diagnostics as either `SuggestedFix`es,
 ///    `relatedInformation` or additional message lines.
 ///
-/// If the diagnostic has no primary span this will return `None`
+/// If the diagnostic has no primary span this will return `None`. This covers
+/// the build summaries like "aborting due to 2 previous errors".
 pub(crate) fn map_rust_diagnostic_to_lsp(
     rd: &RustDiagnostic,
     workspace_root: &PathBuf,
@@ -194,7 +219,12 @@ pub(crate) fn map_rust_diagnostic_to_lsp(
 
     let location = map_span_to_location(&primary_span, workspace_root);
 
-    let severity = map_level_to_severity(rd.level);
+    let is_expectation = is_lint_expectation(rd);
+    let severity = if is_expectation {
+        Some(DiagnosticSeverity::Information)
+    } else {
+        map_level_to_severity(rd.level)
+    };
     let mut primary_span_label = primary_span.label.as_ref();
 
     let mut source = String::from("rustc");
@@ -249,6 +279,18 @@ pub(crate) fn map_rust_diagnostic_to_lsp(
         write!(&mut message, "\n{}", primary_span_label).unwrap();
     }
 
+    // The documentation of the code can't be sent as the LSP `codeDescription`
+    // until `lsp-types` supports it, so link it the way Clippy's help does.
+    if let Some(url) = code.as_ref().and_then(|code| code_url(&source, code)) {
+        if !message.contains(url.as_str()) {
+            write!(&mut message, "\nfor further information visit {}", url).unwrap();
+        }
+    }
+
+    if is_expectation {
+        write!(&mut message, "\n(expected by an `#[expect]` attribute)").unwrap();
+    }
+
     if is_unused_or_unnecessary(rd) {
         tags.push(DiagnosticTag::Unnecessary);
     }
@@ -271,7 +313,96 @@ pub(crate) fn map_rust_diagnostic_to_lsp(
         tags: if !tags.is_empty() { Some(tags) } else { None },
     };
 
-    Some(MappedRustDiagnostic { location, diagnostic, fixes })
+    let rendered = rd.rendered.as_ref().map(|it| strip_ansi_codes(it));
+
+    Some(MappedRustDiagnostic { location, diagnostic, fixes, rendered })
+}
+
+/// Converts a batch of Rust diagnostics to LSP form, like
+/// `map_rust_diagnostic_to_lsp`.
+///
+/// Diagnostics with the same message and primary span text, like an error in
+/// a macro repeated for each of its expansions, are collapsed into the first
+/// of them. The locations of the others are added to its `relatedInformation`
+/// and their fixes are kept.
+pub(crate) fn map_rust_diagnostics_to_lsp(
+    rds: &[RustDiagnostic],
+    workspace_root: &PathBuf,
+) -> Vec<MappedRustDiagnostic> {
+    let mut res: Vec<MappedRustDiagnostic> = Vec::new();
+    let mut first_by_key = HashMap::new();
+    for rd in rds {
+        let mapped = match map_rust_diagnostic_to_lsp(rd, workspace_root) {
+            Some(it) => it,
+            None => continue,
+        };
+        let primary_text = rd
+            .spans
+            .iter()
+            .filter(|span| span.is_primary)
+            .flat_map(|span| span.text.iter())
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        match first_by_key.get(&(rd.message.as_str(), primary_text.clone())) {
+            Some(&idx) => {
+                let first: &mut MappedRustDiagnostic = &mut res[idx];
+                first.diagnostic.related_information.get_or_insert_with(Vec::new).push(
+                    DiagnosticRelatedInformation {
+                        location: mapped.location,
+                        message: "Same diagnostic here".to_string(),
+                    },
+                );
+                first.fixes.extend(mapped.fixes);
+            }
+            None => {
+                first_by_key.insert((rd.message.as_str(), primary_text), res.len());
+                res.push(mapped);
+            }
+        }
+    }
+    res
+}
+
+/// Returns the documentation of a diagnostic code: the error index entry for
+/// rustc's `E0308`-style errors or the lint list entry for Clippy lints. Other
+/// lints, like `dead_code`, don't have a page of their own.
+fn code_url(source: &str, code: &str) -> Option<Url> {
+    let url = match source {
+        "rustc" if is_rustc_error_code(code) => {
+            format!("https://doc.rust-lang.org/error-index.html#{}", code)
+        }
+        "clippy" => format!("https://rust-lang.github.io/rust-clippy/master/index.html#{}", code),
+        _ => return None,
+    };
+    Url::parse(&url).ok()
+}
+
+fn is_rustc_error_code(code: &str) -> bool {
+    code.len() == 5 && code.starts_with('E') && code[1..].bytes().all(|it| it.is_ascii_digit())
+}
+
+/// Removes ANSI escape sequences, like the colors in the output of
+/// `--message-format=json-diagnostic-rendered-ansi`, from `text`
+fn strip_ansi_codes(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            res.push(c);
+            continue;
+        }
+        // Control sequences are `ESC [`, some parameters and a final byte
+        if chars.clone().next() == Some('[') {
+            chars.next();
+            for c in &mut chars {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    res
 }
 
 /// Returns a `Url` object from a given path, will lowercase drive letters if present.
@@ -936,3 +936,258 @@ fn snap_macro_compiler_error() {
     let diag = map_rust_diagnostic_to_lsp(&diag, &workspace_root).expect("couldn't map diagnostic");
     insta::assert_debug_snapshot!(diag);
 }
+
+#[test]
+#[cfg(not(windows))]
+fn snap_rustc_expected_lint() {
+    let diag = parse_diagnostic(
+        r##"{
+"message": "unused variable: `foo`",
+"code": {
+    "code": "unused_variables",
+    "explanation": null
+},
+"level": "warning",
+"spans": [
+    {
+        "file_name": "driver/subcommand/repl.rs",
+        "byte_start": 9228,
+        "byte_end": 9231,
+        "line_start": 291,
+        "line_end": 291,
+        "column_start": 9,
+        "column_end": 12,
+        "is_primary": true,
+        "text": [
+            {
+                "text": "    let foo = 42;",
+                "highlight_start": 9,
+                "highlight_end": 12
+            }
+        ],
+        "label": null,
+        "suggested_replacement": null,
+        "suggestion_applicability": null,
+        "expansion": null
+    }
+],
+"children": [
+    {
+        "message": "the lint level is defined here",
+        "code": null,
+        "level": "note",
+        "spans": [
+            {
+                "file_name": "driver/subcommand/repl.rs",
+                "byte_start": 9180,
+                "byte_end": 9196,
+                "line_start": 289,
+                "line_end": 289,
+                "column_start": 14,
+                "column_end": 30,
+                "is_primary": true,
+                "text": [
+                    {
+                        "text": "    #[expect(unused_variables)]",
+                        "highlight_start": 14,
+                        "highlight_end": 30
+                    }
+                ],
+                "label": null,
+                "suggested_replacement": null,
+                "suggestion_applicability": null,
+                "expansion": null
+            }
+        ],
+        "children": [],
+        "rendered": null
+    }
+],
+"rendered": "warning: unused variable: `foo`\n   --> driver/subcommand/repl.rs:291:9\n    |\n291 |     let foo = 42;\n    |         ^^^\n    |\nnote: the lint level is defined here\n   --> driver/subcommand/repl.rs:289:14\n    |\n289 |     #[expect(unused_variables)]\n    |              ^^^^^^^^^^^^^^^^\n\n"
+}"##,
+    );
+
+    let workspace_root = PathBuf::from("/test/");
+    let diag = map_rust_diagnostic_to_lsp(&diag, &workspace_root).expect("couldn't map diagnostic");
+    insta::assert_debug_snapshot!(diag);
+}
+
+#[test]
+fn rustc_aborting_summary_is_ignored() {
+    let diag = parse_diagnostic(
+        r##"{
+    "message": "aborting due to 2 previous errors",
+    "code": null,
+    "level": "error",
+    "spans": [],
+    "children": [],
+    "rendered": "error: aborting due to 2 previous errors\n\n"
+}"##,
+    );
+
+    let workspace_root = PathBuf::from("/test/");
+    assert!(map_rust_diagnostic_to_lsp(&diag, &workspace_root).is_none());
+}
+
+#[test]
+fn rendered_message_is_stripped_of_ansi_codes() {
+    let diag = parse_diagnostic(
+        r##"{
+    "message": "unused variable: `foo`",
+    "code": {
+        "code": "unused_variables",
+        "explanation": null
+    },
+    "level": "warning",
+    "spans": [
+        {
+            "file_name": "src/main.rs",
+            "byte_start": 20,
+            "byte_end": 23,
+            "line_start": 2,
+            "line_end": 2,
+            "column_start": 9,
+            "column_end": 12,
+            "is_primary": true,
+            "text": [
+                {
+                    "text": "    let foo = 42;",
+                    "highlight_start": 9,
+                    "highlight_end": 12
+                }
+            ],
+            "label": null,
+            "suggested_replacement": null,
+            "suggestion_applicability": null,
+            "expansion": null
+        }
+    ],
+    "children": [],
+    "rendered": "\u001b[0m\u001b[1m\u001b[33mwarning\u001b[0m\u001b[0m\u001b[1m: unused variable: `foo`\u001b[0m\n\u001b[0m \u001b[0m\u001b[0m\u001b[1m\u001b[38;5;12m--> \u001b[0m\u001b[0msrc/main.rs:2:9\u001b[0m\n"
+}"##,
+    );
+
+    let workspace_root = PathBuf::from("/test/");
+    let diag = map_rust_diagnostic_to_lsp(&diag, &workspace_root).expect("couldn't map diagnostic");
+    assert_eq!(
+        diag.rendered.as_deref(),
+        Some("warning: unused variable: `foo`\n --> src/main.rs:2:9\n")
+    );
+}
+
+#[test]
+fn error_codes_link_to_their_documentation() {
+    let url = |source: &str, code: &str| super::code_url(source, code).map(|it| it.to_string());
+    assert_eq!(
+        url("rustc", "E0308").as_deref(),
+        Some("https://doc.rust-lang.org/error-index.html#E0308")
+    );
+    assert_eq!(
+        url("clippy", "needless_return").as_deref(),
+        Some("https://rust-lang.github.io/rust-clippy/master/index.html#needless_return")
+    );
+    assert_eq!(url("rustc", "dead_code"), None);
+    assert_eq!(url("rustc", "E03081"), None);
+    assert_eq!(url("other_tool", "E0308"), None);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn identical_diagnostics_are_collapsed() {
+    let diag_at = |line: u32| {
+        parse_diagnostic(&format!(
+            r##"{{
+                "message": "unused variable: `foo`",
+                "code": {{ "code": "unused_variables", "explanation": null }},
+                "level": "warning",
+                "spans": [
+                    {{
+                        "file_name": "src/lib.rs",
+                        "byte_start": 0,
+                        "byte_end": 3,
+                        "line_start": {line},
+                        "line_end": {line},
+                        "column_start": 9,
+                        "column_end": 12,
+                        "is_primary": true,
+                        "text": [{{ "text": "    let foo = 42;", "highlight_start": 9, "highlight_end": 12 }}],
+                        "label": null,
+                        "suggested_replacement": null,
+                        "suggestion_applicability": null,
+                        "expansion": null
+                    }}
+                ],
+                "children": [
+                    {{
+                        "message": "consider prefixing with an underscore",
+                        "code": null,
+                        "level": "help",
+                        "spans": [
+                            {{
+                                "file_name": "src/lib.rs",
+                                "byte_start": 0,
+                                "byte_end": 3,
+                                "line_start": {line},
+                                "line_end": {line},
+                                "column_start": 9,
+                                "column_end": 12,
+                                "is_primary": true,
+                                "text": [{{ "text": "    let foo = 42;", "highlight_start": 9, "highlight_end": 12 }}],
+                                "label": null,
+                                "suggested_replacement": "_foo",
+                                "suggestion_applicability": "MachineApplicable",
+                                "expansion": null
+                            }}
+                        ],
+                        "children": [],
+                        "rendered": null
+                    }}
+                ],
+                "rendered": null
+            }}"##,
+            line = line,
+        ))
+    };
+    let other = parse_diagnostic(
+        r##"{
+            "message": "unused variable: `bar`",
+            "code": { "code": "unused_variables", "explanation": null },
+            "level": "warning",
+            "spans": [
+                {
+                    "file_name": "src/lib.rs",
+                    "byte_start": 0,
+                    "byte_end": 3,
+                    "line_start": 3,
+                    "line_end": 3,
+                    "column_start": 9,
+                    "column_end": 12,
+                    "is_primary": true,
+                    "text": [{ "text": "    let bar = 42;", "highlight_start": 9, "highlight_end": 12 }],
+                    "label": null,
+                    "suggested_replacement": null,
+                    "suggestion_applicability": null,
+                    "expansion": null
+                }
+            ],
+            "children": [],
+            "rendered": null
+        }"##,
+    );
+
+    let workspace_root = PathBuf::from("/test/");
+    let diags = [diag_at(2), other, diag_at(7)];
+    let mapped = map_rust_diagnostics_to_lsp(&diags, &workspace_root);
+    assert_eq!(mapped.len(), 2);
+
+    let first = &mapped[0];
+    assert_eq!(first.diagnostic.range.start.line, 1);
+    let related = first.diagnostic.related_information.as_ref().unwrap();
+    assert_eq!(related.len(), 1);
+    assert_eq!(related[0].location.range.start.line, 6);
+    assert_eq!(related[0].message, "Same diagnostic here");
+    assert_eq!(first.fixes.len(), 2);
+
+    assert_eq!(mapped[1].diagnostic.message, "unused variable: `bar`");
+    assert!(mapped[1].diagnostic.related_information.is_none());
+}
@@ -1,7 +1,7 @@
 //! cargo_check provides the functionality needed to run `cargo check` or
 //! another compatible command (f.x. clippy) in a background thread and provide
 //! LSP diagnostics based on the output of the command.
-use cargo_metadata::Message;
+use cargo_metadata::{diagnostic::Diagnostic as RustDiagnostic, Message};
 use crossbeam_channel::{never, select, unbounded, Receiver, RecvError, Sender};
 use lsp_types::{
     CodeAction, CodeActionOrCommand, Diagnostic, Url, WorkDoneProgress, WorkDoneProgressBegin,
@@ -17,7 +17,7 @@ use std::{
 
 mod conv;
 
-use crate::conv::{map_rust_diagnostic_to_lsp, MappedRustDiagnostic};
+use crate::conv::{map_rust_diagnostic_to_lsp, map_rust_diagnostics_to_lsp, MappedRustDiagnostic};
 
 pub use crate::conv::url_from_path_with_drive_lowercasing;
 
@@ -27,6 +27,9 @@ pub struct CheckOptions {
     pub args: Vec<String>,
     pub command: String,
     pub all_targets: bool,
+    /// Hold back diagnostics until the check finishes and merge the ones that
+    /// only differ in their location into a single diagnostic.
+    pub collapse_duplicates: bool,
 }
 
 /// CheckWatcher wraps the shared state and communication machinery used for
@@ -104,6 +107,7 @@ struct CheckWatcherThread {
     workspace_root: PathBuf,
     watcher: WatchThread,
     last_update_req: Option<Instant>,
+    pending_diagnostics: Vec<RustDiagnostic>,
 }
 
 impl CheckWatcherThread {
@@ -113,6 +117,7 @@ impl CheckWatcherThread {
             workspace_root,
             watcher: WatchThread::dummy(),
             last_update_req: None,
+            pending_diagnostics: Vec::new(),
         }
     }
 
@@ -138,6 +143,7 @@ impl CheckWatcherThread {
 
             if self.should_recheck() {
                 self.last_update_req.take();
+                self.pending_diagnostics.clear();
                 task_send.send(CheckTask::ClearDiagnostics).unwrap();
 
                 // Replace with a dummy watcher first so we drop the original and wait for completion
@@ -165,7 +171,7 @@ impl CheckWatcherThread {
         }
     }
 
-    fn handle_message(&self, msg: CheckEvent, task_send: &Sender<CheckTask>) {
+    fn handle_message(&mut self, msg: CheckEvent, task_send: &Sender<CheckTask>) {
         match msg {
             CheckEvent::Begin => {
                 task_send
@@ -179,6 +185,11 @@ impl CheckWatcherThread {
             }
 
             CheckEvent::End => {
+                let pending = std::mem::replace(&mut self.pending_diagnostics, Vec::new());
+                for map_result in map_rust_diagnostics_to_lsp(&pending, &self.workspace_root) {
+                    send_diagnostic(map_result, task_send);
+                }
+
                 task_send
                     .send(CheckTask::Status(WorkDoneProgress::End(WorkDoneProgressEnd {
                         message: None,
@@ -197,23 +208,16 @@ impl CheckWatcherThread {
             }
 
             CheckEvent::Msg(Message::CompilerMessage(msg)) => {
-                let map_result =
-                    match map_rust_diagnostic_to_lsp(&msg.message, &self.workspace_root) {
-                        Some(map_result) => map_result,
-                        None => return,
-                    };
-
-                let MappedRustDiagnostic { location, diagnostic, fixes } = map_result;
-                let fixes = fixes
-                    .into_iter()
-                    .map(|fix| {
-                        CodeAction { diagnostics: Some(vec![diagnostic.clone()]), ..fix }.into()
-                    })
-                    .collect();
+                if self.options.collapse_duplicates {
+                    self.pending_diagnostics.push(msg.message);
+                    return;
+                }
 
-                task_send
-                    .send(CheckTask::AddDiagnostic { url: location.uri, diagnostic, fixes })
-                    .unwrap();
+                if let Some(map_result) =
+                    map_rust_diagnostic_to_lsp(&msg.message, &self.workspace_root)
+                {
+                    send_diagnostic(map_result, task_send);
+                }
             }
 
             CheckEvent::Msg(Message::BuildScriptExecuted(_msg)) => {}
@@ -222,6 +226,17 @@ impl CheckWatcherThread {
     }
 }
 
+fn send_diagnostic(map_result: MappedRustDiagnostic, task_send: &Sender<CheckTask>) {
+    // LSP diagnostics have no field to carry rustc's rendering to the client
+    let MappedRustDiagnostic { location, diagnostic, fixes, rendered: _ } = map_result;
+    let fixes = fixes
+        .into_iter()
+        .map(|fix| CodeAction { diagnostics: Some(vec![diagnostic.clone()]), ..fix }.into())
+        .collect();
+
+    task_send.send(CheckTask::AddDiagnostic { url: location.uri, diagnostic, fixes }).unwrap();
+}
+
 #[derive(Debug)]
 pub struct DiagnosticWithFixes {
     diagnostic: Diagnostic,
@@ -148,6 +148,10 @@ impl CrateGraph {
         &self.arena[&crate_id].cfg_options
     }
 
+    pub fn env(&self, crate_id: CrateId) -> &Env {
+        &self.arena[&crate_id].env
+    }
+
     pub fn add_dep(
         &mut self,
         from: CrateId,
@@ -264,6 +268,16 @@ impl fmt::Display for Edition {
     }
 }
 
+impl Env {
+    pub fn set(&mut self, env: &str, value: String) {
+        self.entries.insert(env.to_owned(), value);
+    }
+
+    pub fn get(&self, env: &str) -> Option<&str> {
+        self.entries.get(env).map(|it| it.as_str())
+    }
+}
+
 impl Dependency {
     pub fn crate_id(&self) -> CrateId {
         self.crate_id
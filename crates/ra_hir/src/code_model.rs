@@ -11,9 +11,9 @@ use hir_def::{
     per_ns::PerNs,
     resolver::HasResolver,
     type_ref::{Mutability, TypeRef},
-    AdtId, AssocContainerId, ConstId, DefWithBodyId, EnumId, FunctionId, GenericDefId, HasModule,
-    ImplId, LocalEnumVariantId, LocalModuleId, LocalStructFieldId, Lookup, ModuleId, StaticId,
-    StructId, TraitId, TypeAliasId, TypeParamId, UnionId,
+    AdtId, AssocContainerId, ConstId, ConstParamId, DefWithBodyId, EnumId, FunctionId,
+    GenericDefId, HasModule, ImplId, LocalEnumVariantId, LocalModuleId, LocalStructFieldId, Lookup,
+    ModuleId, StaticId, StructId, TraitId, TypeAliasId, TypeParamId, UnionId,
 };
 use hir_expand::{
     diagnostics::DiagnosticSink,
@@ -64,7 +64,6 @@ impl Crate {
             .collect()
     }
 
-    // FIXME: add `transitive_reverse_dependencies`.
     pub fn reverse_dependencies(self, db: &impl DefDatabase) -> Vec<Crate> {
         let crate_graph = db.crate_graph();
         crate_graph
@@ -74,11 +73,32 @@ impl Crate {
             .collect()
     }
 
+    /// All the crates depending on this one, directly or indirectly.
+    pub fn transitive_reverse_dependencies(self, db: &impl DefDatabase) -> Vec<Crate> {
+        let mut res = Vec::new();
+        let mut queue = vec![self];
+        while let Some(krate) = queue.pop() {
+            for rev_dep in krate.reverse_dependencies(db) {
+                if !res.contains(&rev_dep) {
+                    res.push(rev_dep);
+                    queue.push(rev_dep);
+                }
+            }
+        }
+        res
+    }
+
     pub fn root_module(self, db: &impl DefDatabase) -> Option<Module> {
         let module_id = db.crate_def_map(self.id).root;
         Some(Module::new(self, module_id))
     }
 
+    /// The prelude module, whose items are in scope everywhere in the crate
+    /// without being imported.
+    pub fn prelude(self, db: &impl DefDatabase) -> Option<Module> {
+        db.crate_def_map(self.id).prelude().map(|id| Module { id })
+    }
+
     pub fn root_file(self, db: &impl DefDatabase) -> FileId {
         db.crate_graph().crate_root(self.id)
     }
@@ -347,6 +367,10 @@ impl Struct {
         Type::from_def(db, self.id.lookup(db).container.module(db).krate, self.id)
     }
 
+    pub fn kind(self, db: &impl HirDatabase) -> StructKind {
+        self.variant_data(db).kind()
+    }
+
     fn variant_data(self, db: &impl DefDatabase) -> Arc<VariantData> {
         db.struct_data(self.id).variant_data.clone()
     }
@@ -558,6 +582,10 @@ impl Function {
         db.function_data(self.id).has_self_param
     }
 
+    pub fn is_unsafe(self, db: &impl HirDatabase) -> bool {
+        db.function_data(self.id).is_unsafe
+    }
+
     pub fn params(self, db: &impl HirDatabase) -> Vec<TypeRef> {
         db.function_data(self.id).params.clone()
     }
@@ -568,6 +596,14 @@ impl Function {
         infer.add_diagnostics(db, self.id, sink);
         let mut validator = ExprValidator::new(self.id, infer, sink);
         validator.validate_body(db);
+        validator.validate_self_param_usage(db);
+    }
+}
+
+impl HasVisibility for Function {
+    fn visibility(&self, db: &impl HirDatabase) -> Visibility {
+        let function_data = db.function_data(self.id);
+        function_data.visibility.resolve(db, &self.id.resolver(db))
     }
 }
 
@@ -850,6 +886,22 @@ impl TypeParam {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ConstParam {
+    pub(crate) id: ConstParamId,
+}
+
+impl ConstParam {
+    pub fn name(self, db: &impl HirDatabase) -> Name {
+        let params = db.generic_params(self.id.parent);
+        params.consts[self.id.local_id].name.clone()
+    }
+
+    pub fn module(self, db: &impl HirDatabase) -> Module {
+        self.id.parent.module(db).into()
+    }
+}
+
 // FIXME: rename from `ImplBlock` to `Impl`
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ImplBlock {
@@ -870,6 +922,11 @@ impl ImplBlock {
         db.impl_data(self.id).target_trait.clone()
     }
 
+    /// Returns the trait implemented by this block, if it is a trait impl.
+    pub fn target_trait_def(&self, db: &impl HirDatabase) -> Option<Trait> {
+        db.impl_trait(self.id).map(|it| Trait { id: it.value.trait_ })
+    }
+
     pub fn target_type(&self, db: &impl DefDatabase) -> TypeRef {
         db.impl_data(self.id).target_type.clone()
     }
@@ -957,6 +1014,46 @@ impl Type {
         }
     }
 
+    pub fn is_integral(&self) -> bool {
+        match &self.ty.value {
+            Ty::Apply(a_ty) => match a_ty.ctor {
+                TypeCtor::Int(_) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        match &self.ty.value {
+            Ty::Apply(a_ty) => match a_ty.ctor {
+                TypeCtor::Float(_) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    pub fn is_str(&self) -> bool {
+        match &self.ty.value {
+            Ty::Apply(a_ty) => match a_ty.ctor {
+                TypeCtor::Str => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    pub fn is_raw_ptr(&self) -> bool {
+        match &self.ty.value {
+            Ty::Apply(a_ty) => match a_ty.ctor {
+                TypeCtor::RawPtr(_) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
     pub fn is_mutable_reference(&self) -> bool {
         match &self.ty.value {
             Ty::Apply(a_ty) => match a_ty.ctor {
@@ -1001,6 +1098,12 @@ impl Type {
         Some(self.ty.value.as_callable()?.0)
     }
 
+    /// Whether the type is fully known, so that e.g. its methods can be
+    /// enumerated reliably.
+    pub fn is_resolved(&self) -> bool {
+        self.ty.value.is_resolved()
+    }
+
     pub fn contains_unknown(&self) -> bool {
         return go(&self.ty.value);
 
@@ -1103,6 +1206,7 @@ impl Type {
         db: &impl HirDatabase,
         krate: Crate,
         traits_in_scope: &FxHashSet<TraitId>,
+        from_module: Option<Module>,
         name: Option<&Name>,
         mut callback: impl FnMut(&Ty, Function) -> Option<T>,
     ) -> Option<T> {
@@ -1123,7 +1227,13 @@ impl Type {
             name,
             method_resolution::LookupMode::MethodCall,
             |ty, it| match it {
-                AssocItemId::FunctionId(f) => callback(ty, f.into()),
+                AssocItemId::FunctionId(f) => {
+                    let func = Function::from(f);
+                    if from_module.map_or(false, |m| !func.is_visible_from(db, m)) {
+                        return None;
+                    }
+                    callback(ty, func)
+                }
                 _ => None,
             },
         )
@@ -1162,6 +1272,16 @@ impl Type {
         Some(adt.into())
     }
 
+    /// For `&T` and `&mut T`, returns `T`.
+    pub fn remove_ref(&self) -> Option<Type> {
+        match &self.ty.value {
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Ref(_), parameters }) => {
+                Some(self.derived(parameters.as_single().clone()))
+            }
+            _ => None,
+        }
+    }
+
     // FIXME: provide required accessors such that it becomes implementable from outside.
     pub fn is_equal_for_find_impls(&self, other: &Type) -> bool {
         match (&self.ty.value, &other.ty.value) {
@@ -9,8 +9,8 @@ use hir_def::{
 use ra_syntax::ast;
 
 use crate::{
-    db::DefDatabase, Const, Enum, EnumVariant, FieldSource, Function, ImplBlock, MacroDef, Module,
-    Static, Struct, StructField, Trait, TypeAlias, TypeParam, Union,
+    db::DefDatabase, Const, ConstParam, Enum, EnumVariant, FieldSource, Function, ImplBlock,
+    MacroDef, Module, Static, Struct, StructField, Trait, TypeAlias, TypeParam, Union,
 };
 
 pub use hir_expand::InFile;
@@ -125,3 +125,10 @@ impl HasSource for TypeParam {
         child_source.map(|it| it[self.id.local_id].clone())
     }
 }
+
+impl HasSource for ConstParam {
+    type Ast = ast::ConstParam;
+    fn source(self, db: &impl DefDatabase) -> InFile<ast::ConstParam> {
+        self.id.source(db)
+    }
+}
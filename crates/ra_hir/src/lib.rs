@@ -39,10 +39,10 @@ mod has_source;
 
 pub use crate::{
     code_model::{
-        Adt, AsAssocItem, AssocItem, AssocItemContainer, AttrDef, Const, Crate, CrateDependency,
-        DefWithBody, Docs, Enum, EnumVariant, FieldSource, Function, GenericDef, HasAttrs,
-        HasVisibility, ImplBlock, Local, MacroDef, Module, ModuleDef, ScopeDef, Static, Struct,
-        StructField, Trait, Type, TypeAlias, TypeParam, Union, VariantDef,
+        Adt, AsAssocItem, AssocItem, AssocItemContainer, AttrDef, Const, ConstParam, Crate,
+        CrateDependency, DefWithBody, Docs, Enum, EnumVariant, FieldSource, Function, GenericDef,
+        HasAttrs, HasVisibility, ImplBlock, Local, MacroDef, Module, ModuleDef, ScopeDef, Static,
+        Struct, StructField, Trait, Type, TypeAlias, TypeParam, Union, VariantDef,
     },
     has_source::HasSource,
     source_analyzer::{PathResolution, ScopeEntryWithSyntax, SourceAnalyzer},
@@ -61,4 +61,7 @@ pub use hir_def::{
 pub use hir_expand::{
     name::Name, HirFileId, InFile, MacroCallId, MacroCallLoc, MacroDefId, MacroFile, Origin,
 };
-pub use hir_ty::{display::HirDisplay, CallableDef};
+pub use hir_ty::{
+    display::{DisplayOptions, HirDisplay},
+    CallableDef,
+};
@@ -13,12 +13,13 @@ use hir_def::{
         scope::{ExprScopes, ScopeId},
         BodySourceMap,
     },
-    expr::{ExprId, PatId},
+    expr::{BinaryOp, ExprId, PatId},
+    lang_item::LangItemTarget,
     resolver::{self, resolver_for_scope, Resolver, TypeNs, ValueNs},
     AsMacroCall, DefWithBodyId, TraitId,
 };
 use hir_expand::{hygiene::Hygiene, name::AsName, HirFileId, InFile, MacroCallId};
-use hir_ty::{InEnvironment, InferenceResult, TraitEnvironment};
+use hir_ty::{op, InEnvironment, InferenceResult, TraitEnvironment};
 use ra_syntax::{
     ast::{self, AstNode},
     AstPtr, SyntaxNode, SyntaxNodePtr, SyntaxToken, TextRange, TextUnit,
@@ -26,8 +27,8 @@ use ra_syntax::{
 use rustc_hash::FxHashSet;
 
 use crate::{
-    db::HirDatabase, Adt, Const, DefWithBody, EnumVariant, Function, Local, MacroDef, Name, Path,
-    ScopeDef, Static, Struct, Trait, Type, TypeAlias, TypeParam,
+    db::HirDatabase, Adt, Const, ConstParam, DefWithBody, EnumVariant, Function, Local, MacroDef,
+    Name, Path, ScopeDef, Static, Struct, Trait, Type, TypeAlias, TypeParam,
 };
 
 /// `SourceAnalyzer` is a convenience wrapper which exposes HIR API in terms of
@@ -50,6 +51,8 @@ pub enum PathResolution {
     Local(Local),
     /// A generic parameter
     TypeParam(TypeParam),
+    /// A const generic parameter (only value namespace)
+    ConstParam(ConstParam),
     SelfType(crate::ImplBlock),
     Macro(MacroDef),
     AssocItem(crate::AssocItem),
@@ -204,6 +207,39 @@ impl SourceAnalyzer {
         self.infer.as_ref()?.method_resolution(expr_id).map(Function::from)
     }
 
+    /// Resolves the trait method an overloaded binary operator dispatches to,
+    /// e.g. `Add::add` for `a + b`, if an applicable impl exists.
+    pub fn resolve_bin_expr(
+        &self,
+        db: &impl HirDatabase,
+        bin_expr: &ast::BinExpr,
+    ) -> Option<Function> {
+        let op = BinaryOp::from(bin_expr.op_kind()?);
+        let infer = self.infer.as_ref()?;
+        let lhs_ty = &infer[self.expr_id(&bin_expr.lhs()?)?];
+        let rhs_ty = &infer[self.expr_id(&bin_expr.rhs()?)?];
+        let krate = self.resolver.krate()?;
+        let env = self.trait_env(db);
+        let (_, func, _) = op::resolve_binary_op(db, krate, &env, op, lhs_ty, rhs_ty)?;
+        Some(func.into())
+    }
+
+    /// Like `resolve_bin_expr`, but for prefix operators like `-a`.
+    pub fn resolve_prefix_expr(
+        &self,
+        db: &impl HirDatabase,
+        prefix_expr: &ast::PrefixExpr,
+    ) -> Option<Function> {
+        let (lang_item, method) = op::unary_op_trait_method(prefix_expr.op_kind()?)?;
+        let ty = &self.infer.as_ref()?[self.expr_id(&prefix_expr.expr()?)?];
+        let krate = self.resolver.krate()?;
+        let trait_ = match db.lang_item(krate, lang_item.into())? {
+            LangItemTarget::TraitId(it) => it,
+            _ => return None,
+        };
+        op::find_op_impl_method(db, krate, trait_, method, ty, None).map(Function::from)
+    }
+
     pub fn resolve_field(&self, field: &ast::FieldExpr) -> Option<crate::StructField> {
         let expr_id = self.expr_id(&field.clone().into())?;
         self.infer.as_ref()?.field_resolution(expr_id).map(|it| it.into())
@@ -269,6 +305,7 @@ impl SourceAnalyzer {
                     ValueNs::StaticId(it) => PathResolution::Def(Static::from(it).into()),
                     ValueNs::StructId(it) => PathResolution::Def(Struct::from(it).into()),
                     ValueNs::EnumVariantId(it) => PathResolution::Def(EnumVariant::from(it).into()),
+                    ValueNs::ConstParam(id) => PathResolution::ConstParam(ConstParam { id }),
                 };
                 Some(res)
             });
@@ -287,9 +324,12 @@ impl SourceAnalyzer {
 
     pub fn resolve_path(&self, db: &impl HirDatabase, path: &ast::Path) -> Option<PathResolution> {
         if let Some(path_expr) = path.syntax().parent().and_then(ast::PathExpr::cast) {
-            let expr_id = self.expr_id(&path_expr.into())?;
-            if let Some(assoc) = self.infer.as_ref()?.assoc_resolutions_for_expr(expr_id) {
-                return Some(PathResolution::AssocItem(assoc.into()));
+            // Path expressions outside of bodies, like array lengths in
+            // signatures, are resolved by the resolver alone
+            if let Some(expr_id) = self.expr_id(&path_expr.into()) {
+                if let Some(assoc) = self.infer.as_ref()?.assoc_resolutions_for_expr(expr_id) {
+                    return Some(PathResolution::AssocItem(assoc.into()));
+                }
             }
         }
         if let Some(path_pat) = path.syntax().parent().and_then(ast::PathPat::cast) {
@@ -17,7 +17,7 @@ use ra_syntax::{
 };
 use rustc_hash::FxHashMap;
 
-use crate::{db::HirDatabase, Local, Module, SourceAnalyzer, TypeParam};
+use crate::{db::HirDatabase, ConstParam, Local, Module, SourceAnalyzer, TypeParam};
 use ra_db::FileId;
 
 pub struct SourceBinder<'a, DB> {
@@ -300,26 +300,48 @@ impl ToDef for ast::TypeParam {
         src: InFile<ast::TypeParam>,
     ) -> Option<TypeParam> {
         let mut sb = SourceBinder::new(sb.db);
-        let file_id = src.file_id;
-        let parent: GenericDefId = src.value.syntax().ancestors().find_map(|it| {
-            let res = match_ast! {
-                match it {
-                    ast::FnDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
-                    ast::StructDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
-                    ast::EnumDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
-                    ast::TraitDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
-                    ast::TypeAliasDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
-                    ast::ImplBlock(value) => { sb.to_id(InFile { value, file_id})?.into() },
-                    _ => return None,
-                }
-            };
-            Some(res)
-        })?;
+        let parent = generic_param_parent(&mut sb, src.as_ref().map(|it| it.syntax()))?;
         let &id = sb.child_by_source(parent.into())[keys::TYPE_PARAM].get(&src)?;
         Some(TypeParam { id })
     }
 }
 
+impl ToDef for ast::ConstParam {
+    type Def = ConstParam;
+
+    fn to_def<DB: HirDatabase>(
+        sb: &mut SourceBinder<'_, DB>,
+        src: InFile<ast::ConstParam>,
+    ) -> Option<ConstParam> {
+        let mut sb = SourceBinder::new(sb.db);
+        let parent = generic_param_parent(&mut sb, src.as_ref().map(|it| it.syntax()))?;
+        let &id = sb.child_by_source(parent.into())[keys::CONST_PARAM].get(&src)?;
+        Some(ConstParam { id })
+    }
+}
+
+/// Finds the item declaring the generic parameter `param`.
+fn generic_param_parent<DB: HirDatabase>(
+    sb: &mut SourceBinder<'_, DB>,
+    param: InFile<&SyntaxNode>,
+) -> Option<GenericDefId> {
+    let file_id = param.file_id;
+    param.value.ancestors().find_map(|it| {
+        let res = match_ast! {
+            match it {
+                ast::FnDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
+                ast::StructDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
+                ast::EnumDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
+                ast::TraitDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
+                ast::TypeAliasDef(value) => { sb.to_id(InFile { value, file_id})?.into() },
+                ast::ImplBlock(value) => { sb.to_id(InFile { value, file_id})?.into() },
+                _ => return None,
+            }
+        };
+        Some(res)
+    })
+}
+
 impl ToId for ast::Module {
     type ID = ModuleId;
 
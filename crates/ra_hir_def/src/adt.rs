@@ -10,11 +10,12 @@ use hir_expand::{
 use ra_arena::{map::ArenaMap, Arena};
 use ra_prof::profile;
 use ra_syntax::ast::{self, NameOwner, TypeAscriptionOwner, VisibilityOwner};
+use tt::{Leaf, TokenTree};
 
 use crate::{
-    db::DefDatabase, src::HasChildSource, src::HasSource, trace::Trace, type_ref::TypeRef,
-    visibility::RawVisibility, EnumId, LocalEnumVariantId, LocalStructFieldId, Lookup, StructId,
-    UnionId, VariantId,
+    attr::Attrs, builtin_type::BuiltinInt, db::DefDatabase, src::HasChildSource, src::HasSource,
+    trace::Trace, type_ref::TypeRef, visibility::RawVisibility, AdtId, EnumId, LocalEnumVariantId,
+    LocalStructFieldId, Lookup, StructId, UnionId, VariantId,
 };
 
 /// Note that we use `StructData` for unions as well!
@@ -22,12 +23,64 @@ use crate::{
 pub struct StructData {
     pub name: Name,
     pub variant_data: Arc<VariantData>,
+    pub repr: Option<ReprData>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct EnumData {
     pub name: Name,
     pub variants: Arena<LocalEnumVariantId, EnumVariantData>,
+    pub repr: Option<ReprData>,
+}
+
+/// The `#[repr(...)]` of an ADT. Several `repr` attributes are merged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReprData {
+    pub c: bool,
+    pub transparent: bool,
+    /// The alignment of `packed(N)`; plain `packed` means `packed(1)`.
+    pub packed: Option<u32>,
+    /// The discriminant type of `repr(u8)` and friends.
+    pub int: Option<BuiltinInt>,
+}
+
+impl ReprData {
+    fn from_attrs(attrs: &Attrs) -> Option<ReprData> {
+        let mut tts = attrs.by_key("repr").tt_values().peekable();
+        tts.peek()?;
+        let mut res = ReprData::default();
+        for tt in tts {
+            let mut token_trees = tt.token_trees.iter().peekable();
+            while let Some(token_tree) = token_trees.next() {
+                let ident = match token_tree {
+                    TokenTree::Leaf(Leaf::Ident(ident)) => ident.text.as_str(),
+                    _ => continue,
+                };
+                match ident {
+                    "C" => res.c = true,
+                    "transparent" => res.transparent = true,
+                    "packed" => {
+                        let mut align = 1;
+                        if let Some(TokenTree::Subtree(args)) = token_trees.peek() {
+                            if let Some(TokenTree::Leaf(Leaf::Literal(lit))) =
+                                args.token_trees.first()
+                            {
+                                align = lit.text.parse().unwrap_or(1);
+                            }
+                            token_trees.next();
+                        }
+                        res.packed = Some(align);
+                    }
+                    _ => {
+                        if let Some(int) = BuiltinInt::from_suffix(ident) {
+                            res.int = Some(int);
+                        }
+                    }
+                }
+            }
+        }
+        Some(res)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -57,7 +110,8 @@ impl StructData {
         let name = src.value.name().map_or_else(Name::missing, |n| n.as_name());
         let variant_data = VariantData::new(db, src.map(|s| s.kind()));
         let variant_data = Arc::new(variant_data);
-        Arc::new(StructData { name, variant_data })
+        let repr = ReprData::from_attrs(&db.attrs(AdtId::StructId(id).into()));
+        Arc::new(StructData { name, variant_data, repr })
     }
     pub(crate) fn union_data_query(db: &impl DefDatabase, id: UnionId) -> Arc<StructData> {
         let src = id.lookup(db).source(db);
@@ -71,7 +125,8 @@ impl StructData {
             }),
         );
         let variant_data = Arc::new(variant_data);
-        Arc::new(StructData { name, variant_data })
+        let repr = ReprData::from_attrs(&db.attrs(AdtId::UnionId(id).into()));
+        Arc::new(StructData { name, variant_data, repr })
     }
 }
 
@@ -82,7 +137,8 @@ impl EnumData {
         let name = src.value.name().map_or_else(Name::missing, |n| n.as_name());
         let mut trace = Trace::new_for_arena();
         lower_enum(db, &mut trace, &src);
-        Arc::new(EnumData { name, variants: trace.into_arena() })
+        let repr = ReprData::from_attrs(&db.attrs(AdtId::EnumId(e).into()));
+        Arc::new(EnumData { name, variants: trace.into_arena(), repr })
     }
 
     pub fn variant(&self, name: &Name) -> Option<LocalEnumVariantId> {
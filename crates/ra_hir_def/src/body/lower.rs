@@ -183,7 +183,14 @@ where
                 let body = self.collect_block_opt(e.body());
                 self.alloc_expr(Expr::TryBlock { body }, syntax_ptr)
             }
-            ast::Expr::BlockExpr(e) => self.collect_block(e),
+            ast::Expr::BlockExpr(e) => {
+                if e.is_unsafe() {
+                    let body = self.collect_block(e);
+                    self.alloc_expr(Expr::Unsafe { body }, syntax_ptr)
+                } else {
+                    self.collect_block(e)
+                }
+            }
             ast::Expr::LoopExpr(e) => {
                 let body = self.collect_block_opt(e.loop_body());
                 self.alloc_expr(Expr::Loop { body }, syntax_ptr)
@@ -415,7 +422,7 @@ where
                     LiteralKind::IntNumber { suffix } => {
                         let known_name = suffix.and_then(|it| BuiltinInt::from_suffix(&it));
 
-                        Literal::Int(Default::default(), known_name)
+                        Literal::Int(e.int_value().unwrap_or_default(), known_name)
                     }
                     LiteralKind::FloatNumber { suffix } => {
                         let known_name = suffix.and_then(|it| BuiltinFloat::from_suffix(&it));
@@ -6,13 +6,19 @@ use hir_expand::{
     name::{name, AsName, Name},
     AstId, InFile,
 };
-use ra_syntax::ast::{self, AstNode, ImplItem, ModuleItemOwner, NameOwner, TypeAscriptionOwner};
+use ra_syntax::{
+    ast::{
+        self, AstNode, ImplItem, ModuleItemOwner, NameOwner, TypeAscriptionOwner, VisibilityOwner,
+    },
+    SyntaxKind,
+};
 
 use crate::{
     db::DefDatabase,
     path::{path, GenericArgs, Path},
     src::HasSource,
     type_ref::{Mutability, TypeBound, TypeRef},
+    visibility::RawVisibility,
     AssocContainerId, AssocItemId, ConstId, ConstLoc, Expander, FunctionId, FunctionLoc, HasModule,
     ImplId, Intern, Lookup, ModuleId, StaticId, TraitId, TypeAliasId, TypeAliasLoc,
 };
@@ -25,15 +31,26 @@ pub struct FunctionData {
     /// True if the first param is `self`. This is relevant to decide whether this
     /// can be called as a method.
     pub has_self_param: bool,
+    /// True for C-variadic functions (`fn printf(fmt: *const c_char, ...)`).
+    pub is_varargs: bool,
+    /// True for `unsafe fn`s, whose calls must be wrapped in an `unsafe` block.
+    pub is_unsafe: bool,
+    pub visibility: RawVisibility,
 }
 
 impl FunctionData {
     pub(crate) fn fn_data_query(db: &impl DefDatabase, func: FunctionId) -> Arc<FunctionData> {
-        let src = func.lookup(db).source(db);
+        let loc = func.lookup(db);
+        let src = loc.source(db);
         let name = src.value.name().map(|n| n.as_name()).unwrap_or_else(Name::missing);
         let mut params = Vec::new();
         let mut has_self_param = false;
+        let mut is_varargs = false;
         if let Some(param_list) = src.value.param_list() {
+            is_varargs = param_list
+                .syntax()
+                .children_with_tokens()
+                .any(|it| it.kind() == SyntaxKind::DOTDOTDOT);
             if let Some(self_param) = param_list.self_param() {
                 let self_type = if let Some(type_ref) = self_param.ascribed_type() {
                     TypeRef::from_ast(type_ref)
@@ -71,7 +88,33 @@ impl FunctionData {
             ret_type
         };
 
-        let sig = FunctionData { name, params, ret_type, has_self_param };
+        let visibility = match loc.container {
+            // Items of traits and trait impls inherit the visibility of the trait.
+            // FIXME: use the actual visibility of the trait
+            AssocContainerId::TraitId(_) => RawVisibility::Public,
+            AssocContainerId::ImplId(impl_id) if db.impl_data(impl_id).target_trait.is_some() => {
+                RawVisibility::Public
+            }
+            _ => RawVisibility::from_ast(db, src.as_ref().map(|it| it.visibility())),
+        };
+
+        // Functions declared in `extern` blocks are implicitly unsafe to call
+        let is_extern = src
+            .value
+            .syntax()
+            .parent()
+            .map_or(false, |it| it.kind() == SyntaxKind::EXTERN_ITEM_LIST);
+        let is_unsafe = src.value.is_unsafe() || is_extern;
+
+        let sig = FunctionData {
+            name,
+            params,
+            ret_type,
+            has_self_param,
+            is_varargs,
+            is_unsafe,
+            visibility,
+        };
         Arc::new(sig)
     }
 }
@@ -2,9 +2,12 @@
 
 use std::any::Any;
 
-use hir_expand::diagnostics::Diagnostic;
+use hir_expand::{
+    db::AstDatabase,
+    diagnostics::{AstDiagnostic, Diagnostic},
+};
 use ra_db::RelativePathBuf;
-use ra_syntax::{ast, AstPtr, SyntaxNodePtr};
+use ra_syntax::{ast, AstNode, AstPtr, SyntaxNodePtr};
 
 use hir_expand::{HirFileId, InFile};
 
@@ -26,3 +29,31 @@ impl Diagnostic for UnresolvedModule {
         self
     }
 }
+
+#[derive(Debug)]
+pub struct RedundantImport {
+    pub file: HirFileId,
+    pub use_tree: AstPtr<ast::UseTree>,
+}
+
+impl Diagnostic for RedundantImport {
+    fn message(&self) -> String {
+        "redundant import".to_string()
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.use_tree.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+impl AstDiagnostic for RedundantImport {
+    type AST = ast::UseTree;
+
+    fn ast(&self, db: &impl AstDatabase) -> Self::AST {
+        let root = db.parse_or_expand(self.file).unwrap();
+        let node = self.source().value.to_node(&root);
+        ast::UseTree::cast(node).unwrap()
+    }
+}
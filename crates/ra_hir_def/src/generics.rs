@@ -11,7 +11,7 @@ use hir_expand::{
 };
 use ra_arena::{map::ArenaMap, Arena};
 use ra_db::FileId;
-use ra_syntax::ast::{self, NameOwner, TypeBoundsOwner, TypeParamsOwner};
+use ra_syntax::ast::{self, NameOwner, TypeAscriptionOwner, TypeBoundsOwner, TypeParamsOwner};
 
 use crate::{
     child_by_source::ChildBySource,
@@ -21,7 +21,7 @@ use crate::{
     src::HasChildSource,
     src::HasSource,
     type_ref::{TypeBound, TypeRef},
-    AdtId, GenericDefId, LocalTypeParamId, Lookup, TypeParamId,
+    AdtId, ConstParamId, GenericDefId, LocalConstParamId, LocalTypeParamId, Lookup, TypeParamId,
 };
 
 /// Data about a generic parameter (to a function, struct, impl, ...).
@@ -32,6 +32,13 @@ pub struct TypeParamData {
     pub provenance: TypeParamProvenance,
 }
 
+/// Data about a const generic parameter, like `const N: usize`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ConstParamData {
+    pub name: Name,
+    pub ty: TypeRef,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum TypeParamProvenance {
     TypeParamList,
@@ -43,6 +50,7 @@ pub enum TypeParamProvenance {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct GenericParams {
     pub types: Arena<LocalTypeParamId, TypeParamData>,
+    pub consts: Arena<LocalConstParamId, ConstParamData>,
     // lifetimes: Arena<LocalLifetimeParamId, LifetimeParamData>,
     pub where_predicates: Vec<WherePredicate>,
 }
@@ -66,6 +74,12 @@ pub enum WherePredicateTarget {
 
 type SourceMap = ArenaMap<LocalTypeParamId, Either<ast::TraitDef, ast::TypeParam>>;
 
+#[derive(Default)]
+struct SourceMaps {
+    types: SourceMap,
+    consts: ArenaMap<LocalConstParamId, ast::ConstParam>,
+}
+
 impl GenericParams {
     pub(crate) fn generic_params_query(
         db: &impl DefDatabase,
@@ -75,9 +89,13 @@ impl GenericParams {
         Arc::new(params)
     }
 
-    fn new(db: &impl DefDatabase, def: GenericDefId) -> (GenericParams, InFile<SourceMap>) {
-        let mut generics = GenericParams { types: Arena::default(), where_predicates: Vec::new() };
-        let mut sm = ArenaMap::default();
+    fn new(db: &impl DefDatabase, def: GenericDefId) -> (GenericParams, InFile<SourceMaps>) {
+        let mut generics = GenericParams {
+            types: Arena::default(),
+            consts: Arena::default(),
+            where_predicates: Vec::new(),
+        };
+        let mut sm = SourceMaps::default();
         // FIXME: add `: Sized` bound for everything except for `Self` in traits
         let file_id = match def {
             GenericDefId::FunctionId(it) => {
@@ -114,7 +132,7 @@ impl GenericParams {
                     default: None,
                     provenance: TypeParamProvenance::TraitSelf,
                 });
-                sm.insert(self_param_id, Either::Left(src.value.clone()));
+                sm.types.insert(self_param_id, Either::Left(src.value.clone()));
                 // add super traits as bounds on Self
                 // i.e., trait Foo: Bar is equivalent to trait Foo where Self: Bar
                 let self_param = TypeRef::Path(name![Self].into());
@@ -143,7 +161,7 @@ impl GenericParams {
         (generics, InFile::new(file_id, sm))
     }
 
-    fn fill(&mut self, sm: &mut SourceMap, node: &dyn TypeParamsOwner) {
+    fn fill(&mut self, sm: &mut SourceMaps, node: &dyn TypeParamsOwner) {
         if let Some(params) = node.type_param_list() {
             self.fill_params(sm, params)
         }
@@ -160,7 +178,7 @@ impl GenericParams {
         }
     }
 
-    fn fill_params(&mut self, sm: &mut SourceMap, params: ast::TypeParamList) {
+    fn fill_params(&mut self, sm: &mut SourceMaps, params: ast::TypeParamList) {
         for type_param in params.type_params() {
             let name = type_param.name().map_or_else(Name::missing, |it| it.as_name());
             // FIXME: Use `Path::from_src`
@@ -171,11 +189,17 @@ impl GenericParams {
                 provenance: TypeParamProvenance::TypeParamList,
             };
             let param_id = self.types.alloc(param);
-            sm.insert(param_id, Either::Right(type_param.clone()));
+            sm.types.insert(param_id, Either::Right(type_param.clone()));
 
             let type_ref = TypeRef::Path(name.into());
             self.fill_bounds(&type_param, type_ref);
         }
+        for const_param in params.const_params() {
+            let name = const_param.name().map_or_else(Name::missing, |it| it.as_name());
+            let ty = TypeRef::from_ast_opt(const_param.ascribed_type());
+            let param_id = self.consts.alloc(ConstParamData { name, ty });
+            sm.consts.insert(param_id, const_param);
+        }
     }
 
     fn fill_where_predicates(&mut self, where_clause: ast::WhereClause) {
@@ -192,10 +216,6 @@ impl GenericParams {
     }
 
     fn add_where_predicate_from_bound(&mut self, bound: ast::TypeBound, type_ref: TypeRef) {
-        if bound.has_question_mark() {
-            // FIXME: remove this bound
-            return;
-        }
         let bound = TypeBound::from_ast(bound);
         self.where_predicates
             .push(WherePredicate { target: WherePredicateTarget::TypeRef(type_ref), bound });
@@ -226,6 +246,10 @@ impl GenericParams {
             .find_map(|(id, p)| if p.name.as_ref() == Some(name) { Some(id) } else { None })
     }
 
+    pub fn find_const_by_name(&self, name: &Name) -> Option<LocalConstParamId> {
+        self.consts.iter().find_map(|(id, p)| if &p.name == name { Some(id) } else { None })
+    }
+
     pub fn find_trait_self_param(&self) -> Option<LocalTypeParamId> {
         self.types.iter().find_map(|(id, p)| {
             if p.provenance == TypeParamProvenance::TraitSelf {
@@ -242,21 +266,33 @@ impl HasChildSource for GenericDefId {
     type Value = Either<ast::TraitDef, ast::TypeParam>;
     fn child_source(&self, db: &impl DefDatabase) -> InFile<SourceMap> {
         let (_, sm) = GenericParams::new(db, *self);
-        sm
+        sm.map(|it| it.types)
+    }
+}
+
+impl HasSource for ConstParamId {
+    type Value = ast::ConstParam;
+    fn source(&self, db: &impl DefDatabase) -> InFile<ast::ConstParam> {
+        let (_, sm) = GenericParams::new(db, self.parent);
+        sm.map(|it| it.consts[self.local_id].clone())
     }
 }
 
 impl ChildBySource for GenericDefId {
     fn child_by_source(&self, db: &impl DefDatabase) -> DynMap {
         let mut res = DynMap::default();
-        let arena_map = self.child_source(db);
-        let arena_map = arena_map.as_ref();
-        for (local_id, src) in arena_map.value.iter() {
+        let (_, sm) = GenericParams::new(db, *self);
+        let sm = sm.as_ref();
+        for (local_id, src) in sm.value.types.iter() {
             let id = TypeParamId { parent: *self, local_id };
             if let Either::Right(type_param) = src {
-                res[keys::TYPE_PARAM].insert(arena_map.with_value(type_param.clone()), id)
+                res[keys::TYPE_PARAM].insert(sm.with_value(type_param.clone()), id)
             }
         }
+        for (local_id, const_param) in sm.value.consts.iter() {
+            let id = ConstParamId { parent: *self, local_id };
+            res[keys::CONST_PARAM].insert(sm.with_value(const_param.clone()), id)
+        }
         res
     }
 }
@@ -104,7 +104,7 @@ impl ItemScope {
         }
     }
 
-    pub(crate) fn name_of(&self, item: ItemInNs) -> Option<(&Name, Visibility)> {
+    pub fn name_of(&self, item: ItemInNs) -> Option<(&Name, Visibility)> {
         for (name, per_ns) in &self.visible {
             if let Some(vis) = item.match_with(*per_ns) {
                 return Some((name, vis));
@@ -177,6 +177,16 @@ pub struct TypeParamId {
 pub struct LocalTypeParamId(RawId);
 impl_arena_id!(LocalTypeParamId);
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ConstParamId {
+    pub parent: GenericDefId,
+    pub local_id: LocalConstParamId,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalConstParamId(RawId);
+impl_arena_id!(LocalConstParamId);
+
 macro_rules! impl_froms {
     ($e:ident: $($v:ident $(($($sv:ident),*))?),*) => {
         $(
@@ -211,6 +211,12 @@ impl CrateDefMap {
         self.diagnostics.iter().for_each(|it| it.add_to(db, module, sink))
     }
 
+    /// The module whose items are implicitly in scope in every module of the
+    /// crate, usually `std::prelude::v1`.
+    pub fn prelude(&self) -> Option<ModuleId> {
+        self.prelude
+    }
+
     pub fn modules_for_file(&self, file_id: FileId) -> impl Iterator<Item = LocalModuleId> + '_ {
         self.modules
             .iter()
@@ -293,11 +299,17 @@ pub enum ModuleSource {
 }
 
 mod diagnostics {
-    use hir_expand::diagnostics::DiagnosticSink;
+    use hir_expand::{diagnostics::DiagnosticSink, hygiene::Hygiene, InFile};
     use ra_db::RelativePathBuf;
     use ra_syntax::{ast, AstPtr};
 
-    use crate::{db::DefDatabase, diagnostics::UnresolvedModule, nameres::LocalModuleId, AstId};
+    use crate::{
+        db::DefDatabase,
+        diagnostics::{RedundantImport, UnresolvedModule},
+        nameres::LocalModuleId,
+        path::ModPath,
+        AstId,
+    };
 
     #[derive(Debug, PartialEq, Eq)]
     pub(super) enum DefDiagnostic {
@@ -306,6 +318,11 @@ mod diagnostics {
             declaration: AstId<ast::Module>,
             candidate: RelativePathBuf,
         },
+        RedundantImport {
+            module: LocalModuleId,
+            import: AstId<ast::UseItem>,
+            use_tree_index: usize,
+        },
     }
 
     impl DefDiagnostic {
@@ -327,6 +344,25 @@ mod diagnostics {
                         candidate: candidate.clone(),
                     })
                 }
+                DefDiagnostic::RedundantImport { module, import, use_tree_index } => {
+                    if *module != target_module {
+                        return;
+                    }
+                    let use_item = import.to_node(db);
+                    let hygiene = Hygiene::new(db, import.file_id);
+                    let mut use_trees = Vec::new();
+                    ModPath::expand_use_item(
+                        InFile::new(import.file_id, use_item),
+                        &hygiene,
+                        |_path, use_tree, _is_glob, _alias| use_trees.push(use_tree.clone()),
+                    );
+                    if let Some(use_tree) = use_trees.get(*use_tree_index) {
+                        sink.push(RedundantImport {
+                            file: import.file_id,
+                            use_tree: AstPtr::new(use_tree),
+                        })
+                    }
+                }
             }
         }
     }
@@ -11,8 +11,8 @@ use hir_expand::{
 };
 use ra_cfg::CfgOptions;
 use ra_db::{CrateId, FileId};
-use ra_syntax::ast;
-use rustc_hash::FxHashMap;
+use ra_syntax::ast::{self, AstNode};
+use rustc_hash::{FxHashMap, FxHashSet};
 use test_utils::tested_by;
 
 use crate::{
@@ -32,9 +32,15 @@ use crate::{
 
 pub(super) fn collect_defs(db: &impl DefDatabase, mut def_map: CrateDefMap) -> CrateDefMap {
     let crate_graph = db.crate_graph();
+    let cfg_options = crate_graph.cfg_options(def_map.krate);
+    let no_std = is_no_std(db, crate_graph.crate_root(def_map.krate), cfg_options);
 
     // populate external prelude
     for dep in crate_graph.dependencies(def_map.krate) {
+        // `#![no_std]` crates only see `std` after an explicit `extern crate std;`
+        if no_std && dep.name == "std" {
+            continue;
+        }
         let dep_def_map = db.crate_def_map(dep.crate_id);
         log::debug!("crate dep {:?} -> {:?}", dep.name, dep.crate_id);
         def_map.extern_prelude.insert(
@@ -51,8 +57,6 @@ pub(super) fn collect_defs(db: &impl DefDatabase, mut def_map: CrateDefMap) -> C
         }
     }
 
-    let cfg_options = crate_graph.cfg_options(def_map.krate);
-
     let mut collector = DefCollector {
         db,
         def_map,
@@ -69,6 +73,43 @@ pub(super) fn collect_defs(db: &impl DefDatabase, mut def_map: CrateDefMap) -> C
     collector.finish()
 }
 
+/// Checks whether the crate root `file_id` has a `#![no_std]` attribute, either
+/// directly or through a `#![cfg_attr(..)]` that is enabled by `cfg_options`.
+fn is_no_std(db: &impl DefDatabase, file_id: FileId, cfg_options: &CfgOptions) -> bool {
+    db.parse(file_id)
+        .tree()
+        .syntax()
+        .children()
+        .filter_map(ast::Attr::cast)
+        .filter(|attr| attr.kind() == ast::AttrKind::Inner)
+        .any(|attr| match attr.as_simple_call() {
+            Some((name, args)) if name == "cfg_attr" => cfg_attr_enables_no_std(&args, cfg_options),
+            _ => attr.as_simple_atom().map_or(false, |name| name == "no_std"),
+        })
+}
+
+/// Checks the arguments of `#![cfg_attr(predicate, attrs..)]` for `no_std`
+/// among the attributes, and whether `predicate` holds.
+fn cfg_attr_enables_no_std(args: &ast::TokenTree, cfg_options: &CfgOptions) -> bool {
+    let (args, _) = match mbe::ast_to_token_tree(args) {
+        Some(it) => it,
+        None => return false,
+    };
+    let mut parts = args.token_trees.split(|it| match it {
+        tt::TokenTree::Leaf(tt::Leaf::Punct(punct)) => punct.char == ',',
+        _ => false,
+    });
+    let predicate = match parts.next() {
+        Some(it) => tt::Subtree { delimiter: None, token_trees: it.to_vec() },
+        None => return false,
+    };
+    let has_no_std = parts.any(|attr| match attr {
+        [tt::TokenTree::Leaf(tt::Leaf::Ident(ident))] => ident.text == "no_std",
+        _ => false,
+    });
+    has_no_std && cfg_options.is_cfg_enabled(&predicate) == Some(true)
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 enum PartialResolvedImport {
     /// None of any namespaces is resolved
@@ -92,6 +133,7 @@ impl PartialResolvedImport {
 #[derive(Clone, Debug, Eq, PartialEq)]
 struct ImportDirective {
     module_id: LocalModuleId,
+    file_id: HirFileId,
     import_id: raw::Import,
     import: raw::ImportData,
     status: PartialResolvedImport,
@@ -179,6 +221,8 @@ where
         for directive in unresolved_imports {
             self.record_resolved_import(&directive)
         }
+
+        self.report_redundant_imports();
     }
 
     /// Define a macro with `macro_rules`.
@@ -310,6 +354,18 @@ where
         }
     }
 
+    fn resolve_dependency(&self, name: &Name) -> PerNs {
+        let crate_graph = self.db.crate_graph();
+        match crate_graph.dependencies(self.def_map.krate).find(|dep| dep.as_name() == *name) {
+            Some(dep) => {
+                let dep_def_map = self.db.crate_def_map(dep.crate_id);
+                let module = ModuleId { krate: dep.crate_id, local_id: dep_def_map.root };
+                PerNs::types(module.into(), Visibility::Public)
+            }
+            None => PerNs::none(),
+        }
+    }
+
     fn resolve_import(
         &self,
         module_id: LocalModuleId,
@@ -317,12 +373,16 @@ where
     ) -> PartialResolvedImport {
         log::debug!("resolving import: {:?} ({:?})", import, self.def_map.edition);
         if import.is_extern_crate {
-            let res = self.def_map.resolve_name_in_extern_prelude(
-                &import
-                    .path
-                    .as_ident()
-                    .expect("extern crate should have been desugared to one-element path"),
-            );
+            let name = import
+                .path
+                .as_ident()
+                .expect("extern crate should have been desugared to one-element path");
+            let mut res = self.def_map.resolve_name_in_extern_prelude(name);
+            if res.is_none() {
+                // `std` is missing from the extern prelude of `#![no_std]`
+                // crates, but they can still link it
+                res = self.resolve_dependency(name);
+            }
             PartialResolvedImport::Resolved(res)
         } else {
             let res = self.def_map.resolve_path_fp_with_macro(
@@ -465,6 +525,83 @@ where
         }
     }
 
+    /// Reports private, non-renamed imports of a name which the prelude
+    /// already brings into scope with the same resolution, like
+    /// `use std::option::Option;`.
+    ///
+    /// This is deliberately conservative: modules with glob imports are
+    /// skipped, as an explicit import might disambiguate between the glob and
+    /// the prelude there.
+    fn report_redundant_imports(&mut self) {
+        let prelude = match self.def_map.prelude {
+            Some(it) => it,
+            None => return,
+        };
+        let prelude_def_map;
+        let prelude_scope = if prelude.krate == self.def_map.krate {
+            &self.def_map[prelude.local_id].scope
+        } else {
+            prelude_def_map = self.db.crate_def_map(prelude.krate);
+            &prelude_def_map[prelude.local_id].scope
+        };
+
+        let modules_with_globs: FxHashSet<LocalModuleId> = self
+            .resolved_imports
+            .iter()
+            .filter(|directive| directive.import.is_glob)
+            .map(|directive| directive.module_id)
+            .collect();
+
+        let mut redundant = Vec::new();
+        for directive in self.resolved_imports.iter() {
+            let module_id = directive.module_id;
+            let module = ModuleId { krate: self.def_map.krate, local_id: module_id };
+            let import = &directive.import;
+            let ast_id = match import.ast_id {
+                Some(it) => it,
+                None => continue,
+            };
+            if import.is_glob || modules_with_globs.contains(&module_id) || module == prelude {
+                continue;
+            }
+            let name = match (&import.alias, import.path.segments.last()) {
+                (None, Some(name)) => name,
+                _ => continue,
+            };
+            let vis = self.def_map.resolve_visibility(self.db, module_id, &import.visibility);
+            if vis != Some(Visibility::Module(module)) {
+                continue;
+            }
+
+            // Visibilities don't matter here, only what the name refers to.
+            let def = directive.status.namespaces().with_visibility(Visibility::Public);
+            if def.is_none() {
+                continue;
+            }
+            let from_prelude = prelude_scope
+                .get(name, BuiltinShadowMode::Other)
+                .with_visibility(Visibility::Public);
+            let in_module = self.def_map[module_id]
+                .scope
+                .get(name, BuiltinShadowMode::Other)
+                .with_visibility(Visibility::Public);
+            if def != from_prelude || def != in_module {
+                continue;
+            }
+
+            let diagnostic = DefDiagnostic::RedundantImport {
+                module: module_id,
+                import: AstId::new(directive.file_id, ast_id),
+                use_tree_index: import.use_tree_index,
+            };
+            // Indeterminate imports are resolved twice, so may be listed twice.
+            if !redundant.contains(&diagnostic) {
+                redundant.push(diagnostic);
+            }
+        }
+        self.def_map.diagnostics.extend(redundant);
+    }
+
     fn update(&mut self, module_id: LocalModuleId, resolutions: &[(Name, PerNs)], vis: Visibility) {
         self.update_recursive(module_id, resolutions, vis, 0)
     }
@@ -642,6 +779,7 @@ where
                     raw::RawItemKind::Import(import_id) => {
                         self.def_collector.unresolved_imports.push(ImportDirective {
                             module_id: self.module_id,
+                            file_id: self.file_id,
                             import_id,
                             import: self.raw_items[import_id].clone(),
                             status: PartialResolvedImport::Unresolved,
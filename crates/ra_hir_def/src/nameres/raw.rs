@@ -18,6 +18,7 @@ use ra_prof::profile;
 use ra_syntax::{
     ast::{self, AttrsOwner, NameOwner, VisibilityOwner},
     AstNode,
+    SyntaxKind::{EXTERN_BLOCK, EXTERN_ITEM_LIST},
 };
 use test_utils::tested_by;
 
@@ -154,6 +155,11 @@ pub struct ImportData {
     pub(super) is_extern_crate: bool,
     pub(super) is_macro_use: bool,
     pub(super) visibility: RawVisibility,
+    /// `None` for `extern crate` items.
+    pub(super) ast_id: Option<FileAstId<ast::UseItem>>,
+    /// The position of the use tree among the ones `ModPath::expand_use_item`
+    /// visits for the same `use` item.
+    pub(super) use_tree_index: usize,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -231,6 +237,22 @@ impl RawItemsCollector {
                 ast::ItemOrMacro::Item(item) => self.add_item(current_module, item),
             }
         }
+        // Extern blocks have no AST node of their own, so their functions and
+        // statics are collected from the syntax tree directly.
+        let extern_items = body
+            .syntax()
+            .children()
+            .filter(|it| it.kind() == EXTERN_BLOCK)
+            .flat_map(|block| block.children().filter(|it| it.kind() == EXTERN_ITEM_LIST))
+            .flat_map(|item_list| item_list.children().filter_map(ast::ModuleItem::cast));
+        for item in extern_items {
+            match item {
+                ast::ModuleItem::FnDef(_) | ast::ModuleItem::StaticDef(_) => {
+                    self.add_item(current_module, item)
+                }
+                _ => (),
+            }
+        }
     }
 
     fn add_item(&mut self, current_module: Option<Module>, item: ast::ModuleItem) {
@@ -324,8 +346,10 @@ impl RawItemsCollector {
         let is_prelude = use_item.has_atom_attr("prelude_import");
         let attrs = self.parse_attrs(&use_item);
         let visibility = RawVisibility::from_ast_with_hygiene(use_item.visibility(), &self.hygiene);
+        let ast_id = self.source_ast_id_map.ast_id(&use_item);
 
         let mut buf = Vec::new();
+        let mut use_tree_index = 0;
         ModPath::expand_use_item(
             InFile { value: use_item, file_id: self.file_id },
             &self.hygiene,
@@ -338,7 +362,10 @@ impl RawItemsCollector {
                     is_extern_crate: false,
                     is_macro_use: false,
                     visibility: visibility.clone(),
+                    ast_id: Some(ast_id),
+                    use_tree_index,
                 };
+                use_tree_index += 1;
                 buf.push(import_data);
             },
         );
@@ -370,6 +397,8 @@ impl RawItemsCollector {
                 is_extern_crate: true,
                 is_macro_use,
                 visibility,
+                ast_id: None,
+                use_tree_index: 0,
             };
             self.push_import(current_module, attrs, import_data);
         }
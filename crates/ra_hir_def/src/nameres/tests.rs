@@ -116,6 +116,26 @@ fn use_as() {
     );
 }
 
+#[test]
+fn extern_block_items() {
+    let map = def_map(
+        r#"
+        //- /lib.rs
+        extern "C" {
+            fn foo();
+            static BAR: u32;
+        }
+        "#,
+    );
+    assert_snapshot!(map,
+        @r###"
+   ⋮crate
+   ⋮BAR: v
+   ⋮foo: v
+    "###
+    );
+}
+
 #[test]
 fn use_trees() {
     let map = def_map(
@@ -204,6 +224,51 @@ fn std_prelude() {
     "###);
 }
 
+#[test]
+fn redundant_import_of_prelude_item() {
+    let map = compute_crate_def_map(
+        "
+        //- /main.rs crate:main deps:std
+        use std::option::Option;
+
+        //- /std.rs crate:std
+        #[prelude_import]
+        use prelude::*;
+        pub mod prelude {
+            pub use crate::option::Option;
+        }
+        pub mod option {
+            pub enum Option<T> { None, Some(T) }
+        }
+        ",
+    );
+    insta::assert_debug_snapshot!(map.diagnostics, @r###"
+    [
+        RedundantImport {
+            module: LocalModuleId(
+                0,
+            ),
+            import: InFile {
+                file_id: HirFileId(
+                    FileId(
+                        FileId(
+                            0,
+                        ),
+                    ),
+                ),
+                value: FileAstId {
+                    raw: ErasedFileAstId(
+                        0,
+                    ),
+                    _ty: PhantomData,
+                },
+            },
+            use_tree_index: 0,
+        },
+    ]
+    "###);
+}
+
 #[test]
 fn can_import_enum_variant() {
     covers!(can_import_enum_variant);
@@ -457,6 +522,76 @@ fn std_prelude_takes_precedence_above_core_prelude() {
     "###);
 }
 
+#[test]
+fn no_std_crate_uses_core_prelude() {
+    let map = def_map(
+        r#"
+        //- /main.rs crate:main deps:core,std
+        #![no_std]
+        use {Foo, Bar};
+        use std::prelude::Foo as StdFoo;
+
+        //- /std.rs crate:std deps:core
+        #[prelude_import]
+        pub use self::prelude::*;
+        pub mod prelude {
+            pub struct Foo;
+            pub use core::prelude::Bar;
+        }
+
+        //- /core.rs crate:core
+        #[prelude_import]
+        pub use self::prelude::*;
+        pub mod prelude {
+            pub struct Bar;
+        }
+        "#,
+    );
+
+    assert_snapshot!(map, @r###"
+        ⋮crate
+        ⋮Bar: t v
+        ⋮Foo: _
+        ⋮StdFoo: _
+    "###);
+}
+
+#[test]
+fn no_std_crate_can_link_std() {
+    let map = def_map(
+        r#"
+        //- /main.rs crate:main deps:core,std cfg:feature=foo
+        #![cfg_attr(feature = "foo", no_std)]
+        extern crate std;
+        use {Foo, Bar};
+        use std::prelude::Foo as StdFoo;
+
+        //- /std.rs crate:std deps:core
+        #[prelude_import]
+        pub use self::prelude::*;
+        pub mod prelude {
+            pub struct Foo;
+            pub use core::prelude::Bar;
+        }
+
+        //- /core.rs crate:core
+        #[prelude_import]
+        pub use self::prelude::*;
+        pub mod prelude {
+            pub struct Bar;
+        }
+        "#,
+    );
+
+    assert_snapshot!(map, @r###"
+        ⋮crate
+        ⋮Bar: t v
+        ⋮Foo: _
+        ⋮StdFoo: t v
+        ⋮std: t
+    "###);
+}
+
 #[test]
 fn cfg_not_test() {
     let map = def_map(
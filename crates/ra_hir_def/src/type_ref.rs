@@ -44,7 +44,8 @@ pub enum TypeRef {
     Path(Path),
     RawPtr(Box<TypeRef>, Mutability),
     Reference(Box<TypeRef>, Mutability),
-    Array(Box<TypeRef> /*, Expr*/),
+    /// An array type; the length is `None` if it isn't an integer literal.
+    Array(Box<TypeRef>, Option<u64>),
     Slice(Box<TypeRef>),
     /// A fn pointer. Last element of the vector is the return type.
     Fn(Vec<TypeRef>),
@@ -57,6 +58,9 @@ pub enum TypeRef {
 #[derive(Clone, PartialEq, Eq, Hash, Debug)]
 pub enum TypeBound {
     Path(Path),
+    /// A relaxed bound like `?Sized`, which removes an implicit bound instead
+    /// of adding one.
+    Maybe(Path),
     // also for<> bounds
     // also Lifetimes
     Error,
@@ -81,7 +85,11 @@ impl TypeRef {
                 TypeRef::RawPtr(Box::new(inner_ty), mutability)
             }
             ast::TypeRef::ArrayType(inner) => {
-                TypeRef::Array(Box::new(TypeRef::from_ast_opt(inner.type_ref())))
+                let len = match inner.expr() {
+                    Some(ast::Expr::Literal(lit)) => lit.int_value(),
+                    _ => None,
+                };
+                TypeRef::Array(Box::new(TypeRef::from_ast_opt(inner.type_ref())), len)
             }
             ast::TypeRef::SliceType(inner) => {
                 TypeRef::Slice(Box::new(TypeRef::from_ast_opt(inner.type_ref())))
@@ -134,7 +142,7 @@ impl TypeRef {
                 TypeRef::Fn(types) | TypeRef::Tuple(types) => types.iter().for_each(|t| go(t, f)),
                 TypeRef::RawPtr(type_ref, _)
                 | TypeRef::Reference(type_ref, _)
-                | TypeRef::Array(type_ref)
+                | TypeRef::Array(type_ref, _)
                 | TypeRef::Slice(type_ref) => go(&type_ref, f),
                 TypeRef::ImplTrait(bounds) | TypeRef::DynTrait(bounds) => {
                     for bound in bounds {
@@ -189,7 +197,11 @@ impl TypeBound {
                     Some(p) => p,
                     None => return TypeBound::Error,
                 };
-                TypeBound::Path(path)
+                if node.has_question_mark() {
+                    TypeBound::Maybe(path)
+                } else {
+                    TypeBound::Path(path)
+                }
             }
             ast::TypeBoundKind::ForType(_) | ast::TypeBoundKind::Lifetime(_) => TypeBound::Error,
         }
@@ -5,7 +5,7 @@
 
 use std::iter::successors;
 
-use hir_def::lang_item::LangItemTarget;
+use hir_def::{lang_item::LangItemTarget, type_ref::Mutability};
 use hir_expand::name::name;
 use log::{info, warn};
 use ra_db::CrateId;
@@ -14,7 +14,7 @@ use crate::{
     db::HirDatabase,
     traits::{InEnvironment, Solution},
     utils::generics,
-    Canonical, Substs, Ty, TypeWalk,
+    ApplicationTy, Canonical, Substs, Ty, TypeCtor, TypeWalk,
 };
 
 const AUTODEREF_RECURSION_LIMIT: usize = 10;
@@ -31,15 +31,54 @@ pub fn autoderef<'a>(
     .take(AUTODEREF_RECURSION_LIMIT)
 }
 
+/// How a type was dereferenced in a step of autoderef.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DerefKind {
+    /// A reference or raw pointer with the given mutability was dereferenced.
+    Builtin(Mutability),
+    /// The type was dereferenced through the `Deref` trait. Mutable access to
+    /// the result needs `DerefMut` as well.
+    Overloaded,
+}
+
+/// Like `autoderef`, but skips the initial type and yields each of the
+/// dereferenced types together with the way it was reached.
+pub fn autoderef_with_steps<'a>(
+    db: &'a impl HirDatabase,
+    krate: Option<CrateId>,
+    ty: InEnvironment<Canonical<Ty>>,
+) -> impl Iterator<Item = (Canonical<Ty>, DerefKind)> + 'a {
+    let InEnvironment { value: ty, environment } = ty;
+    let step = move |ty: &Canonical<Ty>| {
+        deref_step(db, krate?, InEnvironment { value: ty, environment: environment.clone() })
+    };
+    successors(step(&ty), move |(ty, _)| step(ty)).take(AUTODEREF_RECURSION_LIMIT - 1)
+}
+
 pub(crate) fn deref(
     db: &impl HirDatabase,
     krate: CrateId,
     ty: InEnvironment<&Canonical<Ty>>,
 ) -> Option<Canonical<Ty>> {
-    if let Some(derefed) = ty.value.value.builtin_deref() {
-        Some(Canonical { value: derefed, num_vars: ty.value.num_vars })
-    } else {
-        deref_by_trait(db, krate, ty)
+    deref_step(db, krate, ty).map(|(derefed, _)| derefed)
+}
+
+fn deref_step(
+    db: &impl HirDatabase,
+    krate: CrateId,
+    ty: InEnvironment<&Canonical<Ty>>,
+) -> Option<(Canonical<Ty>, DerefKind)> {
+    let mutability = match &ty.value.value {
+        Ty::Apply(ApplicationTy { ctor: TypeCtor::Ref(mutability), .. })
+        | Ty::Apply(ApplicationTy { ctor: TypeCtor::RawPtr(mutability), .. }) => Some(*mutability),
+        _ => None,
+    };
+    match (ty.value.value.builtin_deref(), mutability) {
+        (Some(derefed), Some(mutability)) => Some((
+            Canonical { value: derefed, num_vars: ty.value.num_vars },
+            DerefKind::Builtin(mutability),
+        )),
+        _ => deref_by_trait(db, krate, ty).map(|derefed| (derefed, DerefKind::Overloaded)),
     }
 }
 
@@ -2,6 +2,7 @@
 
 use std::any::Any;
 
+use hir_def::FunctionId;
 use hir_expand::{db::AstDatabase, name::Name, HirFileId, InFile};
 use ra_syntax::{ast, AstNode, AstPtr, SyntaxNodePtr};
 
@@ -89,3 +90,120 @@ impl AstDiagnostic for MissingOkInTailExpr {
         ast::Expr::cast(node).unwrap()
     }
 }
+
+#[derive(Debug)]
+pub struct MissingUnsafe {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::Expr>,
+}
+
+impl Diagnostic for MissingUnsafe {
+    fn message(&self) -> String {
+        "This operation is unsafe and requires an unsafe function or block".to_string()
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct MismatchedArgCount {
+    pub file: HirFileId,
+    pub call_expr: AstPtr<ast::Expr>,
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl Diagnostic for MismatchedArgCount {
+    fn message(&self) -> String {
+        let s = if self.expected == 1 { "" } else { "s" };
+        format!("Expected {} argument{}, found {}", self.expected, s, self.found)
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.call_expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct AssocFnCalledAsMethod {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::Expr>,
+    pub func: Name,
+}
+
+impl Diagnostic for AssocFnCalledAsMethod {
+    fn message(&self) -> String {
+        format!(
+            "`{0}` is an associated function, not a method; use `::{0}` instead of `.{0}`",
+            self.func
+        )
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct TypeAnnotationsNeeded {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::Expr>,
+}
+
+impl Diagnostic for TypeAnnotationsNeeded {
+    fn message(&self) -> String {
+        "type annotations needed".to_string()
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct UseAfterMove {
+    pub file: HirFileId,
+    pub expr: AstPtr<ast::Expr>,
+    pub moved_at: AstPtr<ast::Expr>,
+}
+
+impl Diagnostic for UseAfterMove {
+    fn message(&self) -> String {
+        "use of moved value".to_string()
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.expr.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
+
+#[derive(Debug)]
+pub struct UnusedSelfParam {
+    pub file: HirFileId,
+    pub func: FunctionId,
+    pub self_param: AstPtr<ast::SelfParam>,
+}
+
+impl Diagnostic for UnusedSelfParam {
+    fn message(&self) -> String {
+        "`self` is never used, consider making this an associated function".to_string()
+    }
+    fn source(&self) -> InFile<SyntaxNodePtr> {
+        InFile { file_id: self.file, value: self.self_param.into() }
+    }
+    fn as_any(&self) -> &(dyn Any + Send + 'static) {
+        self
+    }
+}
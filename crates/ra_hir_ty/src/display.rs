@@ -3,19 +3,50 @@
 use std::fmt;
 
 use crate::{
-    db::HirDatabase, utils::generics, ApplicationTy, CallableDef, FnSig, GenericPredicate,
+    db::HirDatabase, utils::generics, ApplicationTy, CallableDef, FnSig, GenericPredicate, InferTy,
     Obligation, ProjectionTy, Substs, TraitRef, Ty, TypeCtor,
 };
-use hir_def::{generics::TypeParamProvenance, AdtId, AssocContainerId, Lookup};
+use ena::unify::UnifyKey;
+use hir_def::{
+    body::Body,
+    expr::{Expr, ExprId, Pat, PatId},
+    generics::TypeParamProvenance,
+    path::Path,
+    resolver::{resolver_for_expr, ValueNs},
+    AdtId, AssocContainerId, DefWithBodyId, FunctionId, HasModule, Lookup, ModuleId, TraitId,
+};
 use hir_expand::name::Name;
+use ra_arena::ArenaId;
+use ra_db::{salsa::InternKey, CrateId};
+use ra_syntax::{ast, AstNode, AstPtr, T};
 
 pub struct HirFormatter<'a, 'b, DB> {
     pub db: &'a DB,
     fmt: &'a mut fmt::Formatter<'b>,
     buf: String,
     curr_size: usize,
-    pub(crate) max_size: Option<usize>,
-    omit_verbose_types: bool,
+    options: DisplayOptions,
+}
+
+/// Controls how `HirDisplay::display_with_options` renders types. The default
+/// options render them like `HirDisplay::display`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DisplayOptions {
+    /// Replace the parts of the type beyond this many characters with `…`.
+    pub max_size: Option<usize>,
+    /// Leave out defaulted type arguments and closure parameters.
+    pub omit_verbose_types: bool,
+    /// Append the `where` clauses of function types after their signature.
+    pub show_where_clauses: bool,
+    /// Show literals that aren't constrained yet with the type they default
+    /// to, e.g. `i32` instead of `{integer}`.
+    pub default_literal_types: bool,
+    /// Render ADTs and traits with their full path as seen from this crate,
+    /// e.g. `core::option::Option<i32>` instead of `Option<i32>`.
+    pub qualify_paths_from: Option<CrateId>,
+    /// List the locals captured by closures after their signature, e.g.
+    /// `|i32| -> i32 [captures by ref: x, y]`.
+    pub show_closure_captures: bool,
 }
 
 pub trait HirDisplay {
@@ -25,7 +56,7 @@ pub trait HirDisplay {
     where
         Self: Sized,
     {
-        HirDisplayWrapper(db, self, None, false)
+        HirDisplayWrapper(db, self, DisplayOptions::default())
     }
 
     fn display_truncated<'a, DB>(
@@ -36,7 +67,19 @@ pub trait HirDisplay {
     where
         Self: Sized,
     {
-        HirDisplayWrapper(db, self, max_size, true)
+        let options = DisplayOptions { max_size, omit_verbose_types: true, ..Default::default() };
+        HirDisplayWrapper(db, self, options)
+    }
+
+    fn display_with_options<'a, DB>(
+        &'a self,
+        db: &'a DB,
+        options: DisplayOptions,
+    ) -> HirDisplayWrapper<'a, DB, Self>
+    where
+        Self: Sized,
+    {
+        HirDisplayWrapper(db, self, options)
     }
 }
 
@@ -72,7 +115,7 @@ where
     }
 
     pub fn should_truncate(&self) -> bool {
-        if let Some(max_size) = self.max_size {
+        if let Some(max_size) = self.options.max_size {
             self.curr_size >= max_size
         } else {
             false
@@ -80,11 +123,66 @@ where
     }
 
     pub fn omit_verbose_types(&self) -> bool {
-        self.omit_verbose_types
+        self.options.omit_verbose_types
+    }
+
+    /// Displays a type nested in the one being formatted with the same
+    /// options, but without truncating it.
+    fn display_nested<'t, T: HirDisplay>(&self, t: &'t T) -> HirDisplayWrapper<'t, DB, T>
+    where
+        'a: 't,
+    {
+        let options = DisplayOptions { max_size: None, omit_verbose_types: false, ..self.options };
+        HirDisplayWrapper(self.db, t, options)
+    }
+
+    /// Writes the path of `module`, followed by `::`, if paths should be
+    /// qualified. The crate is named as `qualify_paths_from` refers to it.
+    fn write_module_path(&mut self, module: ModuleId) -> fmt::Result {
+        let from_crate = match self.options.qualify_paths_from {
+            Some(it) => it,
+            None => return Ok(()),
+        };
+        if module.krate == from_crate {
+            write!(self, "crate::")?;
+        } else {
+            let crate_graph = self.db.crate_graph();
+            // Crates that aren't direct dependencies have no name from the
+            // viewing crate, so use the name any other crate knows them by.
+            let crate_name = crate_graph
+                .dependencies(from_crate)
+                .chain(crate_graph.iter().flat_map(|krate| crate_graph.dependencies(krate)))
+                .find(|dep| dep.crate_id == module.krate)
+                .map(|dep| dep.name.clone());
+            if let Some(name) = crate_name {
+                write!(self, "{}::", name)?;
+            }
+        }
+
+        let def_map = self.db.crate_def_map(module.krate);
+        let mut segments = Vec::new();
+        let mut local_id = module.local_id;
+        while let Some(parent) = def_map[local_id].parent {
+            if let Some((name, _)) =
+                def_map[parent].children.iter().find(|(_, &child)| child == local_id)
+            {
+                segments.push(name.clone());
+            }
+            local_id = parent;
+        }
+        for segment in segments.iter().rev() {
+            write!(self, "{}::", segment)?;
+        }
+        Ok(())
+    }
+
+    fn write_trait_path(&mut self, trait_: TraitId) -> fmt::Result {
+        self.write_module_path(trait_.lookup(self.db).container.module(self.db))?;
+        write!(self, "{}", self.db.trait_data(trait_).name.clone())
     }
 }
 
-pub struct HirDisplayWrapper<'a, DB, T>(&'a DB, &'a T, Option<usize>, bool);
+pub struct HirDisplayWrapper<'a, DB, T>(&'a DB, &'a T, DisplayOptions);
 
 impl<'a, DB, T> fmt::Display for HirDisplayWrapper<'a, DB, T>
 where
@@ -97,14 +195,70 @@ where
             fmt: f,
             buf: String::with_capacity(20),
             curr_size: 0,
-            max_size: self.2,
-            omit_verbose_types: self.3,
+            options: self.2,
         })
     }
 }
 
 const TYPE_HINT_TRUNCATION: &str = "…";
 
+/// Returns whether the closure `expr` is a `move` closure, which captures its
+/// environment by value rather than by reference.
+fn is_move_closure(db: &impl HirDatabase, def: DefWithBodyId, expr: ExprId) -> bool {
+    let (_, source_map) = db.body_with_source_map(def);
+    let ptr: Option<AstPtr<ast::Expr>> =
+        source_map.expr_syntax(expr).and_then(|src| src.value.left());
+    let root = source_map.expr_syntax(expr).and_then(|src| db.parse_or_expand(src.file_id));
+    match (ptr, root) {
+        (Some(ptr), Some(root)) => {
+            ptr.to_node(&root).syntax().children_with_tokens().any(|it| it.kind() == T![move])
+        }
+        _ => false,
+    }
+}
+
+/// Returns the names of the locals declared outside of the closure `expr`
+/// that are used in its body, in the order of their first use.
+fn closure_captures(db: &impl HirDatabase, def: DefWithBodyId, expr: ExprId) -> Vec<Name> {
+    let body = db.body(def);
+    let lambda_body = match &body[expr] {
+        Expr::Lambda { body, .. } => *body,
+        _ => return Vec::new(),
+    };
+    // Bindings from inside the closure aren't in scope at the closure itself,
+    // so a path resolving to the same binding at both places is a capture.
+    let outer_resolver = resolver_for_expr(db, def, expr);
+    let mut paths = Vec::new();
+    collect_paths(&body, lambda_body, &mut paths);
+    let mut captures: Vec<PatId> = Vec::new();
+    for (id, path) in paths {
+        let inner =
+            resolver_for_expr(db, def, id).resolve_path_in_value_ns_fully(db, path.mod_path());
+        let pat = match inner {
+            Some(ValueNs::LocalBinding(pat)) => pat,
+            _ => continue,
+        };
+        let outer = outer_resolver.resolve_path_in_value_ns_fully(db, path.mod_path());
+        if outer == Some(ValueNs::LocalBinding(pat)) && !captures.contains(&pat) {
+            captures.push(pat);
+        }
+    }
+    return captures
+        .into_iter()
+        .filter_map(|pat| match &body[pat] {
+            Pat::Bind { name, .. } => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+
+    fn collect_paths<'b>(body: &'b Body, id: ExprId, acc: &mut Vec<(ExprId, &'b Path)>) {
+        if let Expr::Path(path) = &body[id] {
+            acc.push((id, path));
+        }
+        body[id].walk_child_exprs(|child| collect_paths(body, child, acc));
+    }
+}
+
 impl HirDisplay for &Ty {
     fn hir_fmt(&self, f: &mut HirFormatter<impl HirDatabase>) -> fmt::Result {
         HirDisplay::hir_fmt(*self, f)
@@ -113,53 +267,74 @@ impl HirDisplay for &Ty {
 
 impl HirDisplay for ApplicationTy {
     fn hir_fmt(&self, f: &mut HirFormatter<impl HirDatabase>) -> fmt::Result {
-        if f.should_truncate() {
+        // `()` is no longer than the truncation marker, so it's always shown
+        let is_unit = self.ctor == TypeCtor::Tuple { cardinality: 0 };
+        if f.should_truncate() && !is_unit {
             return write!(f, "{}", TYPE_HINT_TRUNCATION);
         }
 
         match self.ctor {
             TypeCtor::Bool => write!(f, "bool")?,
             TypeCtor::Char => write!(f, "char")?,
+            // Literals that aren't constrained yet will end up with the
+            // default type, which is more useful to show than `{integer}`
+            TypeCtor::Int(t) if f.options.default_literal_types => {
+                write!(f, "{}", t.resolved_or_default())?
+            }
+            TypeCtor::Float(t) if f.options.default_literal_types => {
+                write!(f, "{}", t.resolved_or_default())?
+            }
             TypeCtor::Int(t) => write!(f, "{}", t)?,
             TypeCtor::Float(t) => write!(f, "{}", t)?,
             TypeCtor::Str => write!(f, "str")?,
             TypeCtor::Slice => {
                 let t = self.parameters.as_single();
-                write!(f, "[{}]", t.display(f.db))?;
+                write!(f, "[{}]", f.display_nested(t))?;
             }
-            TypeCtor::Array => {
+            TypeCtor::Array(len) => {
                 let t = self.parameters.as_single();
-                write!(f, "[{}; _]", t.display(f.db))?;
+                match len {
+                    Some(len) => write!(f, "[{}; {}]", f.display_nested(t), len)?,
+                    None => write!(f, "[{}; _]", f.display_nested(t))?,
+                }
             }
             TypeCtor::RawPtr(m) => {
                 let t = self.parameters.as_single();
-                write!(f, "*{}{}", m.as_keyword_for_ptr(), t.display(f.db))?;
+                write!(f, "*{}{}", m.as_keyword_for_ptr(), f.display_nested(t))?;
             }
             TypeCtor::Ref(m) => {
                 let t = self.parameters.as_single();
                 let ty_display = if f.omit_verbose_types() {
-                    t.display_truncated(f.db, f.max_size)
+                    HirDisplayWrapper(f.db, t, f.options)
                 } else {
-                    t.display(f.db)
+                    f.display_nested(t)
                 };
                 write!(f, "&{}{}", m.as_keyword_for_ref(), ty_display)?;
             }
             TypeCtor::Never => write!(f, "!")?,
             TypeCtor::Tuple { .. } => {
                 let ts = &self.parameters;
-                if ts.len() == 1 {
-                    write!(f, "({},)", ts[0].display(f.db))?;
-                } else {
-                    write!(f, "(")?;
-                    f.write_joined(&*ts.0, ", ")?;
-                    write!(f, ")")?;
+                match ts.len() {
+                    0 => write!(f, "()")?,
+                    // A one-tuple needs the trailing comma to not be read as
+                    // a parenthesized type
+                    1 => {
+                        write!(f, "(")?;
+                        ts[0].hir_fmt(f)?;
+                        write!(f, ",)")?;
+                    }
+                    _ => {
+                        write!(f, "(")?;
+                        f.write_joined(&*ts.0, ", ")?;
+                        write!(f, ")")?;
+                    }
                 }
             }
             TypeCtor::FnPtr { .. } => {
                 let sig = FnSig::from_fn_ptr_substs(&self.parameters);
                 write!(f, "fn(")?;
                 f.write_joined(sig.params(), ", ")?;
-                write!(f, ") -> {}", sig.ret().display(f.db))?;
+                write!(f, ") -> {}", f.display_nested(sig.ret()))?;
             }
             TypeCtor::FnDef(def) => {
                 let sig = f.db.callable_item_signature(def).subst(&self.parameters);
@@ -191,7 +366,12 @@ impl HirDisplay for ApplicationTy {
                 }
                 write!(f, "(")?;
                 f.write_joined(sig.params(), ", ")?;
-                write!(f, ") -> {}", sig.ret().display(f.db))?;
+                write!(f, ") -> {}", f.display_nested(sig.ret()))?;
+                if let CallableDef::FunctionId(ff) = def {
+                    if f.options.show_where_clauses {
+                        write_where_clauses(f, ff, &self.parameters)?;
+                    }
+                }
             }
             TypeCtor::Adt(def_id) => {
                 let name = match def_id {
@@ -199,6 +379,7 @@ impl HirDisplay for ApplicationTy {
                     AdtId::UnionId(it) => f.db.union_data(it).name.clone(),
                     AdtId::EnumId(it) => f.db.enum_data(it).name.clone(),
                 };
+                f.write_module_path(def_id.module(f.db))?;
                 write!(f, "{}", name)?;
                 if self.parameters.len() > 0 {
                     write!(f, "<")?;
@@ -242,20 +423,23 @@ impl HirDisplay for ApplicationTy {
                     AssocContainerId::TraitId(it) => it,
                     _ => panic!("not an associated type"),
                 };
-                let trait_name = f.db.trait_data(trait_).name.clone();
                 let name = f.db.type_alias_data(type_alias).name.clone();
-                write!(f, "{}::{}", trait_name, name)?;
+                f.write_trait_path(trait_)?;
+                write!(f, "::{}", name)?;
                 if self.parameters.len() > 0 {
                     write!(f, "<")?;
                     f.write_joined(&*self.parameters.0, ", ")?;
                     write!(f, ">")?;
                 }
             }
-            TypeCtor::Closure { .. } => {
-                let sig = self.parameters[0]
-                    .callable_sig(f.db)
-                    .expect("first closure parameter should contain signature");
-                let return_type_hint = sig.ret().display(f.db);
+            TypeCtor::Closure { def, expr } => {
+                let sig = match self.parameters.0.first().and_then(|it| it.callable_sig(f.db)) {
+                    Some(sig) => sig,
+                    // The signature may be missing if the closure type was
+                    // substituted or built by hand; don't panic for display.
+                    None => return write!(f, "{{closure}}"),
+                };
+                let return_type_hint = f.display_nested(sig.ret());
                 if sig.params().is_empty() {
                     write!(f, "|| -> {}", return_type_hint)?;
                 } else if f.omit_verbose_types() {
@@ -265,6 +449,14 @@ impl HirDisplay for ApplicationTy {
                     f.write_joined(sig.params(), ", ")?;
                     write!(f, "| -> {}", return_type_hint)?;
                 };
+                if f.options.show_closure_captures {
+                    let captures = closure_captures(f.db, def, expr);
+                    if !captures.is_empty() {
+                        let captures = captures.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+                        let mode = if is_move_closure(f.db, def, expr) { "move" } else { "ref" };
+                        write!(f, " [captures by {}: {}]", mode, captures.join(", "))?;
+                    }
+                }
             }
         }
         Ok(())
@@ -277,11 +469,13 @@ impl HirDisplay for ProjectionTy {
             return write!(f, "{}", TYPE_HINT_TRUNCATION);
         }
 
-        let trait_name = f.db.trait_data(self.trait_(f.db)).name.clone();
-        write!(f, "<{} as {}", self.parameters[0].display(f.db), trait_name,)?;
-        if self.parameters.len() > 1 {
+        write!(f, "<")?;
+        self.parameters[0].hir_fmt(f)?;
+        write!(f, " as ")?;
+        f.write_trait_path(self.trait_(f.db))?;
+        if !self.parameters.type_args().is_empty() {
             write!(f, "<")?;
-            f.write_joined(&self.parameters[1..], ", ")?;
+            f.write_joined(self.parameters.type_args(), ", ")?;
             write!(f, ">")?;
         }
         write!(f, ">::{}", f.db.type_alias_data(self.associated_ty).name)?;
@@ -323,7 +517,13 @@ impl HirDisplay for Ty {
                     Ty::Opaque(_) => write!(f, "impl ")?,
                     _ => unreachable!(),
                 };
-                write_bounds_like_dyn_trait(&predicates, f)?;
+                if predicates.is_empty() {
+                    // We don't know any bounds of this type, e.g. an opaque
+                    // type we failed to lower.
+                    write!(f, "{{unknown}}")?;
+                } else {
+                    write_bounds_like_dyn_trait(&predicates, f)?;
+                }
             }
             Ty::Unknown => write!(f, "{{unknown}}")?,
             Ty::Infer(..) => write!(f, "_")?,
@@ -342,9 +542,39 @@ fn write_bounds_like_dyn_trait(
     // aren't as expected (i.e. self types = $0, projection
     // predicates for a certain trait come after the Implemented
     // predicate for that trait).
+
+    // Split the predicates into one group per trait, with the projection
+    // predicates following their trait. Auto traits like `Send` are then moved
+    // behind the principal trait and sorted by name, so that the order doesn't
+    // depend on how the bounds were written.
+    let mut groups: Vec<&[GenericPredicate]> = Vec::new();
+    let mut start = 0;
+    for (i, p) in predicates.iter().enumerate() {
+        if let GenericPredicate::Implemented(_) = p {
+            if i > start {
+                groups.push(&predicates[start..i]);
+            }
+            start = i;
+        }
+    }
+    if predicates.len() > start {
+        groups.push(&predicates[start..]);
+    }
+    groups.sort_by_cached_key(|group| match &group[0] {
+        GenericPredicate::Implemented(trait_ref) => {
+            let trait_data = f.db.trait_data(trait_ref.trait_);
+            if trait_data.auto {
+                Some(trait_data.name.to_string())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    });
+
     let mut first = true;
     let mut angle_open = false;
-    for p in predicates.iter() {
+    for p in groups.into_iter().flatten() {
         match p {
             GenericPredicate::Implemented(trait_ref) => {
                 if angle_open {
@@ -356,10 +586,10 @@ fn write_bounds_like_dyn_trait(
                 // We assume that the self type is $0 (i.e. the
                 // existential) here, which is the only thing that's
                 // possible in actual Rust, and hence don't print it
-                write!(f, "{}", f.db.trait_data(trait_ref.trait_).name.clone())?;
-                if trait_ref.substs.len() > 1 {
+                f.write_trait_path(trait_ref.trait_)?;
+                if !trait_ref.substs.type_args().is_empty() {
                     write!(f, "<")?;
-                    f.write_joined(&trait_ref.substs[1..], ", ")?;
+                    f.write_joined(trait_ref.substs.type_args(), ", ")?;
                     // there might be assoc type bindings, so we leave the angle brackets open
                     angle_open = true;
                 }
@@ -409,10 +639,10 @@ impl TraitRef {
         } else {
             write!(f, ": ")?;
         }
-        write!(f, "{}", f.db.trait_data(self.trait_).name.clone())?;
-        if self.substs.len() > 1 {
+        f.write_trait_path(self.trait_)?;
+        if !self.substs.type_args().is_empty() {
             write!(f, "<")?;
-            f.write_joined(&self.substs[1..], ", ")?;
+            f.write_joined(self.substs.type_args(), ", ")?;
             write!(f, ">")?;
         }
         Ok(())
@@ -446,7 +676,7 @@ impl HirDisplay for GenericPredicate {
                     f,
                     ">::{} = {}",
                     f.db.type_alias_data(projection_pred.projection_ty.associated_ty).name,
-                    projection_pred.ty.display(f.db)
+                    f.display_nested(&projection_pred.ty)
                 )?;
             }
             GenericPredicate::Error => write!(f, "{{error}}")?,
@@ -455,6 +685,47 @@ impl HirDisplay for GenericPredicate {
     }
 }
 
+fn write_where_clauses(
+    f: &mut HirFormatter<impl HirDatabase>,
+    def: FunctionId,
+    parameters: &Substs,
+) -> fmt::Result {
+    let generics = generics(f.db, def.into());
+    let predicates = f.db.generic_predicates(def.into());
+    // Bounds of implicit `impl Trait` params are already shown in the argument types.
+    let is_impl_trait_param = |ty: &Ty| match ty {
+        Ty::Bound(idx) => generics
+            .iter()
+            .nth(*idx as usize)
+            .map_or(false, |(_, data)| data.provenance == TypeParamProvenance::ArgumentImplTrait),
+        _ => false,
+    };
+    let predicates: Vec<_> = predicates
+        .iter()
+        .filter(|pred| match &pred.value {
+            GenericPredicate::Implemented(trait_ref) => !is_impl_trait_param(trait_ref.self_ty()),
+            GenericPredicate::Projection(proj) => {
+                !is_impl_trait_param(&proj.projection_ty.parameters[0])
+            }
+            GenericPredicate::Error => true,
+        })
+        .map(|pred| pred.clone().subst(parameters))
+        .collect();
+    let maybe_bounds = crate::lower::maybe_bounds(f.db, def.into());
+    if predicates.is_empty() && maybe_bounds.is_empty() {
+        return Ok(());
+    }
+    write!(f, " where ")?;
+    f.write_joined(&predicates, ", ")?;
+    for (i, (ty, trait_name)) in maybe_bounds.into_iter().enumerate() {
+        if i > 0 || !predicates.is_empty() {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}: ?{}", f.display_nested(&ty.subst(parameters)), trait_name)?;
+    }
+    Ok(())
+}
+
 impl HirDisplay for Obligation {
     fn hir_fmt(&self, f: &mut HirFormatter<impl HirDatabase>) -> fmt::Result {
         match self {
@@ -468,3 +739,110 @@ impl HirDisplay for Obligation {
         }
     }
 }
+
+impl Ty {
+    /// Renders the structure of the type without looking up any names, e.g.
+    /// `Adt(#3)<Bound(0)>`. Items are shown by their interned ids, so this is
+    /// only useful for logging, in places where no database is available.
+    pub fn display_structural(&self) -> String {
+        Structural(self).to_string()
+    }
+}
+
+struct Structural<'a, T>(&'a T);
+
+fn intern_id(id: impl InternKey) -> u32 {
+    id.as_intern_id().as_u32()
+}
+
+fn write_structural_params(f: &mut fmt::Formatter, params: &Substs) -> fmt::Result {
+    if params.is_empty() {
+        return Ok(());
+    }
+    write!(f, "<")?;
+    for (i, ty) in params.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", Structural(ty))?;
+    }
+    write!(f, ">")
+}
+
+impl fmt::Display for Structural<'_, Ty> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Ty::Apply(a_ty) => {
+                match a_ty.ctor {
+                    TypeCtor::Bool => write!(f, "bool")?,
+                    TypeCtor::Char => write!(f, "char")?,
+                    TypeCtor::Int(t) => write!(f, "{}", t)?,
+                    TypeCtor::Float(t) => write!(f, "{}", t)?,
+                    TypeCtor::Str => write!(f, "str")?,
+                    TypeCtor::Never => write!(f, "!")?,
+                    TypeCtor::Slice => write!(f, "Slice")?,
+                    TypeCtor::Array(len) => write!(f, "Array({:?})", len)?,
+                    TypeCtor::RawPtr(m) => write!(f, "RawPtr({:?})", m)?,
+                    TypeCtor::Ref(m) => write!(f, "Ref({:?})", m)?,
+                    TypeCtor::Tuple { .. } => write!(f, "Tuple")?,
+                    TypeCtor::FnPtr { .. } => write!(f, "FnPtr")?,
+                    TypeCtor::Adt(AdtId::StructId(it)) => write!(f, "Adt(#{})", intern_id(it))?,
+                    TypeCtor::Adt(AdtId::UnionId(it)) => write!(f, "Adt(#{})", intern_id(it))?,
+                    TypeCtor::Adt(AdtId::EnumId(it)) => write!(f, "Adt(#{})", intern_id(it))?,
+                    TypeCtor::FnDef(CallableDef::FunctionId(it)) => {
+                        write!(f, "FnDef(#{})", intern_id(it))?
+                    }
+                    TypeCtor::FnDef(CallableDef::StructId(it)) => {
+                        write!(f, "FnDef(#{})", intern_id(it))?
+                    }
+                    TypeCtor::FnDef(CallableDef::EnumVariantId(it)) => {
+                        write!(f, "FnDef(#{}.{})", intern_id(it.parent), it.local_id.into_raw())?
+                    }
+                    TypeCtor::AssociatedType(it) => write!(f, "AssocType(#{})", intern_id(it))?,
+                    TypeCtor::Closure { expr, .. } => write!(f, "Closure({})", expr.into_raw())?,
+                }
+                write_structural_params(f, &a_ty.parameters)
+            }
+            Ty::Projection(p_ty) => {
+                write!(f, "Projection(#{})", intern_id(p_ty.associated_ty))?;
+                write_structural_params(f, &p_ty.parameters)
+            }
+            Ty::Placeholder(id) => write!(f, "Placeholder({})", id.local_id.into_raw()),
+            Ty::Bound(idx) => write!(f, "Bound({})", idx),
+            Ty::Infer(tv) => match tv {
+                InferTy::TypeVar(it) => write!(f, "TypeVar({})", it.index()),
+                InferTy::IntVar(it) => write!(f, "IntVar({})", it.index()),
+                InferTy::FloatVar(it) => write!(f, "FloatVar({})", it.index()),
+                InferTy::MaybeNeverTypeVar(it) => write!(f, "MaybeNeverTypeVar({})", it.index()),
+            },
+            Ty::Dyn(predicates) | Ty::Opaque(predicates) => {
+                write!(f, "{}(", if let Ty::Dyn(_) = self.0 { "Dyn" } else { "Opaque" })?;
+                for (i, pred) in predicates.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " + ")?;
+                    }
+                    write!(f, "{}", Structural(pred))?;
+                }
+                write!(f, ")")
+            }
+            Ty::Unknown => write!(f, "{{unknown}}"),
+        }
+    }
+}
+
+impl fmt::Display for Structural<'_, GenericPredicate> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            GenericPredicate::Implemented(trait_ref) => {
+                write!(f, "Trait(#{})", intern_id(trait_ref.trait_))?;
+                write_structural_params(f, &trait_ref.substs)
+            }
+            GenericPredicate::Projection(proj) => {
+                write!(f, "Projection(#{})", intern_id(proj.projection_ty.associated_ty))?;
+                write_structural_params(f, &proj.projection_ty.parameters)?;
+                write!(f, " = {}", Structural(&proj.ty))
+            }
+            GenericPredicate::Error => write!(f, "{{error}}"),
+        }
+    }
+}
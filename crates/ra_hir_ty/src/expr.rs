@@ -4,19 +4,22 @@ use std::sync::Arc;
 
 use hir_def::{
     path::{path, Path},
-    resolver::HasResolver,
-    AdtId, FunctionId,
+    resolver::{resolver_for_expr, HasResolver, ValueNs},
+    AdtId, AssocContainerId, FunctionId, Lookup,
 };
 use hir_expand::{diagnostics::DiagnosticSink, name::Name};
+use ra_db::CrateId;
 use ra_syntax::ast;
 use ra_syntax::AstPtr;
-use rustc_hash::FxHashSet;
+use rustc_hash::{FxHashMap, FxHashSet};
 
 use crate::{
     db::HirDatabase,
-    diagnostics::{MissingFields, MissingOkInTailExpr},
+    diagnostics::{
+        MissingFields, MissingOkInTailExpr, MissingUnsafe, UnusedSelfParam, UseAfterMove,
+    },
     utils::variant_data,
-    ApplicationTy, InferenceResult, Ty, TypeCtor,
+    ApplicationTy, CallableDef, InferenceResult, Mutability, TraitEnvironment, Ty, TypeCtor,
 };
 
 pub use hir_def::{
@@ -55,10 +58,250 @@ impl<'a, 'b> ExprValidator<'a, 'b> {
             }
         }
 
+        let resolver = self.func.resolver(db);
+        if let Some(krate) = resolver.krate() {
+            let env = TraitEnvironment::lower(db, &resolver);
+            for (_, expr) in body.exprs.iter() {
+                if let Expr::Block { statements, tail } = expr {
+                    self.validate_moves_in_block(statements, *tail, &body, db, krate, &env);
+                }
+            }
+        }
+
         let body_expr = &body[body.body_expr];
         if let Expr::Block { statements: _, tail: Some(t) } = body_expr {
             self.validate_results_in_tail_expr(body.body_expr, *t, db);
         }
+
+        if !db.function_data(self.func).is_unsafe {
+            let mut unsafe_exprs = Vec::new();
+            self.collect_unsafe_exprs(db, &body, body.body_expr, false, &mut unsafe_exprs);
+            if !unsafe_exprs.is_empty() {
+                let (_, source_map) = db.body_with_source_map(self.func.into());
+                for id in unsafe_exprs {
+                    if let Some(source_ptr) = source_map.expr_syntax(id) {
+                        if let Some(expr) = source_ptr.value.left() {
+                            self.sink.push(MissingUnsafe { file: source_ptr.file_id, expr });
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Collects unsafe operations (calls to `unsafe fn`s, raw pointer derefs
+    /// and union field reads) that are not wrapped in an `unsafe` block.
+    fn collect_unsafe_exprs(
+        &self,
+        db: &impl HirDatabase,
+        body: &Body,
+        id: ExprId,
+        inside_unsafe_block: bool,
+        unsafe_exprs: &mut Vec<ExprId>,
+    ) {
+        let expr = &body[id];
+        if let Expr::Unsafe { body: inner } = expr {
+            self.collect_unsafe_exprs(db, body, *inner, true, unsafe_exprs);
+            return;
+        }
+        if let Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { op: None }) } = expr {
+            // Writing to a union field is safe, only reading from one isn't
+            if let Expr::Field { .. } = &body[*lhs] {
+                body[*lhs].walk_child_exprs(|child| {
+                    self.collect_unsafe_exprs(db, body, child, inside_unsafe_block, unsafe_exprs)
+                });
+                self.collect_unsafe_exprs(db, body, *rhs, inside_unsafe_block, unsafe_exprs);
+                return;
+            }
+        }
+        if !inside_unsafe_block {
+            let is_unsafe = match expr {
+                Expr::Call { callee, .. } => match &self.infer[*callee] {
+                    Ty::Apply(ApplicationTy {
+                        ctor: TypeCtor::FnDef(CallableDef::FunctionId(func)),
+                        ..
+                    }) => db.function_data(*func).is_unsafe,
+                    _ => false,
+                },
+                Expr::MethodCall { .. } => match self.infer.method_resolution(id) {
+                    Some(func) => db.function_data(func).is_unsafe,
+                    None => false,
+                },
+                Expr::UnaryOp { expr: operand, op: UnaryOp::Deref } => {
+                    match &self.infer[*operand] {
+                        Ty::Apply(ApplicationTy { ctor: TypeCtor::RawPtr(_), .. }) => true,
+                        _ => false,
+                    }
+                }
+                Expr::Field { .. } => match self.infer.field_resolution(id) {
+                    Some(field) => match field.parent {
+                        VariantId::UnionId(_) => true,
+                        _ => false,
+                    },
+                    None => false,
+                },
+                _ => false,
+            };
+            if is_unsafe {
+                unsafe_exprs.push(id);
+            }
+        }
+        expr.walk_child_exprs(|child| {
+            self.collect_unsafe_exprs(db, body, child, inside_unsafe_block, unsafe_exprs)
+        });
+    }
+
+    /// Reports methods whose `self` parameter is never referenced in the body,
+    /// as they could be associated functions instead. Trait methods and their
+    /// impls are skipped, since their signature is fixed by the trait.
+    ///
+    /// This is a style hint rather than a problem with the code, so it isn't
+    /// part of `validate_body`.
+    pub fn validate_self_param_usage(&mut self, db: &impl HirDatabase) {
+        if !db.function_data(self.func).has_self_param {
+            return;
+        }
+        let body = db.body(self.func.into());
+        match self.func.lookup(db).container {
+            AssocContainerId::TraitId(_) => return,
+            AssocContainerId::ImplId(impl_id) if db.impl_data(impl_id).target_trait.is_some() => {
+                return
+            }
+            _ => {}
+        }
+        let self_pat = match body.params.first() {
+            Some(it) => *it,
+            None => return,
+        };
+        let is_used =
+            body.exprs.iter().any(|(id, _)| self.resolve_local(db, id, &body) == Some(self_pat));
+        if is_used {
+            return;
+        }
+
+        let (_, source_map) = db.body_with_source_map(self.func.into());
+        if let Some(source_ptr) = source_map.pat_syntax(self_pat) {
+            if let Some(self_param) = source_ptr.value.right() {
+                self.sink.push(UnusedSelfParam {
+                    file: source_ptr.file_id,
+                    func: self.func,
+                    self_param,
+                });
+            }
+        }
+    }
+
+    /// Reports locals of non-`Copy` types used after they were moved by a
+    /// statement of the same block: passed to a call, assigned or used as a
+    /// `let` initializer. This is a conservative approximation without any
+    /// control-flow analysis, moves inside nested expressions like `if`
+    /// branches aren't tracked.
+    fn validate_moves_in_block(
+        &mut self,
+        statements: &[Statement],
+        tail: Option<ExprId>,
+        body: &Body,
+        db: &impl HirDatabase,
+        krate: CrateId,
+        env: &Arc<TraitEnvironment>,
+    ) {
+        let mut moved: FxHashMap<PatId, ExprId> = FxHashMap::default();
+        let mut uses_after_move = Vec::new();
+        let exprs = statements
+            .iter()
+            .filter_map(|stmt| match stmt {
+                // `let _ = s` and `let ref r = s` don't move `s`
+                Statement::Let { pat, initializer, .. } => {
+                    initializer.map(|init| (init, is_by_value_binding(&body[*pat])))
+                }
+                Statement::Expr(expr) => Some((*expr, true)),
+            })
+            .chain(tail.map(|tail| (tail, true)));
+        for (expr, moves_path) in exprs {
+            let assigned_local = match &body[expr] {
+                Expr::BinaryOp { lhs, rhs, op: Some(BinaryOp::Assignment { op: None }) } => {
+                    self.resolve_local(db, *lhs, body).map(|local| (local, *rhs))
+                }
+                _ => None,
+            };
+            if !moved.is_empty() {
+                // Assigning to a moved local isn't a use of it
+                let used = assigned_local.map_or(expr, |(_, rhs)| rhs);
+                self.collect_uses_of_moved(db, body, used, &mut moved, &mut uses_after_move);
+            }
+
+            let moved_exprs = match &body[expr] {
+                Expr::Call { args, .. } | Expr::MethodCall { args, .. } => args.clone(),
+                Expr::BinaryOp { rhs, op: Some(BinaryOp::Assignment { op: None }), .. } => {
+                    // Assigning a new value makes the local usable again
+                    if let Some((local, _)) = assigned_local {
+                        moved.remove(&local);
+                    }
+                    vec![*rhs]
+                }
+                Expr::Path(_) if moves_path => vec![expr],
+                _ => Vec::new(),
+            };
+            for moved_expr in moved_exprs {
+                if let Some(local) = self.resolve_local(db, moved_expr, body) {
+                    let ty = &self.infer[moved_expr];
+                    // `&mut` references are implicitly reborrowed instead of moved
+                    let is_mut_ref = match ty {
+                        Ty::Apply(a_ty) => a_ty.ctor == TypeCtor::Ref(Mutability::Mut),
+                        _ => false,
+                    };
+                    if !is_mut_ref && !ty.is_copy(db, env.clone(), krate) {
+                        moved.entry(local).or_insert(moved_expr);
+                    }
+                }
+            }
+        }
+
+        if uses_after_move.is_empty() {
+            return;
+        }
+        let (_, source_map) = db.body_with_source_map(self.func.into());
+        for (expr, moved_at) in uses_after_move {
+            let source_ptr = match source_map.expr_syntax(expr) {
+                Some(it) => it,
+                None => continue,
+            };
+            let moved_at = source_map.expr_syntax(moved_at).and_then(|it| it.value.left());
+            if let (Some(expr), Some(moved_at)) = (source_ptr.value.left(), moved_at) {
+                self.sink.push(UseAfterMove { file: source_ptr.file_id, expr, moved_at });
+            }
+        }
+    }
+
+    /// Collects the paths in `id` that refer to moved locals. Each local is only
+    /// reported once.
+    fn collect_uses_of_moved(
+        &self,
+        db: &impl HirDatabase,
+        body: &Body,
+        id: ExprId,
+        moved: &mut FxHashMap<PatId, ExprId>,
+        uses: &mut Vec<(ExprId, ExprId)>,
+    ) {
+        if let Some(local) = self.resolve_local(db, id, body) {
+            if let Some(moved_at) = moved.remove(&local) {
+                uses.push((id, moved_at));
+            }
+            return;
+        }
+        body[id].walk_child_exprs(|child| self.collect_uses_of_moved(db, body, child, moved, uses));
+    }
+
+    fn resolve_local(&self, db: &impl HirDatabase, id: ExprId, body: &Body) -> Option<PatId> {
+        let path = match &body[id] {
+            Expr::Path(path) => path,
+            _ => return None,
+        };
+        let resolver = resolver_for_expr(db, self.func.into(), id);
+        match resolver.resolve_path_in_value_ns_fully(db, path.mod_path())? {
+            ValueNs::LocalBinding(pat) => Some(pat),
+            _ => None,
+        }
     }
 
     fn validate_record_literal(
@@ -153,3 +396,11 @@ impl<'a, 'b> ExprValidator<'a, 'b> {
         }
     }
 }
+
+fn is_by_value_binding(pat: &Pat) -> bool {
+    match pat {
+        Pat::Bind { mode: BindingAnnotation::Unannotated, .. }
+        | Pat::Bind { mode: BindingAnnotation::Mutable, .. } => true,
+        _ => false,
+    }
+}
@@ -23,7 +23,7 @@ use rustc_hash::FxHashMap;
 use hir_def::{
     body::Body,
     data::{ConstData, FunctionData},
-    expr::{BindingAnnotation, ExprId, PatId},
+    expr::{BindingAnnotation, Expr, ExprId, PatId, Statement},
     lang_item::LangItemTarget,
     path::{path, Path},
     resolver::{HasResolver, Resolver, TypeNs},
@@ -38,14 +38,14 @@ use ra_syntax::SmolStr;
 use super::{
     primitive::{FloatTy, IntTy},
     traits::{Guidance, Obligation, ProjectionPredicate, Solution},
-    ApplicationTy, GenericPredicate, InEnvironment, ProjectionTy, Substs, TraitEnvironment,
+    ApplicationTy, FnSig, GenericPredicate, InEnvironment, ProjectionTy, Substs, TraitEnvironment,
     TraitRef, Ty, TypeCtor, TypeWalk, Uncertain,
 };
 use crate::{
     db::HirDatabase, infer::diagnostics::InferenceDiagnostic, lower::ImplTraitLoweringMode,
 };
 
-pub(crate) use unify::unify;
+pub(crate) use unify::{could_unify, unify};
 
 macro_rules! ty_app {
     ($ctor:pat, $param:pat) => {
@@ -118,6 +118,33 @@ pub struct TypeMismatch {
     pub actual: Ty,
 }
 
+/// An implicit coercion applied to an expression to make it fit the expected
+/// type.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum Coercion {
+    /// Weakening a pointer, like `&mut T` to `&T` or `&T` to `*const T`.
+    RefAdjust,
+    /// A single auto-deref behind a reference, like `&String` to `&str`.
+    Deref,
+    /// Unsizing, like `&[T; N]` to `&[T]` or `Box<T>` to `Box<dyn Trait>`.
+    Unsize,
+    /// Turning a function item into a function pointer.
+    ReifyFnPointer,
+    /// Turning a closure into a function pointer.
+    ClosureFnPointer,
+}
+
+/// The adjustment applied to the receiver of a method call.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub struct ReceiverAdjustment {
+    /// The number of auto-derefs applied to the receiver.
+    pub autoderefs: usize,
+    /// Whether the auto-derefed array receiver was unsized to a slice.
+    pub unsize: bool,
+    /// The mutability of the auto-ref taken after the auto-derefs, if any.
+    pub autoref: Option<Mutability>,
+}
+
 /// The result of type inference: A mapping from expressions and patterns to types.
 #[derive(Clone, PartialEq, Eq, Debug, Default)]
 pub struct InferenceResult {
@@ -131,6 +158,13 @@ pub struct InferenceResult {
     variant_resolutions: FxHashMap<ExprOrPatId, VariantId>,
     /// For each associated item record what it resolves to
     assoc_resolutions: FxHashMap<ExprOrPatId, AssocItemId>,
+    /// For each closure expr, records its inferred signature.
+    closure_sigs: FxHashMap<ExprId, FnSig>,
+    /// For each expr coerced to its expected type, records the coercions in
+    /// the order they were applied.
+    coercions: FxHashMap<ExprId, Vec<Coercion>>,
+    /// For each method call expr, records how its receiver was adjusted.
+    receiver_adjustments: FxHashMap<ExprId, ReceiverAdjustment>,
     diagnostics: Vec<InferenceDiagnostic>,
     pub type_of_expr: ArenaMap<ExprId, Ty>,
     pub type_of_pat: ArenaMap<PatId, Ty>,
@@ -162,6 +196,15 @@ impl InferenceResult {
     pub fn type_mismatch_for_expr(&self, expr: ExprId) -> Option<&TypeMismatch> {
         self.type_mismatches.get(expr)
     }
+    pub fn closure_sig(&self, expr: ExprId) -> Option<FnSig> {
+        self.closure_sigs.get(&expr).cloned()
+    }
+    pub fn coercions(&self, expr: ExprId) -> &[Coercion] {
+        self.coercions.get(&expr).map_or(&[], |it| it.as_slice())
+    }
+    pub fn receiver_adjustment(&self, expr: ExprId) -> Option<ReceiverAdjustment> {
+        self.receiver_adjustments.get(&expr).copied()
+    }
     pub fn add_diagnostics(
         &self,
         db: &impl HirDatabase,
@@ -232,6 +275,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
 
     fn resolve_all(mut self) -> InferenceResult {
         // FIXME resolve obligations as well (use Guidance if necessary)
+        self.report_type_annotations_needed();
         let mut result = std::mem::take(&mut self.result);
         for ty in result.type_of_expr.values_mut() {
             let resolved = self.table.resolve_ty_completely(mem::replace(ty, Ty::Unknown));
@@ -241,9 +285,53 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             let resolved = self.table.resolve_ty_completely(mem::replace(ty, Ty::Unknown));
             *ty = resolved;
         }
+        for sig in result.closure_sigs.values_mut() {
+            let params = sig
+                .params()
+                .iter()
+                .map(|ty| self.table.resolve_ty_completely(ty.clone()))
+                .collect();
+            let ret = self.table.resolve_ty_completely(sig.ret().clone());
+            *sig = FnSig::from_params_and_return(params, ret);
+        }
         result
     }
 
+    /// Reports `let` statements whose type still contains a general type
+    /// variable after inference. Integer and float variables are fine, as they
+    /// fall back to `i32` and `f64`. Types with unknown parts are skipped, as
+    /// the variable is then more likely caused by something we failed to infer.
+    fn report_type_annotations_needed(&mut self) {
+        let body = self.body.clone();
+        for (_, expr) in body.exprs.iter() {
+            let statements = match expr {
+                Expr::Block { statements, .. } => statements,
+                _ => continue,
+            };
+            for stmt in statements {
+                let (pat, initializer) = match stmt {
+                    Statement::Let { pat, initializer: Some(initializer), .. } => {
+                        (*pat, *initializer)
+                    }
+                    _ => continue,
+                };
+                let ty = match self.result.type_of_pat.get(pat) {
+                    Some(ty) => self.table.resolve_ty_as_possible(ty.clone()),
+                    None => continue,
+                };
+                let has_type_var = ty.any(&mut |ty| match ty {
+                    Ty::Infer(InferTy::TypeVar(_)) => true,
+                    _ => false,
+                });
+                if has_type_var && !ty.contains_unknown() {
+                    self.push_diagnostic(InferenceDiagnostic::TypeAnnotationsNeeded {
+                        expr: initializer,
+                    });
+                }
+            }
+        }
+    }
+
     fn write_expr_ty(&mut self, expr: ExprId, ty: Ty) {
         self.result.type_of_expr.insert(expr, ty);
     }
@@ -252,6 +340,10 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
         self.result.method_resolutions.insert(expr, func);
     }
 
+    fn write_receiver_adjustment(&mut self, expr: ExprId, adjustment: ReceiverAdjustment) {
+        self.result.receiver_adjustments.insert(expr, adjustment);
+    }
+
     fn write_field_resolution(&mut self, expr: ExprId, field: StructFieldId) {
         self.result.field_resolutions.insert(expr, field);
     }
@@ -580,7 +672,7 @@ impl InferTy {
             InferTy::TypeVar(..) => Ty::Unknown,
             InferTy::IntVar(..) => Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::i32()))),
             InferTy::FloatVar(..) => Ty::simple(TypeCtor::Float(Uncertain::Known(FloatTy::f64()))),
-            InferTy::MaybeNeverTypeVar(..) => Ty::simple(TypeCtor::Never),
+            InferTy::MaybeNeverTypeVar(..) => Ty::never(),
         }
     }
 }
@@ -612,11 +704,19 @@ mod diagnostics {
     use hir_def::{expr::ExprId, src::HasSource, FunctionId, Lookup};
     use hir_expand::diagnostics::DiagnosticSink;
 
-    use crate::{db::HirDatabase, diagnostics::NoSuchField};
+    use crate::{
+        db::HirDatabase,
+        diagnostics::{
+            AssocFnCalledAsMethod, MismatchedArgCount, NoSuchField, TypeAnnotationsNeeded,
+        },
+    };
 
     #[derive(Debug, PartialEq, Eq, Clone)]
     pub(super) enum InferenceDiagnostic {
         NoSuchField { expr: ExprId, field: usize },
+        MismatchedArgCount { call: ExprId, expected: usize, found: usize },
+        TypeAnnotationsNeeded { expr: ExprId },
+        AssocFnCalledAsMethod { expr: ExprId, func: FunctionId },
     }
 
     impl InferenceDiagnostic {
@@ -633,6 +733,42 @@ mod diagnostics {
                     let field = source_map.field_syntax(*expr, *field);
                     sink.push(NoSuchField { file, field })
                 }
+                InferenceDiagnostic::MismatchedArgCount { call, expected, found } => {
+                    let (_, source_map) = db.body_with_source_map(owner.into());
+                    let source = match source_map.expr_syntax(*call) {
+                        Some(it) => it,
+                        None => return,
+                    };
+                    if let Some(call_expr) = source.value.left() {
+                        sink.push(MismatchedArgCount {
+                            file: source.file_id,
+                            call_expr,
+                            expected: *expected,
+                            found: *found,
+                        })
+                    }
+                }
+                InferenceDiagnostic::TypeAnnotationsNeeded { expr } => {
+                    let (_, source_map) = db.body_with_source_map(owner.into());
+                    let source = match source_map.expr_syntax(*expr) {
+                        Some(it) => it,
+                        None => return,
+                    };
+                    if let Some(expr) = source.value.left() {
+                        sink.push(TypeAnnotationsNeeded { file: source.file_id, expr })
+                    }
+                }
+                InferenceDiagnostic::AssocFnCalledAsMethod { expr, func } => {
+                    let (_, source_map) = db.body_with_source_map(owner.into());
+                    let source = match source_map.expr_syntax(*expr) {
+                        Some(it) => it,
+                        None => return,
+                    };
+                    if let Some(expr) = source.value.left() {
+                        let func = db.function_data(*func).name.clone();
+                        sink.push(AssocFnCalledAsMethod { file: source.file_id, expr, func })
+                    }
+                }
             }
         }
     }
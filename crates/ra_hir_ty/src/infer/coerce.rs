@@ -4,13 +4,17 @@
 //!
 //! See: https://doc.rust-lang.org/nomicon/coercions.html
 
-use hir_def::{lang_item::LangItemTarget, resolver::Resolver, type_ref::Mutability, AdtId};
+use std::iter;
+
+use hir_def::{
+    expr::ExprId, lang_item::LangItemTarget, resolver::Resolver, type_ref::Mutability, AdtId,
+};
 use rustc_hash::FxHashMap;
 use test_utils::tested_by;
 
 use crate::{autoderef, db::HirDatabase, Substs, Ty, TypeCtor, TypeWalk};
 
-use super::{unify::TypeVarValue, InEnvironment, InferTy, InferenceContext};
+use super::{unify::TypeVarValue, Coercion, InEnvironment, InferTy, InferenceContext};
 
 impl<'a, D: HirDatabase> InferenceContext<'a, D> {
     /// Unify two types, but may coerce the first one to the second one
@@ -18,7 +22,20 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
     pub(super) fn coerce(&mut self, from_ty: &Ty, to_ty: &Ty) -> bool {
         let from_ty = self.resolve_ty_shallow(from_ty).into_owned();
         let to_ty = self.resolve_ty_shallow(to_ty);
-        self.coerce_inner(from_ty, &to_ty)
+        self.coerce_inner(from_ty, &to_ty, &mut Vec::new())
+    }
+
+    /// Like `coerce`, but records the coercions applied to `expr`, if it
+    /// succeeds.
+    pub(super) fn coerce_expr(&mut self, expr: ExprId, from_ty: &Ty, to_ty: &Ty) -> bool {
+        let from_ty = self.resolve_ty_shallow(from_ty).into_owned();
+        let to_ty = self.resolve_ty_shallow(to_ty);
+        let mut coercions = Vec::new();
+        let coerced = self.coerce_inner(from_ty, &to_ty, &mut coercions);
+        if coerced && !coercions.is_empty() {
+            self.result.coercions.insert(expr, coercions);
+        }
+        coerced
     }
 
     /// Merge two types from different branches, with possible implicit coerce.
@@ -77,7 +94,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             .collect()
     }
 
-    fn coerce_inner(&mut self, mut from_ty: Ty, to_ty: &Ty) -> bool {
+    fn coerce_inner(&mut self, mut from_ty: Ty, to_ty: &Ty, coercions: &mut Vec<Coercion>) -> bool {
         match (&from_ty, to_ty) {
             // Never type will make type variable to fallback to Never Type instead of Unknown.
             (ty_app!(TypeCtor::Never), Ty::Infer(InferTy::TypeVar(tv))) => {
@@ -105,6 +122,9 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             | (ty_app!(c1@TypeCtor::Ref(_)), ty_app!(c2@TypeCtor::RawPtr(Mutability::Shared)))
             | (ty_app!(c1@TypeCtor::Ref(_)), ty_app!(c2@TypeCtor::Ref(Mutability::Shared)))
             | (ty_app!(c1@TypeCtor::Ref(Mutability::Mut)), ty_app!(c2@TypeCtor::RawPtr(_))) => {
+                if *c1 != *c2 {
+                    coercions.push(Coercion::RefAdjust);
+                }
                 *c1 = *c2;
             }
 
@@ -126,18 +146,23 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                         let num_args = sig.params_and_return.len() as u16 - 1;
                         from_ty =
                             Ty::apply(TypeCtor::FnPtr { num_args }, Substs(sig.params_and_return));
+                        coercions.push(Coercion::ReifyFnPointer);
                     }
                 }
             }
 
             (ty_app!(TypeCtor::Closure { .. }, params), ty_app!(TypeCtor::FnPtr { .. })) => {
                 from_ty = params[0].clone();
+                coercions.push(Coercion::ClosureFnPointer);
             }
 
             _ => {}
         }
 
         if let Some(ret) = self.try_coerce_unsized(&from_ty, &to_ty) {
+            if ret {
+                coercions.push(Coercion::Unsize);
+            }
             return ret;
         }
 
@@ -145,7 +170,13 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
         match (&from_ty, to_ty) {
             // FIXME: DerefMut
             (ty_app!(TypeCtor::Ref(_), st1), ty_app!(TypeCtor::Ref(_), st2)) => {
-                self.unify_autoderef_behind_ref(&st1[0], &st2[0])
+                match self.unify_autoderef_behind_ref(&st1[0], &st2[0]) {
+                    Some(derefs) => {
+                        coercions.extend(iter::repeat(Coercion::Deref).take(derefs));
+                        true
+                    }
+                    None => false,
+                }
             }
 
             // Otherwise, normal unify
@@ -196,7 +227,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
 
         match (&from_ty, &to_ty) {
             // `[T; N]` -> `[T]`
-            (ty_app!(TypeCtor::Array, st1), ty_app!(TypeCtor::Slice, st2)) => {
+            (ty_app!(TypeCtor::Array(_), st1), ty_app!(TypeCtor::Slice, st2)) => {
                 Some(self.unify(&st1[0], &st2[0]))
             }
 
@@ -307,36 +338,39 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
         }
     }
 
-    /// Unify `from_ty` to `to_ty` with optional auto Deref
+    /// Unify `from_ty` to `to_ty` with optional auto Deref, returning the number
+    /// of derefs applied if it succeeds.
     ///
     /// Note that the parameters are already stripped the outer reference.
-    fn unify_autoderef_behind_ref(&mut self, from_ty: &Ty, to_ty: &Ty) -> bool {
+    fn unify_autoderef_behind_ref(&mut self, from_ty: &Ty, to_ty: &Ty) -> Option<usize> {
         let canonicalized = self.canonicalizer().canonicalize_ty(from_ty.clone());
         let to_ty = self.resolve_ty_shallow(&to_ty);
         // FIXME: Auto DerefMut
-        for derefed_ty in autoderef::autoderef(
+        for (derefs, derefed_ty) in autoderef::autoderef(
             self.db,
             self.resolver.krate(),
             InEnvironment {
                 value: canonicalized.value.clone(),
                 environment: self.trait_env.clone(),
             },
-        ) {
+        )
+        .enumerate()
+        {
             let derefed_ty = canonicalized.decanonicalize_ty(derefed_ty.value);
             match (&*self.resolve_ty_shallow(&derefed_ty), &*to_ty) {
                 // Stop when constructor matches.
                 (ty_app!(from_ctor, st1), ty_app!(to_ctor, st2)) if from_ctor == to_ctor => {
                     // It will not recurse to `coerce`.
-                    return self.table.unify_substs(st1, st2, 0);
+                    return if self.table.unify_substs(st1, st2, 0) { Some(derefs) } else { None };
                 }
                 _ => {
                     if self.table.unify_inner_trivial(&derefed_ty, &to_ty) {
-                        return true;
+                        return Some(derefs);
                     }
                 }
             }
         }
 
-        false
+        None
     }
 }
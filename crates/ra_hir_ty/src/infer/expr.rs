@@ -8,7 +8,7 @@ use hir_def::{
     expr::{Array, BinaryOp, Expr, ExprId, Literal, Statement, UnaryOp},
     path::{GenericArg, GenericArgs},
     resolver::resolver_for_expr,
-    AdtId, AssocContainerId, Lookup, StructFieldId,
+    AdtId, AssocContainerId, AssocItemId, Lookup, StructFieldId,
 };
 use hir_expand::name::Name;
 use ra_syntax::ast::RangeOp;
@@ -19,8 +19,8 @@ use crate::{
     method_resolution, op,
     traits::InEnvironment,
     utils::{generics, variant_data, Generics},
-    ApplicationTy, Binders, CallableDef, InferTy, IntTy, Mutability, Obligation, Substs, TraitRef,
-    Ty, TypeCtor, Uncertain,
+    ApplicationTy, Binders, CallableDef, FnSig, InferTy, IntTy, Mutability, Obligation, Substs,
+    TraitRef, Ty, TypeCtor, Uncertain,
 };
 
 use super::{BindingMode, Expectation, InferenceContext, InferenceDiagnostic, TypeMismatch};
@@ -42,7 +42,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
     /// Return the type after possible coercion.
     pub(super) fn infer_expr_coerce(&mut self, expr: ExprId, expected: &Expectation) -> Ty {
         let ty = self.infer_expr_inner(expr, &expected);
-        let ty = if !self.coerce(&ty, &expected.ty) {
+        let ty = if !self.coerce_expr(expr, &ty, &expected.ty) {
             self.result
                 .type_mismatches
                 .insert(expr, TypeMismatch { expected: expected.ty.clone(), actual: ty.clone() });
@@ -64,7 +64,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Expr::Missing => Ty::Unknown,
             Expr::If { condition, then_branch, else_branch } => {
                 // if let is desugared to match, so this is always simple if
-                self.infer_expr(*condition, &Expectation::has_type(Ty::simple(TypeCtor::Bool)));
+                self.infer_expr(*condition, &Expectation::has_type(Ty::bool()));
 
                 let then_ty = self.infer_expr_inner(*then_branch, &expected);
                 let else_ty = match else_branch {
@@ -75,6 +75,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 self.coerce_merge_branch(&then_ty, &else_ty)
             }
             Expr::Block { statements, tail } => self.infer_block(statements, *tail, expected),
+            Expr::Unsafe { body } => self.infer_expr(*body, expected),
             Expr::TryBlock { body } => {
                 let _inner = self.infer_expr(*body, expected);
                 // FIXME should be std::result::Result<{inner}, _>
@@ -83,11 +84,11 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Expr::Loop { body } => {
                 self.infer_expr(*body, &Expectation::has_type(Ty::unit()));
                 // FIXME handle break with value
-                Ty::simple(TypeCtor::Never)
+                Ty::never()
             }
             Expr::While { condition, body } => {
                 // while let is desugared to a match loop, so this is always simple while
-                self.infer_expr(*condition, &Expectation::has_type(Ty::simple(TypeCtor::Bool)));
+                self.infer_expr(*condition, &Expectation::has_type(Ty::bool()));
                 self.infer_expr(*body, &Expectation::has_type(Ty::unit()));
                 Ty::unit()
             }
@@ -122,9 +123,11 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                     None => self.table.new_type_var(),
                 };
                 sig_tys.push(ret_ty.clone());
+                let sig_substs = Substs(sig_tys.into());
+                self.result.closure_sigs.insert(tgt_expr, FnSig::from_fn_ptr_substs(&sig_substs));
                 let sig_ty = Ty::apply(
-                    TypeCtor::FnPtr { num_args: sig_tys.len() as u16 - 1 },
-                    Substs(sig_tys.into()),
+                    TypeCtor::FnPtr { num_args: sig_substs.len() as u16 - 1 },
+                    sig_substs,
                 );
                 let closure_ty =
                     Ty::apply_one(TypeCtor::Closure { def: self.owner, expr: tgt_expr }, sig_ty);
@@ -145,7 +148,10 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Expr::Call { callee, args } => {
                 let callee_ty = self.infer_expr(*callee, &Expectation::none());
                 let (param_tys, ret_ty) = match callee_ty.callable_sig(self.db) {
-                    Some(sig) => (sig.params().to_vec(), sig.ret().clone()),
+                    Some(sig) => {
+                        self.check_arg_count(tgt_expr, &callee_ty, sig.params().len(), args.len());
+                        (sig.params().to_vec(), sig.ret().clone())
+                    }
                     None => {
                         // Not callable
                         // FIXME: report an error
@@ -161,19 +167,13 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Expr::Match { expr, arms } => {
                 let input_ty = self.infer_expr(*expr, &Expectation::none());
 
-                let mut result_ty = if arms.is_empty() {
-                    Ty::simple(TypeCtor::Never)
-                } else {
-                    self.table.new_type_var()
-                };
+                let mut result_ty =
+                    if arms.is_empty() { Ty::never() } else { self.table.new_type_var() };
 
                 for arm in arms {
                     let _pat_ty = self.infer_pat(arm.pat, &input_ty, BindingMode::default());
                     if let Some(guard_expr) = arm.guard {
-                        self.infer_expr(
-                            guard_expr,
-                            &Expectation::has_type(Ty::simple(TypeCtor::Bool)),
-                        );
+                        self.infer_expr(guard_expr, &Expectation::has_type(Ty::bool()));
                     }
 
                     let arm_ty = self.infer_expr_inner(arm.expr, &expected);
@@ -187,13 +187,13 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 let resolver = resolver_for_expr(self.db, self.owner, tgt_expr);
                 self.infer_path(&resolver, p, tgt_expr.into()).unwrap_or(Ty::Unknown)
             }
-            Expr::Continue => Ty::simple(TypeCtor::Never),
+            Expr::Continue => Ty::never(),
             Expr::Break { expr } => {
                 if let Some(expr) = expr {
                     // FIXME handle break with value
                     self.infer_expr(*expr, &Expectation::none());
                 }
-                Ty::simple(TypeCtor::Never)
+                Ty::never()
             }
             Expr::Return { expr } => {
                 if let Some(expr) = expr {
@@ -202,7 +202,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                     let unit = Ty::unit();
                     self.coerce(&unit, &self.return_ty.clone());
                 }
-                Ty::simple(TypeCtor::Never)
+                Ty::never()
             }
             Expr::RecordLit { path, fields, spread } => {
                 let (ty, def_id) = self.resolve_variant(path.as_ref());
@@ -266,8 +266,15 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                                     .subst(&a_ty.parameters)
                             })
                         }
-                        // FIXME:
-                        TypeCtor::Adt(AdtId::UnionId(_)) => None,
+                        TypeCtor::Adt(AdtId::UnionId(u)) => {
+                            self.db.union_data(u).variant_data.field(name).map(|local_id| {
+                                let field = StructFieldId { parent: u.into(), local_id };
+                                self.write_field_resolution(tgt_expr, field);
+                                self.db.field_types(u.into())[field.local_id]
+                                    .clone()
+                                    .subst(&a_ty.parameters)
+                            })
+                        }
                         _ => None,
                     },
                     _ => None,
@@ -373,7 +380,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Expr::BinaryOp { lhs, rhs, op } => match op {
                 Some(op) => {
                     let lhs_expectation = match op {
-                        BinaryOp::LogicOp(..) => Expectation::has_type(Ty::simple(TypeCtor::Bool)),
+                        BinaryOp::LogicOp(..) => Expectation::has_type(Ty::bool()),
                         _ => Expectation::none(),
                     };
                     let lhs_ty = self.infer_expr(*lhs, &lhs_expectation);
@@ -454,17 +461,16 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             }
             Expr::Array(array) => {
                 let elem_ty = match &expected.ty {
-                    ty_app!(TypeCtor::Array, st) | ty_app!(TypeCtor::Slice, st) => {
-                        st.as_single().clone()
-                    }
+                    ty_app!(TypeCtor::Array(_), st) | ty_app!(TypeCtor::Slice, st) => st[0].clone(),
                     _ => self.table.new_type_var(),
                 };
 
-                match array {
+                let len = match array {
                     Array::ElementList(items) => {
                         for expr in items.iter() {
                             self.infer_expr_coerce(*expr, &Expectation::has_type(elem_ty.clone()));
                         }
+                        Some(items.len() as u64)
                     }
                     Array::Repeat { initializer, repeat } => {
                         self.infer_expr_coerce(
@@ -477,22 +483,24 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                                 IntTy::usize(),
                             )))),
                         );
+                        match &self.body[*repeat] {
+                            Expr::Literal(Literal::Int(len, _)) => Some(*len),
+                            _ => None,
+                        }
                     }
-                }
+                };
 
-                Ty::apply_one(TypeCtor::Array, elem_ty)
+                Ty::array(elem_ty, len)
             }
             Expr::Literal(lit) => match lit {
-                Literal::Bool(..) => Ty::simple(TypeCtor::Bool),
-                Literal::String(..) => {
-                    Ty::apply_one(TypeCtor::Ref(Mutability::Shared), Ty::simple(TypeCtor::Str))
-                }
+                Literal::Bool(..) => Ty::bool(),
+                Literal::String(..) => Ty::apply_one(TypeCtor::Ref(Mutability::Shared), Ty::str()),
                 Literal::ByteString(..) => {
                     let byte_type = Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::u8())));
                     let slice_type = Ty::apply_one(TypeCtor::Slice, byte_type);
                     Ty::apply_one(TypeCtor::Ref(Mutability::Shared), slice_type)
                 }
-                Literal::Char(..) => Ty::simple(TypeCtor::Char),
+                Literal::Char(..) => Ty::char(),
                 Literal::Int(_v, ty) => Ty::simple(TypeCtor::Int((*ty).into())),
                 Literal::Float(_v, ty) => Ty::simple(TypeCtor::Float((*ty).into())),
             },
@@ -546,7 +554,7 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             Ty::unit()
         };
         if diverges {
-            Ty::simple(TypeCtor::Never)
+            Ty::never()
         } else {
             ty
         }
@@ -576,12 +584,16 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
             )
         });
         let (derefed_receiver_ty, method_ty, def_generics) = match resolved {
-            Some((ty, func)) => {
+            Some((ty, func, adjustment)) => {
                 let ty = canonicalized_receiver.decanonicalize_ty(ty);
                 self.write_method_resolution(tgt_expr, func);
+                self.write_receiver_adjustment(tgt_expr, adjustment);
                 (ty, self.db.value_ty(func.into()), Some(generics(self.db, func.into())))
             }
-            None => (receiver_ty, Binders::new(0, Ty::Unknown), None),
+            None => {
+                self.check_assoc_fn_called_as_method(tgt_expr, receiver, &receiver_ty, method_name);
+                (receiver_ty, Binders::new(0, Ty::Unknown), None)
+            }
         };
         let substs = self.substs_for_method_call(def_generics, generic_args, &derefed_receiver_ty);
         let method_ty = method_ty.subst(&substs);
@@ -590,6 +602,8 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
         let (expected_receiver_ty, param_tys, ret_ty) = match method_ty.callable_sig(self.db) {
             Some(sig) => {
                 if !sig.params().is_empty() {
+                    // The receiver is the first parameter, but isn't among `args`.
+                    self.check_arg_count(tgt_expr, &method_ty, sig.params().len() - 1, args.len());
                     (sig.params()[0].clone(), sig.params()[1..].to_vec(), sig.ret().clone())
                 } else {
                     (Ty::Unknown, Vec::new(), sig.ret().clone())
@@ -609,6 +623,67 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
         self.normalize_associated_types_in(ret_ty)
     }
 
+    fn check_arg_count(&mut self, call: ExprId, callee_ty: &Ty, expected: usize, found: usize) {
+        if expected == found {
+            return;
+        }
+        if let ty_app!(TypeCtor::FnDef(CallableDef::FunctionId(func))) = callee_ty {
+            if self.db.function_data(*func).is_varargs {
+                return;
+            }
+        }
+        self.push_diagnostic(InferenceDiagnostic::MismatchedArgCount { call, expected, found });
+    }
+
+    /// Called when method resolution failed, to catch `Type.new()` and similar
+    /// calls of associated functions without a `self` parameter.
+    fn check_assoc_fn_called_as_method(
+        &mut self,
+        tgt_expr: ExprId,
+        receiver: ExprId,
+        receiver_ty: &Ty,
+        method_name: &Name,
+    ) {
+        let self_ty = match (receiver_ty, &self.body[receiver]) {
+            // The receiver is most likely a type used as a value, as in `Type.new()`
+            (Ty::Unknown, Expr::Path(path)) => {
+                let resolver = resolver_for_expr(self.db, self.owner, receiver);
+                let ctx = crate::lower::TyLoweringContext::new(self.db, &resolver);
+                Ty::from_hir_path(&ctx, path)
+            }
+            _ => receiver_ty.clone(),
+        };
+        if let Ty::Unknown = self_ty {
+            return;
+        }
+        let krate = match self.resolver.krate() {
+            Some(krate) => krate,
+            None => return,
+        };
+        let canonical_ty = self.canonicalizer().canonicalize_ty(self_ty);
+        let traits_in_scope = self.resolver.traits_in_scope(self.db);
+        let func = method_resolution::iterate_method_candidates(
+            &canonical_ty.value,
+            self.db,
+            self.trait_env.clone(),
+            krate,
+            &traits_in_scope,
+            Some(method_name),
+            method_resolution::LookupMode::Path,
+            |_ty, item| match item {
+                // Functions with `self` failed method resolution for another reason
+                AssocItemId::FunctionId(f) if !self.db.function_data(f).has_self_param => Some(f),
+                _ => None,
+            },
+        );
+        if let Some(func) = func {
+            self.push_diagnostic(InferenceDiagnostic::AssocFnCalledAsMethod {
+                expr: tgt_expr,
+                func,
+            });
+        }
+    }
+
     fn check_call_arguments(&mut self, args: &[ExprId], param_tys: &[Ty]) {
         // Quoting https://github.com/rust-lang/rust/blob/6ef275e6c3cb1384ec78128eceeb4963ff788dca/src/librustc_typeck/check/mod.rs#L3325 --
         // We do this in a pretty awful way: first we type-check any arguments
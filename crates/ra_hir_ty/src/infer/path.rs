@@ -63,6 +63,10 @@ impl<'a, D: HirDatabase> InferenceContext<'a, D> {
                 let ty = self.resolve_ty_as_possible(ty);
                 return Some(ty);
             }
+            ValueNs::ConstParam(it) => {
+                let params = self.db.generic_params(it.parent);
+                return Some(self.make_ty(&params.consts[it.local_id].ty));
+            }
             ValueNs::FunctionId(it) => it.into(),
             ValueNs::ConstId(it) => it.into(),
             ValueNs::StaticId(it) => it.into(),
@@ -3,6 +3,7 @@
 use std::borrow::Cow;
 
 use ena::unify::{InPlaceUnificationTable, NoError, UnifyKey, UnifyValue};
+use rustc_hash::FxHashMap;
 
 use test_utils::tested_by;
 
@@ -181,6 +182,32 @@ pub fn unify(ty1: &Canonical<Ty>, ty2: &Canonical<Ty>) -> Option<Substs> {
     )
 }
 
+/// Checks whether `ty1` and `ty2` could be unified, using a fresh table so
+/// that no inference state is affected. Inference variables from whatever
+/// context the types came from are replaced by new ones, so they act as
+/// wildcards just like `Ty::Unknown`.
+pub(crate) fn could_unify(ty1: &Ty, ty2: &Ty) -> bool {
+    let mut table = InferenceTable::new();
+    let mut vars = FxHashMap::default();
+    let mut replace_vars = |ty: Ty| {
+        ty.fold(&mut |ty| match ty {
+            Ty::Infer(tv) => vars
+                .entry(tv)
+                .or_insert_with(|| match tv {
+                    InferTy::TypeVar(_) => table.new_type_var(),
+                    InferTy::IntVar(_) => table.new_integer_var(),
+                    InferTy::FloatVar(_) => table.new_float_var(),
+                    InferTy::MaybeNeverTypeVar(_) => table.new_maybe_never_type_var(),
+                })
+                .clone(),
+            ty => ty,
+        })
+    };
+    let ty1 = replace_vars(ty1.clone());
+    let ty2 = replace_vars(ty2.clone());
+    table.unify(&ty1, &ty2)
+}
+
 #[derive(Clone, Debug)]
 pub(crate) struct InferenceTable {
     pub(super) var_unification_table: InPlaceUnificationTable<TypeVarId>,
@@ -240,6 +267,12 @@ impl InferenceTable {
             (Ty::Apply(a_ty1), Ty::Apply(a_ty2)) if a_ty1.ctor == a_ty2.ctor => {
                 self.unify_substs(&a_ty1.parameters, &a_ty2.parameters, depth + 1)
             }
+            // an unknown array length unifies with any length
+            (ty_app!(TypeCtor::Array(len1), st1), ty_app!(TypeCtor::Array(len2), st2))
+                if len1.is_none() || len2.is_none() =>
+            {
+                self.unify_substs(st1, st2, depth + 1)
+            }
             _ => self.unify_inner_trivial(&ty1, &ty2),
         }
     }
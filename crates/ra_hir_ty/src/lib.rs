@@ -24,7 +24,7 @@ mod autoderef;
 pub mod primitive;
 pub mod traits;
 pub mod method_resolution;
-mod op;
+pub mod op;
 mod lower;
 mod infer;
 pub mod display;
@@ -44,10 +44,13 @@ use std::sync::Arc;
 use std::{iter, mem};
 
 use hir_def::{
-    expr::ExprId, type_ref::Mutability, AdtId, AssocContainerId, DefWithBodyId, GenericDefId,
-    HasModule, Lookup, TraitId, TypeAliasId, TypeParamId,
+    adt::ReprData, expr::ExprId, lang_item::LangItemTarget, type_ref::Mutability, AdtId,
+    AssocContainerId, DefWithBodyId, GenericDefId, HasModule, Lookup, TraitId, TypeAliasId,
+    TypeParamId,
 };
+use once_cell::sync::Lazy;
 use ra_db::{impl_intern_key, salsa, CrateId};
+use rustc_hash::FxHashMap;
 
 use crate::{
     db::HirDatabase,
@@ -56,8 +59,8 @@ use crate::{
 };
 use display::HirDisplay;
 
-pub use autoderef::autoderef;
-pub use infer::{do_infer_query, InferTy, InferenceResult};
+pub use autoderef::{autoderef, autoderef_with_steps, DerefKind};
+pub use infer::{do_infer_query, Coercion, InferTy, InferenceResult, ReceiverAdjustment};
 pub use lower::CallableDef;
 pub use lower::{
     callable_item_sig, ImplTraitLoweringMode, TyDefId, TyLoweringContext, ValueTyDefId,
@@ -91,8 +94,12 @@ pub enum TypeCtor {
     /// The pointee of an array slice.  Written as `[T]`.
     Slice,
 
-    /// An array with the given length. Written as `[T; n]`.
-    Array,
+    /// An array with the given length, `None` if it could not be evaluated.
+    /// Written as `[T; n]`.
+    ///
+    /// An unknown length unifies with any length during inference. Chalk
+    /// doesn't know about lengths at all, see `Ty::to_chalk`.
+    Array(Option<u64>),
 
     /// A raw pointer. Written as `*mut T` or `*const T`
     RawPtr(Mutability),
@@ -161,7 +168,7 @@ impl TypeCtor {
             | TypeCtor::Str
             | TypeCtor::Never => 0,
             TypeCtor::Slice
-            | TypeCtor::Array
+            | TypeCtor::Array(_)
             | TypeCtor::RawPtr(_)
             | TypeCtor::Ref(_)
             | TypeCtor::Closure { .. } // 1 param representing the signature of the closure
@@ -192,7 +199,7 @@ impl TypeCtor {
             | TypeCtor::Str
             | TypeCtor::Never
             | TypeCtor::Slice
-            | TypeCtor::Array
+            | TypeCtor::Array(_)
             | TypeCtor::RawPtr(_)
             | TypeCtor::Ref(_)
             | TypeCtor::FnPtr { .. }
@@ -214,7 +221,7 @@ impl TypeCtor {
             | TypeCtor::Str
             | TypeCtor::Never
             | TypeCtor::Slice
-            | TypeCtor::Array
+            | TypeCtor::Array(_)
             | TypeCtor::RawPtr(_)
             | TypeCtor::Ref(_)
             | TypeCtor::FnPtr { .. }
@@ -259,8 +266,8 @@ impl ProjectionTy {
 }
 
 impl TypeWalk for ProjectionTy {
-    fn walk(&self, f: &mut impl FnMut(&Ty)) {
-        self.parameters.walk(f);
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool {
+        self.parameters.try_walk(f)
     }
 
     fn walk_mut_binders(&mut self, f: &mut impl FnMut(&mut Ty, usize), binders: usize) {
@@ -329,10 +336,8 @@ pub enum Ty {
 pub struct Substs(Arc<[Ty]>);
 
 impl TypeWalk for Substs {
-    fn walk(&self, f: &mut impl FnMut(&Ty)) {
-        for t in self.0.iter() {
-            t.walk(f);
-        }
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool {
+        self.0.iter().any(|t| t.try_walk(f))
     }
 
     fn walk_mut_binders(&mut self, f: &mut impl FnMut(&mut Ty, usize), binders: usize) {
@@ -343,8 +348,11 @@ impl TypeWalk for Substs {
 }
 
 impl Substs {
+    /// Returns the empty substitution. All parameterless types share a single
+    /// allocation, so this is cheap to call on hot paths.
     pub fn empty() -> Substs {
-        Substs(Arc::new([]))
+        static EMPTY: Lazy<Substs> = Lazy::new(|| Substs(Arc::new([])));
+        EMPTY.clone()
     }
 
     pub fn single(ty: Ty) -> Substs {
@@ -355,6 +363,23 @@ impl Substs {
         Substs(self.0[..std::cmp::min(self.0.len(), n)].into())
     }
 
+    /// Splits into the first `n` parameters and the rest, e.g. to separate
+    /// the parent's parameters from an item's own ones.
+    pub fn split_at(&self, n: usize) -> (Substs, Substs) {
+        let (prefix, rest) = self.0.split_at(std::cmp::min(self.0.len(), n));
+        (Substs(prefix.into()), Substs(rest.into()))
+    }
+
+    /// For the substs of a trait ref or projection, where index 0 is always the
+    /// `Self` type, returns the trait's actual type arguments after `Self`.
+    pub fn type_args(&self) -> &[Ty] {
+        if self.0.is_empty() {
+            &[]
+        } else {
+            &self.0[1..]
+        }
+    }
+
     pub fn as_single(&self) -> &Ty {
         if self.0.len() != 1 {
             panic!("expected substs of len 1, got {:?}", self);
@@ -491,8 +516,8 @@ impl TraitRef {
 }
 
 impl TypeWalk for TraitRef {
-    fn walk(&self, f: &mut impl FnMut(&Ty)) {
-        self.substs.walk(f);
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool {
+        self.substs.try_walk(f)
     }
 
     fn walk_mut_binders(&mut self, f: &mut impl FnMut(&mut Ty, usize), binders: usize) {
@@ -528,6 +553,13 @@ impl GenericPredicate {
         }
     }
 
+    /// Whether the trait was resolved and none of its type arguments are
+    /// unknown. Unlike `is_error`, this is also `false` for predicates like
+    /// `T: Trait<{unknown}>`.
+    pub fn is_fully_known(&self) -> bool {
+        !self.is_error() && !self.contains_unknown()
+    }
+
     pub fn trait_ref(&self, db: &impl HirDatabase) -> Option<TraitRef> {
         match self {
             GenericPredicate::Implemented(tr) => Some(tr.clone()),
@@ -538,11 +570,11 @@ impl GenericPredicate {
 }
 
 impl TypeWalk for GenericPredicate {
-    fn walk(&self, f: &mut impl FnMut(&Ty)) {
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool {
         match self {
-            GenericPredicate::Implemented(trait_ref) => trait_ref.walk(f),
-            GenericPredicate::Projection(projection_pred) => projection_pred.walk(f),
-            GenericPredicate::Error => {}
+            GenericPredicate::Implemented(trait_ref) => trait_ref.try_walk(f),
+            GenericPredicate::Projection(projection_pred) => projection_pred.try_walk(f),
+            GenericPredicate::Error => false,
         }
     }
 
@@ -598,10 +630,8 @@ impl FnSig {
 }
 
 impl TypeWalk for FnSig {
-    fn walk(&self, f: &mut impl FnMut(&Ty)) {
-        for t in self.params_and_return.iter() {
-            t.walk(f);
-        }
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool {
+        self.params_and_return.iter().any(|t| t.try_walk(f))
     }
 
     fn walk_mut_binders(&mut self, f: &mut impl FnMut(&mut Ty, usize), binders: usize) {
@@ -622,7 +652,27 @@ impl Ty {
         Ty::Apply(ApplicationTy { ctor, parameters })
     }
     pub fn unit() -> Self {
-        Ty::apply(TypeCtor::Tuple { cardinality: 0 }, Substs::empty())
+        Ty::simple(TypeCtor::Tuple { cardinality: 0 })
+    }
+    pub fn bool() -> Ty {
+        Ty::simple(TypeCtor::Bool)
+    }
+    pub fn char() -> Ty {
+        Ty::simple(TypeCtor::Char)
+    }
+    pub fn str() -> Ty {
+        Ty::simple(TypeCtor::Str)
+    }
+    pub fn never() -> Ty {
+        Ty::simple(TypeCtor::Never)
+    }
+    /// The type integer literals fall back to, `i32`.
+    pub fn default_int() -> Ty {
+        Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::i32())))
+    }
+    /// The type float literals fall back to, `f64`.
+    pub fn default_float() -> Ty {
+        Ty::simple(TypeCtor::Float(Uncertain::Known(FloatTy::f64())))
     }
 
     pub fn as_reference(&self) -> Option<(&Ty, Mutability)> {
@@ -643,6 +693,15 @@ impl Ty {
         }
     }
 
+    /// Returns the `#[repr(...)]` of the ADT behind this type, if it has one.
+    pub fn adt_repr(&self, db: &impl HirDatabase) -> Option<ReprData> {
+        match self.as_adt()?.0 {
+            AdtId::StructId(it) => db.struct_data(it).repr.clone(),
+            AdtId::UnionId(it) => db.union_data(it).repr.clone(),
+            AdtId::EnumId(it) => db.enum_data(it).repr.clone(),
+        }
+    }
+
     pub fn as_tuple(&self) -> Option<&Substs> {
         match self {
             Ty::Apply(ApplicationTy { ctor: TypeCtor::Tuple { .. }, parameters }) => {
@@ -652,6 +711,31 @@ impl Ty {
         }
     }
 
+    /// Creates an array type `[elem; len]`.
+    pub fn array(elem: Ty, len: Option<u64>) -> Ty {
+        Ty::apply_one(TypeCtor::Array(len), elem)
+    }
+
+    /// Returns the element type and length of an array type `[T; N]`.
+    pub fn as_array(&self) -> Option<(&Ty, Option<u64>)> {
+        match self {
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Array(len), parameters }) => {
+                Some((parameters.as_single(), *len))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the element type of a slice type `[T]`.
+    pub fn as_slice(&self) -> Option<&Ty> {
+        match self {
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Slice, parameters }) => {
+                Some(parameters.as_single())
+            }
+            _ => None,
+        }
+    }
+
     pub fn as_callable(&self) -> Option<(CallableDef, &Substs)> {
         match self {
             Ty::Apply(ApplicationTy { ctor: TypeCtor::FnDef(callable_def), parameters }) => {
@@ -661,6 +745,15 @@ impl Ty {
         }
     }
 
+    pub fn as_closure(&self) -> Option<(DefWithBodyId, ExprId)> {
+        match self {
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Closure { def, expr }, .. }) => {
+                Some((*def, *expr))
+            }
+            _ => None,
+        }
+    }
+
     fn builtin_deref(&self) -> Option<Ty> {
         match self {
             Ty::Apply(a_ty) => match a_ty.ctor {
@@ -713,24 +806,270 @@ impl Ty {
         }
     }
 
+    /// Returns the number of generic arguments the type constructor takes, like
+    /// 2 for `HashMap<K, V>`. Types which aren't applications take none.
+    pub fn expected_generic_arg_count(&self, db: &impl HirDatabase) -> usize {
+        match self {
+            Ty::Apply(a_ty) => a_ty.ctor.num_ty_params(db),
+            _ => 0,
+        }
+    }
+
+    /// Returns the number of generic arguments that are known. Arguments left
+    /// out when writing the type are lowered to `{unknown}` unless they have a
+    /// default, so trailing unknown arguments are considered not provided.
+    pub fn provided_generic_arg_count(&self) -> usize {
+        match self.substs() {
+            Some(substs) => {
+                substs.len() - substs.iter().rev().take_while(|ty| **ty == Ty::Unknown).count()
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns how many generic arguments are missing, like 1 for `HashMap<K>`.
+    pub fn missing_generic_arg_count(&self, db: &impl HirDatabase) -> usize {
+        self.expected_generic_arg_count(db).saturating_sub(self.provided_generic_arg_count())
+    }
+
+    /// Returns `true` if values of the type are copied instead of moved.
+    /// Builtin types are checked structurally, other types by looking for an
+    /// impl of the `Copy` lang item. Unknown types are assumed to be `Copy`, so
+    /// that callers don't report moves they can't be sure about.
+    pub fn is_copy(
+        &self,
+        db: &impl HirDatabase,
+        env: Arc<TraitEnvironment>,
+        krate: CrateId,
+    ) -> bool {
+        let a_ty = match self {
+            Ty::Apply(a_ty) => a_ty,
+            Ty::Unknown | Ty::Infer(_) => return true,
+            _ => return self.implements_copy(db, env, krate),
+        };
+        match a_ty.ctor {
+            TypeCtor::Bool
+            | TypeCtor::Char
+            | TypeCtor::Int(_)
+            | TypeCtor::Float(_)
+            | TypeCtor::RawPtr(_)
+            | TypeCtor::Ref(Mutability::Shared)
+            | TypeCtor::FnDef(_)
+            | TypeCtor::FnPtr { .. }
+            | TypeCtor::Never => true,
+            // FIXME: closures are `Copy` if all their captures are
+            TypeCtor::Str
+            | TypeCtor::Slice
+            | TypeCtor::Ref(Mutability::Mut)
+            | TypeCtor::Closure { .. } => false,
+            TypeCtor::Array(_) | TypeCtor::Tuple { .. } => {
+                a_ty.parameters.iter().all(|ty| ty.is_copy(db, env.clone(), krate))
+            }
+            TypeCtor::Adt(_) | TypeCtor::AssociatedType(_) => self.implements_copy(db, env, krate),
+        }
+    }
+
+    fn implements_copy(
+        &self,
+        db: &impl HirDatabase,
+        env: Arc<TraitEnvironment>,
+        krate: CrateId,
+    ) -> bool {
+        let copy_trait = match db.lang_item(krate, "copy".into()) {
+            Some(LangItemTarget::TraitId(it)) => it,
+            // Without a `Copy` trait there's nothing to check against
+            _ => return true,
+        };
+        let ty = Canonical { value: self.clone(), num_vars: 0 };
+        method_resolution::implements_trait(&ty, db, env, krate, copy_trait)
+    }
+
+    /// Returns `true` if the type is fully known: it contains no unknown
+    /// types, inference variables or projections that couldn't be normalized.
+    pub fn is_resolved(&self) -> bool {
+        !self.any(&mut |ty| match ty {
+            Ty::Unknown | Ty::Infer(_) | Ty::Projection(_) => true,
+            _ => false,
+        })
+    }
+
     /// If this is an `impl Trait` or `dyn Trait`, returns that trait.
     pub fn inherent_trait(&self) -> Option<TraitId> {
+        self.principal_trait_ref().map(|it| it.trait_)
+    }
+
+    /// If this is an `impl Trait` or `dyn Trait`, returns the trait ref of
+    /// that trait. Its self type is `Ty::Bound(0)`.
+    pub fn principal_trait_ref(&self) -> Option<&TraitRef> {
         match self {
             Ty::Dyn(predicates) | Ty::Opaque(predicates) => {
                 predicates.iter().find_map(|pred| match pred {
-                    GenericPredicate::Implemented(tr) => Some(tr.trait_),
+                    GenericPredicate::Implemented(tr) => Some(tr),
                     _ => None,
                 })
             }
             _ => None,
         }
     }
+
+    /// Builds the trait object type `dyn Trait<Assoc = Ty>` from the trait ref
+    /// and the associated type bindings. Their self types are replaced by the
+    /// `Self` of the trait object, `Ty::Bound(0)`, and the other parameters
+    /// are shifted into that binder.
+    pub fn dyn_trait(trait_ref: TraitRef, bindings: Vec<ProjectionPredicate>) -> Ty {
+        let with_bound_self = |substs: Substs| {
+            let mut substs = substs.shift_bound_vars(1);
+            if let Some(self_ty) = make_mut_slice(&mut substs.0).first_mut() {
+                *self_ty = Ty::Bound(0);
+            }
+            substs
+        };
+        let principal = GenericPredicate::Implemented(TraitRef {
+            trait_: trait_ref.trait_,
+            substs: with_bound_self(trait_ref.substs),
+        });
+        let bindings = bindings.into_iter().map(|binding| {
+            GenericPredicate::Projection(ProjectionPredicate {
+                projection_ty: ProjectionTy {
+                    associated_ty: binding.projection_ty.associated_ty,
+                    parameters: with_bound_self(binding.projection_ty.parameters),
+                },
+                ty: binding.ty.shift_bound_vars(1),
+            })
+        });
+        Ty::Dyn(iter::once(principal).chain(bindings).collect())
+    }
+
+    /// Checks whether `self` and `other` are the same type up to a consistent
+    /// renaming of their free bound variables. So `(?0, ?0)` and `(?1, ?1)`
+    /// are alpha-equal, but `(?0, ?1)` and `(?0, ?0)` are not. This is the
+    /// right notion of equality for canonicalized types, whose variable
+    /// numbering depends on the order in which they were encountered.
+    pub fn alpha_eq(&self, other: &Ty) -> bool {
+        AlphaEq::default().tys(self, other, 0)
+    }
+
+    /// Checks whether `self` could be unified with `other`, treating unknown
+    /// types and inference variables as wildcards. This runs a throwaway
+    /// unification, so no inference state is affected. Associated type
+    /// projections aren't normalized, only compared structurally.
+    pub fn could_unify_with(&self, other: &Ty) -> bool {
+        infer::could_unify(self, other)
+    }
+}
+
+/// The state for `Ty::alpha_eq`: the bijection between the free bound
+/// variables of both sides seen so far.
+#[derive(Default)]
+struct AlphaEq {
+    left_to_right: FxHashMap<u32, u32>,
+    right_to_left: FxHashMap<u32, u32>,
+}
+
+impl AlphaEq {
+    fn tys(&mut self, left: &Ty, right: &Ty, binders: u32) -> bool {
+        match (left, right) {
+            (Ty::Apply(left), Ty::Apply(right)) => {
+                left.ctor == right.ctor && self.substs(&left.parameters, &right.parameters, binders)
+            }
+            (Ty::Projection(left), Ty::Projection(right)) => {
+                left.associated_ty == right.associated_ty
+                    && self.substs(&left.parameters, &right.parameters, binders)
+            }
+            (Ty::Dyn(left), Ty::Dyn(right)) | (Ty::Opaque(left), Ty::Opaque(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(left, right)| self.predicates(left, right, binders + 1))
+            }
+            (&Ty::Bound(left), &Ty::Bound(right)) => {
+                // variables bound inside the type (e.g. the `Self` of a `dyn
+                // Trait`) have to match exactly
+                if left < binders || right < binders {
+                    return left == right;
+                }
+                let (left, right) = (left - binders, right - binders);
+                *self.left_to_right.entry(left).or_insert(right) == right
+                    && *self.right_to_left.entry(right).or_insert(left) == left
+            }
+            _ => left == right,
+        }
+    }
+
+    fn substs(&mut self, left: &Substs, right: &Substs, binders: u32) -> bool {
+        left.len() == right.len()
+            && left.iter().zip(right.iter()).all(|(left, right)| self.tys(left, right, binders))
+    }
+
+    fn predicates(
+        &mut self,
+        left: &GenericPredicate,
+        right: &GenericPredicate,
+        binders: u32,
+    ) -> bool {
+        match (left, right) {
+            (GenericPredicate::Implemented(left), GenericPredicate::Implemented(right)) => {
+                left.trait_ == right.trait_ && self.substs(&left.substs, &right.substs, binders)
+            }
+            (GenericPredicate::Projection(left), GenericPredicate::Projection(right)) => {
+                left.projection_ty.associated_ty == right.projection_ty.associated_ty
+                    && self.substs(
+                        &left.projection_ty.parameters,
+                        &right.projection_ty.parameters,
+                        binders,
+                    )
+                    && self.tys(&left.ty, &right.ty, binders)
+            }
+            (GenericPredicate::Error, GenericPredicate::Error) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Returned by the callback of `TypeWalk::try_walk` to tell whether to visit
+/// the remaining types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalkControl {
+    Continue,
+    Break,
 }
 
 /// This allows walking structures that contain types to do something with those
 /// types, similar to Chalk's `Fold` trait.
 pub trait TypeWalk {
-    fn walk(&self, f: &mut impl FnMut(&Ty));
+    /// Visits all types in `self`, innermost first, until `f` returns
+    /// `Break`. Returns `true` if the walk was stopped that way.
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool;
+    fn walk(&self, f: &mut impl FnMut(&Ty)) {
+        self.try_walk(&mut |ty| {
+            f(ty);
+            WalkControl::Continue
+        });
+    }
+    /// Returns `true` if `f` returns `true` for any of the types visited by
+    /// `walk`. Stops at the first such type.
+    fn any(&self, f: &mut impl FnMut(&Ty) -> bool) -> bool {
+        self.try_walk(&mut |ty| {
+            if f(ty) {
+                WalkControl::Break
+            } else {
+                WalkControl::Continue
+            }
+        })
+    }
+    fn contains_unknown(&self) -> bool {
+        self.any(&mut |ty| match ty {
+            Ty::Unknown => true,
+            _ => false,
+        })
+    }
+    fn contains_infer(&self) -> bool {
+        self.any(&mut |ty| match ty {
+            Ty::Infer(_) => true,
+            _ => false,
+        })
+    }
     fn walk_mut(&mut self, f: &mut impl FnMut(&mut Ty)) {
         self.walk_mut_binders(&mut |ty, _binders| f(ty), 0);
     }
@@ -794,26 +1133,16 @@ pub trait TypeWalk {
 }
 
 impl TypeWalk for Ty {
-    fn walk(&self, f: &mut impl FnMut(&Ty)) {
-        match self {
-            Ty::Apply(a_ty) => {
-                for t in a_ty.parameters.iter() {
-                    t.walk(f);
-                }
-            }
-            Ty::Projection(p_ty) => {
-                for t in p_ty.parameters.iter() {
-                    t.walk(f);
-                }
-            }
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool {
+        let stopped = match self {
+            Ty::Apply(a_ty) => a_ty.parameters.try_walk(f),
+            Ty::Projection(p_ty) => p_ty.parameters.try_walk(f),
             Ty::Dyn(predicates) | Ty::Opaque(predicates) => {
-                for p in predicates.iter() {
-                    p.walk(f);
-                }
+                predicates.iter().any(|p| p.try_walk(f))
             }
-            Ty::Placeholder { .. } | Ty::Bound(_) | Ty::Infer(_) | Ty::Unknown => {}
-        }
-        f(self);
+            Ty::Placeholder { .. } | Ty::Bound(_) | Ty::Infer(_) | Ty::Unknown => false,
+        };
+        stopped || f(self) == WalkControl::Break
     }
 
     fn walk_mut_binders(&mut self, f: &mut impl FnMut(&mut Ty, usize), binders: usize) {
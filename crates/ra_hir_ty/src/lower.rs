@@ -19,6 +19,7 @@ use hir_def::{
     ImplId, LocalStructFieldId, Lookup, StaticId, StructId, TraitId, TypeAliasId, TypeParamId,
     UnionId, VariantId,
 };
+use hir_expand::name::Name;
 use ra_arena::map::ArenaMap;
 use ra_db::CrateId;
 
@@ -92,7 +93,7 @@ pub enum TypeParamLoweringMode {
 impl Ty {
     pub fn from_hir(ctx: &TyLoweringContext<'_, impl HirDatabase>, type_ref: &TypeRef) -> Self {
         match type_ref {
-            TypeRef::Never => Ty::simple(TypeCtor::Never),
+            TypeRef::Never => Ty::never(),
             TypeRef::Tuple(inner) => {
                 let inner_tys: Arc<[Ty]> = inner.iter().map(|tr| Ty::from_hir(ctx, tr)).collect();
                 Ty::apply(
@@ -105,9 +106,9 @@ impl Ty {
                 let inner_ty = Ty::from_hir(ctx, inner);
                 Ty::apply_one(TypeCtor::RawPtr(*mutability), inner_ty)
             }
-            TypeRef::Array(inner) => {
+            TypeRef::Array(inner, len) => {
                 let inner_ty = Ty::from_hir(ctx, inner);
-                Ty::apply_one(TypeCtor::Array, inner_ty)
+                Ty::array(inner_ty, *len)
             }
             TypeRef::Slice(inner) => {
                 let inner_ty = Ty::from_hir(ctx, inner);
@@ -309,6 +310,27 @@ impl Ty {
         Ty::from_type_relative_path(ctx, ty, remaining_segments)
     }
 
+    pub(crate) fn from_where_predicate_target(
+        ctx: &TyLoweringContext<'_, impl HirDatabase>,
+        target: &WherePredicateTarget,
+    ) -> Ty {
+        match target {
+            WherePredicateTarget::TypeRef(type_ref) => Ty::from_hir(ctx, type_ref),
+            WherePredicateTarget::TypeParam(param_id) => {
+                let generic_def = ctx.resolver.generic_def().expect("generics in scope");
+                let generics = generics(ctx.db, generic_def);
+                let param_id = hir_def::TypeParamId { parent: generic_def, local_id: *param_id };
+                match ctx.type_param_mode {
+                    TypeParamLoweringMode::Placeholder => Ty::Placeholder(param_id),
+                    TypeParamLoweringMode::Variable => {
+                        let idx = generics.param_idx(param_id).expect("matching generics");
+                        Ty::Bound(idx)
+                    }
+                }
+            }
+        }
+    }
+
     pub(crate) fn from_hir_path(ctx: &TyLoweringContext<'_, impl HirDatabase>, path: &Path) -> Ty {
         // Resolve the path (in type namespace)
         if let Some(type_ref) = path.type_anchor() {
@@ -526,7 +548,7 @@ impl TraitRef {
     ) -> Option<TraitRef> {
         match bound {
             TypeBound::Path(path) => TraitRef::from_path(ctx, path, Some(self_ty)),
-            TypeBound::Error => None,
+            TypeBound::Maybe(_) | TypeBound::Error => None,
         }
     }
 }
@@ -536,21 +558,7 @@ impl GenericPredicate {
         ctx: &'a TyLoweringContext<'a, impl HirDatabase>,
         where_predicate: &'a WherePredicate,
     ) -> impl Iterator<Item = GenericPredicate> + 'a {
-        let self_ty = match &where_predicate.target {
-            WherePredicateTarget::TypeRef(type_ref) => Ty::from_hir(ctx, type_ref),
-            WherePredicateTarget::TypeParam(param_id) => {
-                let generic_def = ctx.resolver.generic_def().expect("generics in scope");
-                let generics = generics(ctx.db, generic_def);
-                let param_id = hir_def::TypeParamId { parent: generic_def, local_id: *param_id };
-                match ctx.type_param_mode {
-                    TypeParamLoweringMode::Placeholder => Ty::Placeholder(param_id),
-                    TypeParamLoweringMode::Variable => {
-                        let idx = generics.param_idx(param_id).expect("matching generics");
-                        Ty::Bound(idx)
-                    }
-                }
-            }
-        };
+        let self_ty = Ty::from_where_predicate_target(ctx, &where_predicate.target);
         GenericPredicate::from_type_bound(ctx, &where_predicate.bound, self_ty)
     }
 
@@ -560,12 +568,18 @@ impl GenericPredicate {
         self_ty: Ty,
     ) -> impl Iterator<Item = GenericPredicate> + 'a {
         let trait_ref = TraitRef::from_type_bound(ctx, bound, self_ty);
-        iter::once(trait_ref.clone().map_or(GenericPredicate::Error, GenericPredicate::Implemented))
-            .chain(
-                trait_ref
-                    .into_iter()
-                    .flat_map(move |tr| assoc_type_bindings_from_type_bound(ctx, bound, tr)),
-            )
+        let implemented = match bound {
+            // Maybe-bounds don't add any predicate
+            TypeBound::Maybe(_) => None,
+            _ => Some(
+                trait_ref.clone().map_or(GenericPredicate::Error, GenericPredicate::Implemented),
+            ),
+        };
+        implemented.into_iter().chain(
+            trait_ref
+                .into_iter()
+                .flat_map(move |tr| assoc_type_bindings_from_type_bound(ctx, bound, tr)),
+        )
     }
 }
 
@@ -576,7 +590,7 @@ fn assoc_type_bindings_from_type_bound<'a>(
 ) -> impl Iterator<Item = GenericPredicate> + 'a {
     let last_segment = match bound {
         TypeBound::Path(path) => path.segments().last(),
-        TypeBound::Error => None,
+        TypeBound::Maybe(_) | TypeBound::Error => None,
     };
     last_segment
         .into_iter()
@@ -722,6 +736,28 @@ pub(crate) fn generic_predicates_query(
         .collect()
 }
 
+/// Resolve the relaxed bounds like `T: ?Sized` of `def`, along with the name
+/// of the relaxed trait. These aren't predicates, so they're only used for
+/// display.
+pub(crate) fn maybe_bounds(db: &impl HirDatabase, def: GenericDefId) -> Vec<(Binders<Ty>, Name)> {
+    let resolver = def.resolver(db);
+    let ctx =
+        TyLoweringContext::new(db, &resolver).with_type_param_mode(TypeParamLoweringMode::Variable);
+    let generics = generics(db, def);
+    resolver
+        .where_predicates_in_scope()
+        .filter_map(|pred| {
+            let path = match &pred.bound {
+                TypeBound::Maybe(path) => path,
+                _ => return None,
+            };
+            let name = path.segments().last()?.name.clone();
+            let ty = Ty::from_where_predicate_target(&ctx, &pred.target);
+            Some((Binders::new(generics.len(), ty), name))
+        })
+        .collect()
+}
+
 /// Resolve the default type params from generics
 pub(crate) fn generic_defaults_query(db: &impl HirDatabase, def: GenericDefId) -> Substs {
     let resolver = def.resolver(db);
@@ -20,7 +20,8 @@ use crate::{
     db::HirDatabase,
     primitive::{FloatBitness, Uncertain},
     utils::all_super_traits,
-    Canonical, InEnvironment, TraitEnvironment, TraitRef, Ty, TypeCtor, TypeWalk,
+    ApplicationTy, Canonical, InEnvironment, ReceiverAdjustment, TraitEnvironment, TraitRef, Ty,
+    TypeCtor, TypeWalk,
 };
 
 /// This is used as a key for indexing impls.
@@ -35,6 +36,10 @@ impl TyFingerprint {
     /// `impl &S`. Hence, this will return `None` for reference types and such.
     fn for_impl(ty: &Ty) -> Option<TyFingerprint> {
         match ty {
+            // impls on arrays apply to arrays of any length
+            Ty::Apply(ApplicationTy { ctor: TypeCtor::Array(_), .. }) => {
+                Some(TyFingerprint::Apply(TypeCtor::Array(None)))
+            }
             Ty::Apply(a_ty) => Some(TyFingerprint::Apply(a_ty.ctor)),
             _ => None,
         }
@@ -140,7 +145,8 @@ impl Ty {
     }
 }
 /// Look up the method with the given name, returning the actual autoderefed
-/// receiver type (but without autoref applied yet).
+/// receiver type (but without autoref applied yet), and the adjustment that
+/// was applied to the receiver to find it.
 pub(crate) fn lookup_method(
     ty: &Canonical<Ty>,
     db: &impl HirDatabase,
@@ -148,22 +154,69 @@ pub(crate) fn lookup_method(
     krate: CrateId,
     traits_in_scope: &FxHashSet<TraitId>,
     name: &Name,
-) -> Option<(Ty, FunctionId)> {
-    iterate_method_candidates(
+) -> Option<(Ty, FunctionId, ReceiverAdjustment)> {
+    iterate_method_candidates_with_adjustment(
         ty,
         db,
         env,
         krate,
         &traits_in_scope,
         Some(name),
-        LookupMode::MethodCall,
-        |ty, f| match f {
-            AssocItemId::FunctionId(f) => Some((ty.clone(), f)),
+        |ty, f, adjustment| match f {
+            AssocItemId::FunctionId(f) => Some((ty.clone(), f, adjustment)),
             _ => None,
         },
     )
 }
 
+/// A method resolved by `resolve_method_with_priority`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MethodResolution {
+    /// The autoderefed receiver type, without autoref applied.
+    pub self_ty: Ty,
+    pub func: FunctionId,
+    /// Whether `func` is an inherent method that takes priority over a trait
+    /// method of the same name, which also applies to the receiver.
+    pub shadows_trait_method: bool,
+}
+
+/// Like `lookup_method`, but also reports whether the resolved method shadows
+/// a trait method. Inherent methods always win over trait methods for the same
+/// receiver type, which might not be what the user expects.
+pub fn resolve_method_with_priority(
+    ty: &Canonical<Ty>,
+    db: &impl HirDatabase,
+    env: Arc<TraitEnvironment>,
+    krate: CrateId,
+    traits_in_scope: &FxHashSet<TraitId>,
+    name: &Name,
+) -> Option<MethodResolution> {
+    let (self_ty, func, _) = lookup_method(ty, db, env.clone(), krate, traits_in_scope, name)?;
+    let is_inherent = match func.lookup(db).container {
+        AssocContainerId::ImplId(impl_id) => db.impl_data(impl_id).target_trait.is_none(),
+        AssocContainerId::TraitId(_) | AssocContainerId::ContainerId(_) => false,
+    };
+    let shadows_trait_method = is_inherent
+        && iterate_method_candidates(
+            ty,
+            db,
+            env,
+            krate,
+            traits_in_scope,
+            Some(name),
+            LookupMode::MethodCall,
+            |_ty, item| match item {
+                AssocItemId::FunctionId(f) => match f.lookup(db).container {
+                    AssocContainerId::TraitId(_) => Some(()),
+                    _ => None,
+                },
+                _ => None,
+            },
+        )
+        .is_some();
+    Some(MethodResolution { self_ty, func, shadows_trait_method })
+}
+
 /// Whether we're looking up a dotted method call (like `v.len()`) or a path
 /// (like `Vec::new`).
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -190,46 +243,15 @@ pub fn iterate_method_candidates<T>(
     mut callback: impl FnMut(&Ty, AssocItemId) -> Option<T>,
 ) -> Option<T> {
     match mode {
-        LookupMode::MethodCall => {
-            // For method calls, rust first does any number of autoderef, and then one
-            // autoref (i.e. when the method takes &self or &mut self). We just ignore
-            // the autoref currently -- when we find a method matching the given name,
-            // we assume it fits.
-
-            // Also note that when we've got a receiver like &S, even if the method we
-            // find in the end takes &self, we still do the autoderef step (just as
-            // rustc does an autoderef and then autoref again).
-            let ty = InEnvironment { value: ty.clone(), environment: env.clone() };
-
-            // We have to be careful about the order we're looking at candidates
-            // in here. Consider the case where we're resolving `x.clone()`
-            // where `x: &Vec<_>`. This resolves to the clone method with self
-            // type `Vec<_>`, *not* `&_`. I.e. we need to consider methods where
-            // the receiver type exactly matches before cases where we have to
-            // do autoref. But in the autoderef steps, the `&_` self type comes
-            // up *before* the `Vec<_>` self type.
-            //
-            // On the other hand, we don't want to just pick any by-value method
-            // before any by-autoref method; it's just that we need to consider
-            // the methods by autoderef order of *receiver types*, not *self
-            // types*.
-
-            let deref_chain: Vec<_> = autoderef::autoderef(db, Some(krate), ty).collect();
-            for i in 0..deref_chain.len() {
-                if let Some(result) = iterate_method_candidates_with_autoref(
-                    &deref_chain[i..],
-                    db,
-                    env.clone(),
-                    krate,
-                    traits_in_scope,
-                    name,
-                    &mut callback,
-                ) {
-                    return Some(result);
-                }
-            }
-            None
-        }
+        LookupMode::MethodCall => iterate_method_candidates_with_adjustment(
+            ty,
+            db,
+            env,
+            krate,
+            traits_in_scope,
+            name,
+            |ty, item, _| callback(ty, item),
+        ),
         LookupMode::Path => {
             // No autoderef for path lookups
             iterate_method_candidates_for_self_ty(
@@ -245,15 +267,93 @@ pub fn iterate_method_candidates<T>(
     }
 }
 
+/// Iterates the method call candidates for the receiver `ty`, passing the
+/// adjustment applied to the receiver along with each candidate.
+fn iterate_method_candidates_with_adjustment<T>(
+    ty: &Canonical<Ty>,
+    db: &impl HirDatabase,
+    env: Arc<TraitEnvironment>,
+    krate: CrateId,
+    traits_in_scope: &FxHashSet<TraitId>,
+    name: Option<&Name>,
+    mut callback: impl FnMut(&Ty, AssocItemId, ReceiverAdjustment) -> Option<T>,
+) -> Option<T> {
+    // For method calls, rust first does any number of autoderef, and then one
+    // autoref (i.e. when the method takes &self or &mut self). We try the
+    // receiver types without autoref first, then with `&` and `&mut` -- when
+    // we find a method matching the given name, we assume it fits.
+
+    // Also note that when we've got a receiver like &S, even if the method we
+    // find in the end takes &self, we still do the autoderef step (just as
+    // rustc does an autoderef and then autoref again).
+    let in_env = InEnvironment { value: ty.clone(), environment: env.clone() };
+
+    // We have to be careful about the order we're looking at candidates
+    // in here. Consider the case where we're resolving `x.clone()`
+    // where `x: &Vec<_>`. This resolves to the clone method with self
+    // type `Vec<_>`, *not* `&_`. I.e. we need to consider methods where
+    // the receiver type exactly matches before cases where we have to
+    // do autoref. But in the autoderef steps, the `&_` self type comes
+    // up *before* the `Vec<_>` self type.
+    //
+    // On the other hand, we don't want to just pick any by-value method
+    // before any by-autoref method; it's just that we need to consider
+    // the methods by autoderef order of *receiver types*, not *self
+    // types*.
+
+    let mut deref_chain: Vec<_> = autoderef::autoderef(db, Some(krate), in_env).collect();
+    let autoderefs = deref_chain.len() - 1;
+    // Like rustc, if we end up with an array, we also try unsizing it to a
+    // slice, so that e.g. `[1, 2, 3].len()` finds the slice method.
+    let unsized_ty = deref_chain.last().and_then(|ty| match &ty.value {
+        Ty::Apply(ApplicationTy { ctor: TypeCtor::Array(_), parameters }) => Some(Canonical {
+            num_vars: ty.num_vars,
+            value: Ty::apply_one(TypeCtor::Slice, parameters[0].clone()),
+        }),
+        _ => None,
+    });
+    deref_chain.extend(unsized_ty);
+    for i in 0..deref_chain.len() {
+        let adjustment = ReceiverAdjustment {
+            autoderefs: i.min(autoderefs),
+            autoref: None,
+            unsize: i > autoderefs,
+        };
+        if let Some(result) = iterate_method_candidates_with_autoref(
+            &deref_chain[i..],
+            adjustment,
+            db,
+            env.clone(),
+            krate,
+            traits_in_scope,
+            name,
+            &mut callback,
+        ) {
+            return Some(result);
+        }
+    }
+    None
+}
+
 fn iterate_method_candidates_with_autoref<T>(
     deref_chain: &[Canonical<Ty>],
+    adjustment: ReceiverAdjustment,
     db: &impl HirDatabase,
     env: Arc<TraitEnvironment>,
     krate: CrateId,
     traits_in_scope: &FxHashSet<TraitId>,
     name: Option<&Name>,
-    mut callback: impl FnMut(&Ty, AssocItemId) -> Option<T>,
+    mut callback: impl FnMut(&Ty, AssocItemId, ReceiverAdjustment) -> Option<T>,
 ) -> Option<T> {
+    // Like rustc, we record passing a reference receiver as is as a reborrow
+    let by_value = match &deref_chain[0].value {
+        Ty::Apply(ApplicationTy { ctor: TypeCtor::Ref(mutability), .. }) => ReceiverAdjustment {
+            autoderefs: adjustment.autoderefs + 1,
+            autoref: Some(*mutability),
+            ..adjustment
+        },
+        _ => adjustment,
+    };
     if let Some(result) = iterate_method_candidates_by_receiver(
         &deref_chain[0],
         &deref_chain[1..],
@@ -262,7 +362,7 @@ fn iterate_method_candidates_with_autoref<T>(
         krate,
         &traits_in_scope,
         name,
-        &mut callback,
+        |ty, item| callback(ty, item, by_value),
     ) {
         return Some(result);
     }
@@ -278,7 +378,13 @@ fn iterate_method_candidates_with_autoref<T>(
         krate,
         &traits_in_scope,
         name,
-        &mut callback,
+        |ty, item| {
+            callback(
+                ty,
+                item,
+                ReceiverAdjustment { autoref: Some(Mutability::Shared), ..adjustment },
+            )
+        },
     ) {
         return Some(result);
     }
@@ -294,7 +400,9 @@ fn iterate_method_candidates_with_autoref<T>(
         krate,
         &traits_in_scope,
         name,
-        &mut callback,
+        |ty, item| {
+            callback(ty, item, ReceiverAdjustment { autoref: Some(Mutability::Mut), ..adjustment })
+        },
     ) {
         return Some(result);
     }
@@ -1,12 +1,23 @@
-//! Helper functions for binary operator type inference.
-use hir_def::expr::{ArithOp, BinaryOp, CmpOp};
+//! Helper functions for operator type inference.
+use std::iter;
+
+use hir_def::{
+    expr::{ArithOp, BinaryOp, CmpOp, Ordering, UnaryOp},
+    lang_item::LangItemTarget,
+    AssocItemId, FunctionId, ImplId, TraitId,
+};
+use hir_expand::name::{name, Name};
+use ra_db::CrateId;
 
 use super::{InferTy, Ty, TypeCtor};
-use crate::ApplicationTy;
+use crate::{
+    db::HirDatabase, infer::unify, utils::generics, ApplicationTy, Canonical, ProjectionTy, Substs,
+    TraitEnvironment, TypeWalk,
+};
 
 pub(super) fn binary_op_return_ty(op: BinaryOp, lhs_ty: Ty, rhs_ty: Ty) -> Ty {
     match op {
-        BinaryOp::LogicOp(_) | BinaryOp::CmpOp(_) => Ty::simple(TypeCtor::Bool),
+        BinaryOp::LogicOp(_) | BinaryOp::CmpOp(_) => Ty::bool(),
         BinaryOp::Assignment { .. } => Ty::unit(),
         BinaryOp::ArithOp(ArithOp::Shl) | BinaryOp::ArithOp(ArithOp::Shr) => match lhs_ty {
             Ty::Apply(ApplicationTy { ctor, .. }) => match ctor {
@@ -29,7 +40,7 @@ pub(super) fn binary_op_return_ty(op: BinaryOp, lhs_ty: Ty, rhs_ty: Ty) -> Ty {
 
 pub(super) fn binary_op_rhs_expectation(op: BinaryOp, lhs_ty: Ty) -> Ty {
     match op {
-        BinaryOp::LogicOp(..) => Ty::simple(TypeCtor::Bool),
+        BinaryOp::LogicOp(..) => Ty::bool(),
         BinaryOp::Assignment { op: None } | BinaryOp::CmpOp(CmpOp::Eq { .. }) => match lhs_ty {
             Ty::Apply(ApplicationTy { ctor, .. }) => match ctor {
                 TypeCtor::Int(..)
@@ -55,3 +66,227 @@ pub(super) fn binary_op_rhs_expectation(op: BinaryOp, lhs_ty: Ty) -> Ty {
         },
     }
 }
+
+/// Returns the lang item of the trait overloading `op`, together with the name
+/// of the trait method the operator desugars to.
+pub fn binary_op_trait_method(op: BinaryOp) -> Option<(&'static str, &'static str)> {
+    let res = match op {
+        BinaryOp::LogicOp(_) | BinaryOp::Assignment { op: None } => return None,
+        BinaryOp::ArithOp(op) => match op {
+            ArithOp::Add => ("add", "add"),
+            ArithOp::Mul => ("mul", "mul"),
+            ArithOp::Sub => ("sub", "sub"),
+            ArithOp::Div => ("div", "div"),
+            ArithOp::Rem => ("rem", "rem"),
+            ArithOp::Shl => ("shl", "shl"),
+            ArithOp::Shr => ("shr", "shr"),
+            ArithOp::BitXor => ("bitxor", "bitxor"),
+            ArithOp::BitOr => ("bitor", "bitor"),
+            ArithOp::BitAnd => ("bitand", "bitand"),
+        },
+        BinaryOp::Assignment { op: Some(op) } => match op {
+            ArithOp::Add => ("add_assign", "add_assign"),
+            ArithOp::Mul => ("mul_assign", "mul_assign"),
+            ArithOp::Sub => ("sub_assign", "sub_assign"),
+            ArithOp::Div => ("div_assign", "div_assign"),
+            ArithOp::Rem => ("rem_assign", "rem_assign"),
+            ArithOp::Shl => ("shl_assign", "shl_assign"),
+            ArithOp::Shr => ("shr_assign", "shr_assign"),
+            ArithOp::BitXor => ("bitxor_assign", "bitxor_assign"),
+            ArithOp::BitOr => ("bitor_assign", "bitor_assign"),
+            ArithOp::BitAnd => ("bitand_assign", "bitand_assign"),
+        },
+        BinaryOp::CmpOp(CmpOp::Eq { negated: false }) => ("eq", "eq"),
+        BinaryOp::CmpOp(CmpOp::Eq { negated: true }) => ("eq", "ne"),
+        BinaryOp::CmpOp(CmpOp::Ord { ordering, strict }) => match (ordering, strict) {
+            (Ordering::Less, true) => ("partial_ord", "lt"),
+            (Ordering::Less, false) => ("partial_ord", "le"),
+            (Ordering::Greater, true) => ("partial_ord", "gt"),
+            (Ordering::Greater, false) => ("partial_ord", "ge"),
+        },
+    };
+    Some(res)
+}
+
+/// Like `binary_op_trait_method`, but for prefix operators. Dereferencing is
+/// not included, since `*` on references and pointers is built in.
+pub fn unary_op_trait_method(op: UnaryOp) -> Option<(&'static str, &'static str)> {
+    match op {
+        UnaryOp::Neg => Some(("neg", "neg")),
+        UnaryOp::Not => Some(("not", "not")),
+        UnaryOp::Deref => None,
+    }
+}
+
+/// Resolves the overloaded binary operator `op` applied to operands of types
+/// `lhs_ty` and `rhs_ty` to the operator trait, the trait method the operator
+/// desugars to, and the type of the whole expression.
+///
+/// If no impl applies, but `env` says that `lhs_ty` (e.g. a type parameter)
+/// implements the operator trait, the trait's own method is returned.
+pub fn resolve_binary_op(
+    db: &impl HirDatabase,
+    krate: CrateId,
+    env: &TraitEnvironment,
+    op: BinaryOp,
+    lhs_ty: &Ty,
+    rhs_ty: &Ty,
+) -> Option<(TraitId, FunctionId, Ty)> {
+    let trait_method = binary_op_trait_method(op)?;
+    resolve_op(db, krate, env, trait_method, &name![Output], lhs_ty, Some(rhs_ty))
+}
+
+/// Like `resolve_binary_op`, but for prefix operators. For `*`, the output is
+/// the `Deref::Target` of the operand. Dereferencing references and pointers
+/// is built in, so it isn't resolved to a trait method.
+pub fn resolve_unary_op(
+    db: &impl HirDatabase,
+    krate: CrateId,
+    env: &TraitEnvironment,
+    op: UnaryOp,
+    operand_ty: &Ty,
+) -> Option<(TraitId, FunctionId, Ty)> {
+    let (trait_method, output) = match op {
+        UnaryOp::Deref if operand_ty.builtin_deref().is_some() => return None,
+        UnaryOp::Deref => (("deref", "deref"), name![Target]),
+        _ => (unary_op_trait_method(op)?, name![Output]),
+    };
+    resolve_op(db, krate, env, trait_method, &output, operand_ty, None)
+}
+
+fn resolve_op(
+    db: &impl HirDatabase,
+    krate: CrateId,
+    env: &TraitEnvironment,
+    (lang_item, method): (&str, &str),
+    output: &Name,
+    lhs_ty: &Ty,
+    rhs_ty: Option<&Ty>,
+) -> Option<(TraitId, FunctionId, Ty)> {
+    let trait_ = op_trait(db, krate, lang_item)?;
+
+    if let Some((impl_id, substs)) = find_op_impl(db, krate, trait_, lhs_ty, rhs_ty) {
+        let impl_data = db.impl_data(impl_id);
+        let func = impl_data.items.iter().find_map(|&item| match item {
+            AssocItemId::FunctionId(f) if db.function_data(f).name.to_string() == method => Some(f),
+            _ => None,
+        })?;
+        let output = impl_data.items.iter().find_map(|&item| match item {
+            AssocItemId::TypeAliasId(t) if db.type_alias_data(t).name == *output => {
+                Some(db.ty(t.into()).subst(&substs))
+            }
+            _ => None,
+        });
+        // Only the assignment operators have no `Output`, and they return `()`
+        return Some((trait_, func, output.unwrap_or_else(Ty::unit)));
+    }
+
+    let trait_ref = env.trait_predicates_for_self_ty(lhs_ty).find(|tr| tr.trait_ == trait_)?;
+    let trait_data = db.trait_data(trait_);
+    let func = trait_data.items.iter().find_map(|(name, item)| match item {
+        AssocItemId::FunctionId(f) if name.to_string() == method => Some(*f),
+        _ => None,
+    })?;
+    let output = match trait_data.associated_type_by_name(output) {
+        Some(associated_ty) => {
+            Ty::Projection(ProjectionTy { associated_ty, parameters: trait_ref.substs.clone() })
+        }
+        None => Ty::unit(),
+    };
+    Some((trait_, func, output))
+}
+
+/// Finds the method named `method` in an impl of the operator trait `trait_`
+/// whose self type (and right-hand side type, for binary operators) could
+/// unify with the given operand types.
+pub fn find_op_impl_method(
+    db: &impl HirDatabase,
+    krate: CrateId,
+    trait_: TraitId,
+    method: &str,
+    lhs_ty: &Ty,
+    rhs_ty: Option<&Ty>,
+) -> Option<FunctionId> {
+    let (impl_id, _) = find_op_impl(db, krate, trait_, lhs_ty, rhs_ty)?;
+    db.impl_data(impl_id).items.iter().find_map(|&item| match item {
+        AssocItemId::FunctionId(f) if db.function_data(f).name.to_string() == method => Some(f),
+        _ => None,
+    })
+}
+
+fn op_trait(db: &impl HirDatabase, krate: CrateId, lang_item: &str) -> Option<TraitId> {
+    match db.lang_item(krate, lang_item.into())? {
+        LangItemTarget::TraitId(it) => Some(it),
+        _ => None,
+    }
+}
+
+/// Finds an impl of `trait_` applying to the operand types, together with
+/// the substitution for the impl's type parameters.
+fn find_op_impl(
+    db: &impl HirDatabase,
+    krate: CrateId,
+    trait_: TraitId,
+    lhs_ty: &Ty,
+    rhs_ty: Option<&Ty>,
+) -> Option<(ImplId, Substs)> {
+    // Inference variables in the operand types belong to some other
+    // inference context, so we can only treat them as unknown here.
+    let erase_vars = |ty: &Ty| {
+        ty.clone().fold(&mut |ty| match ty {
+            Ty::Infer(_) => Ty::Unknown,
+            ty => ty,
+        })
+    };
+    let lhs_ty = erase_vars(lhs_ty);
+    if lhs_ty == Ty::Unknown {
+        return None;
+    }
+    let rhs_ty = rhs_ty.map(erase_vars);
+    let operand_count = if rhs_ty.is_some() { 2 } else { 1 };
+    let operands =
+        Canonical { value: tuple(iter::once(lhs_ty).chain(rhs_ty.clone()).collect()), num_vars: 0 };
+
+    let trait_generics = generics(db, trait_.into());
+    let mut candidates = db.impls_for_trait(krate, trait_).iter().filter_map(|&impl_id| {
+        let impl_trait = db.impl_trait(impl_id)?;
+        // Defaulted trait parameters (e.g. `Rhs = Self`) are lowered as
+        // placeholders of the trait's own parameters, so resolve them
+        // against the impl's trait ref before comparing.
+        let trait_substs = &impl_trait.value.substs;
+        let impl_operands = trait_substs
+            .iter()
+            .take(operand_count)
+            .map(|ty| {
+                ty.clone().fold(&mut |ty| match ty {
+                    Ty::Placeholder(id) if id.parent == trait_.into() => trait_generics
+                        .param_idx(id)
+                        .and_then(|idx| trait_substs.get(idx as usize).cloned())
+                        .unwrap_or(Ty::Unknown),
+                    ty => ty,
+                })
+            })
+            .collect();
+        let impl_operands =
+            Canonical { value: tuple(impl_operands), num_vars: impl_trait.num_binders };
+        let substs = unify(&impl_operands, &operands)?;
+        Some((impl_id, substs))
+    });
+
+    match rhs_ty {
+        // An unknown right-hand side would match any impl, so only accept
+        // an impl if it is the only candidate.
+        Some(Ty::Unknown) => {
+            let candidate = candidates.next()?;
+            if candidates.next().is_some() {
+                return None;
+            }
+            Some(candidate)
+        }
+        _ => candidates.next(),
+    }
+}
+
+fn tuple(tys: Vec<Ty>) -> Ty {
+    Ty::apply(TypeCtor::Tuple { cardinality: tys.len() as u16 }, Substs(tys.into()))
+}
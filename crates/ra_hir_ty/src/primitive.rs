@@ -19,6 +19,17 @@ impl From<IntTy> for Uncertain<IntTy> {
     }
 }
 
+impl Uncertain<IntTy> {
+    /// Returns the known type, or `i32`, which is what an unconstrained
+    /// integer literal defaults to.
+    pub fn resolved_or_default(&self) -> IntTy {
+        match *self {
+            Uncertain::Unknown => IntTy::i32(),
+            Uncertain::Known(ty) => ty,
+        }
+    }
+}
+
 impl fmt::Display for Uncertain<IntTy> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -34,6 +45,17 @@ impl From<FloatTy> for Uncertain<FloatTy> {
     }
 }
 
+impl Uncertain<FloatTy> {
+    /// Returns the known type, or `f64`, which is what an unconstrained
+    /// float literal defaults to.
+    pub fn resolved_or_default(&self) -> FloatTy {
+        match *self {
+            Uncertain::Unknown => FloatTy::f64(),
+            Uncertain::Known(ty) => ty,
+        }
+    }
+}
+
 impl fmt::Display for Uncertain<FloatTy> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match *self {
@@ -6,13 +6,22 @@ mod patterns;
 mod traits;
 mod method_resolution;
 mod macros;
+mod display;
+mod ty;
 
 use std::fmt::Write;
 use std::sync::Arc;
 
 use hir_def::{
-    body::BodySourceMap, child_by_source::ChildBySource, db::DefDatabase, item_scope::ItemScope,
-    keys, nameres::CrateDefMap, AssocItemId, DefWithBodyId, LocalModuleId, Lookup, ModuleDefId,
+    body::BodySourceMap,
+    child_by_source::ChildBySource,
+    db::DefDatabase,
+    expr::{Expr, ExprId},
+    item_scope::ItemScope,
+    keys,
+    nameres::CrateDefMap,
+    AdtId, AssocItemId, DefWithBodyId, FunctionId, LocalModuleId, Lookup, ModuleDefId, ModuleId,
+    TraitId,
 };
 use hir_expand::InFile;
 use insta::assert_snapshot;
@@ -22,13 +31,83 @@ use ra_syntax::{
     ast::{self, AstNode},
 };
 
-use crate::{db::HirDatabase, display::HirDisplay, test_db::TestDB, InferenceResult};
+use crate::{
+    db::HirDatabase, display::HirDisplay, test_db::TestDB, Coercion, InferenceResult,
+    ReceiverAdjustment,
+};
 
 // These tests compare the inference results for all expressions in a file
 // against snapshots of the expected results using insta. Use cargo-insta to
 // update the snapshots.
 
+/// Builds a single-file fixture and returns its root module together with the
+/// items declared in it.
+fn single_file_decls(fixture: &str) -> (TestDB, ModuleId, Vec<ModuleDefId>) {
+    let (db, file_id) = TestDB::with_single_file(fixture);
+    let module = db.module_for_file(file_id);
+    let decls = db.crate_def_map(module.krate)[module.local_id].scope.declarations().collect();
+    (db, module, decls)
+}
+
+fn find_fn(db: &TestDB, decls: &[ModuleDefId], name: &str) -> FunctionId {
+    decls
+        .iter()
+        .find_map(|&decl| match decl {
+            ModuleDefId::FunctionId(it) if db.function_data(it).name.to_string() == name => {
+                Some(it)
+            }
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn find_adt(db: &TestDB, decls: &[ModuleDefId], name: &str) -> AdtId {
+    decls
+        .iter()
+        .find_map(|&decl| match decl {
+            ModuleDefId::AdtId(it) if adt_name(db, it) == name => Some(it),
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn find_trait(db: &TestDB, decls: &[ModuleDefId], name: &str) -> TraitId {
+    decls
+        .iter()
+        .find_map(|&decl| match decl {
+            ModuleDefId::TraitId(it) if db.trait_data(it).name.to_string() == name => Some(it),
+            _ => None,
+        })
+        .unwrap()
+}
+
+/// Returns the first closure expression in the body of `func`.
+fn find_closure(db: &TestDB, func: FunctionId) -> ExprId {
+    db.body(func.into())
+        .exprs
+        .iter()
+        .find_map(|(id, expr)| match expr {
+            Expr::Lambda { .. } => Some(id),
+            _ => None,
+        })
+        .unwrap()
+}
+
+fn adt_name(db: &TestDB, adt: AdtId) -> String {
+    match adt {
+        AdtId::StructId(it) => db.struct_data(it).name.to_string(),
+        AdtId::UnionId(it) => db.union_data(it).name.to_string(),
+        AdtId::EnumId(it) => db.enum_data(it).name.to_string(),
+    }
+}
+
 fn type_at_pos(db: &TestDB, pos: FilePosition) -> String {
+    let (func, expr_id) = expr_at_pos(db, pos);
+    let infer = db.infer(func.into());
+    infer[expr_id].display(db).to_string()
+}
+
+fn expr_at_pos(db: &TestDB, pos: FilePosition) -> (FunctionId, ExprId) {
     let file = db.parse(pos.file_id).ok().unwrap();
     let expr = algo::find_node_at_offset::<ast::Expr>(file.syntax(), pos.offset).unwrap();
     let fn_def = expr.syntax().ancestors().find_map(ast::FnDef::cast).unwrap();
@@ -39,9 +118,7 @@ fn type_at_pos(db: &TestDB, pos: FilePosition) -> String {
 
     let (_body, source_map) = db.body_with_source_map(func.into());
     if let Some(expr_id) = source_map.node_expr(InFile::new(pos.file_id.into(), &expr)) {
-        let infer = db.infer(func.into());
-        let ty = &infer[expr_id];
-        return ty.display(db).to_string();
+        return (func, expr_id);
     }
     panic!("Can't find expression")
 }
@@ -51,6 +128,18 @@ fn type_at(content: &str) -> String {
     type_at_pos(&db, file_pos)
 }
 
+fn coercions_at(content: &str) -> Vec<Coercion> {
+    let (db, file_pos) = TestDB::with_position(content);
+    let (func, expr_id) = expr_at_pos(&db, file_pos);
+    db.infer(func.into()).coercions(expr_id).to_vec()
+}
+
+fn receiver_adjustment_at(content: &str) -> Option<ReceiverAdjustment> {
+    let (db, file_pos) = TestDB::with_position(content);
+    let (func, expr_id) = expr_at_pos(&db, file_pos);
+    db.infer(func.into()).receiver_adjustment(expr_id)
+}
+
 fn infer(content: &str) -> String {
     infer_with_mismatches(content, false)
 }
@@ -315,3 +404,209 @@ fn no_such_field_diagnostics() {
     "###
     );
 }
+
+#[test]
+fn mismatched_arg_count_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        struct S;
+        impl S {
+            fn method(&self, x: i32) {}
+        }
+        fn f(a: i32, b: i32) {}
+        fn test() {
+            f(1);
+            f(1, 2);
+            S.method();
+            S.method(1);
+            S::method(&S, 1, 2);
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "f(1)": Expected 2 arguments, found 1
+    "S.method()": Expected 1 argument, found 0
+    "S::method(&S, 1, 2)": Expected 2 arguments, found 3
+    "###
+    );
+}
+
+#[test]
+fn assoc_fn_called_as_method_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        struct String {}
+        impl String {
+            fn new() -> String { String {} }
+            fn len(&self) -> usize { 0 }
+        }
+        fn test(s: String) {
+            String.new();
+            s.new();
+            s.len();
+            String::new();
+            String.missing();
+            String.len();
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "String.new()": `new` is an associated function, not a method; use `::new` instead of `.new`
+    "s.new()": `new` is an associated function, not a method; use `::new` instead of `.new`
+    "###
+    );
+}
+
+#[test]
+fn missing_unsafe_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        struct S;
+        impl S {
+            unsafe fn unsafe_method(&self) {}
+        }
+        unsafe fn unsafe_fn() {}
+        union U { a: u32, b: f32 }
+        extern {
+            fn extern_fn();
+        }
+        fn test(p: *const u32, mut u: U) {
+            unsafe_fn();
+            S.unsafe_method();
+            *p;
+            u.a;
+            u.b = 1.0;
+            extern_fn();
+            unsafe {
+                unsafe_fn();
+                *p;
+            }
+        }
+        unsafe fn unsafe_test(p: *const u32) {
+            unsafe_fn();
+            *p;
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "unsafe_fn()": This operation is unsafe and requires an unsafe function or block
+    "S.unsafe_method()": This operation is unsafe and requires an unsafe function or block
+    "*p": This operation is unsafe and requires an unsafe function or block
+    "u.a": This operation is unsafe and requires an unsafe function or block
+    "extern_fn()": This operation is unsafe and requires an unsafe function or block
+    "###
+    );
+}
+
+#[test]
+fn use_after_move_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r#"
+        //- /lib.rs
+        #[lang = "copy"]
+        trait Copy {}
+        struct String;
+        struct Id(u32);
+        impl Copy for Id {}
+        fn consume<T>(t: T) {}
+        fn test(cond: bool) {
+            let s = String;
+            let id = Id(1);
+            let n = 1;
+            consume(s);
+            consume(id);
+            consume(n);
+            consume(&s);
+            consume(id);
+            consume(n);
+            consume(s);
+
+            let mut t = String;
+            let u = t;
+            t = String;
+            consume(t);
+
+            let c = String;
+            if cond {
+                consume(c);
+            }
+            consume(c);
+
+            let f = || {};
+            consume(f);
+            consume(f);
+        }
+        "#,
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "s": use of moved value
+    "f": use of moved value
+    "###
+    );
+}
+
+#[test]
+fn use_after_move_no_diagnostics_for_non_moves() {
+    let diagnostics = TestDB::with_files(
+        r#"
+        //- /lib.rs
+        struct String;
+        fn consume(s: String) {}
+        fn modify(s: &mut String) {}
+        fn test() {
+            let s = String;
+            let _ = s;
+            let ref r = s;
+            consume(s);
+
+            let mut t = String;
+            let m = &mut t;
+            modify(m);
+            modify(m);
+        }
+        "#,
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @"");
+}
+
+#[test]
+fn type_annotations_needed_diagnostics() {
+    let diagnostics = TestDB::with_files(
+        r"
+        //- /lib.rs
+        struct Vec<T> {}
+        impl<T> Vec<T> {
+            fn new() -> Self { Vec {} }
+            fn push(&mut self, t: T) {}
+        }
+        fn test() {
+            let v = Vec::new();
+            let mut w = Vec::new();
+            w.push(1u32);
+            let x: Vec<_> = Vec::new();
+            w = x;
+            let n = 1;
+            let p = (Vec::new(), missing());
+        }
+        ",
+    )
+    .diagnostics();
+
+    assert_snapshot!(diagnostics, @r###"
+    "Vec::new()": type annotations needed
+    "###
+    );
+}
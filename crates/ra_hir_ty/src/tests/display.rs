@@ -0,0 +1,209 @@
+use hir_def::{type_ref::Mutability, AdtId, StructId};
+use ra_db::{
+    fixture::WithFixture,
+    salsa::{InternId, InternKey},
+};
+
+use super::{expr_at_pos, find_closure, find_fn, single_file_decls};
+use crate::{
+    db::HirDatabase,
+    display::{DisplayOptions, HirDisplay},
+    primitive::{FloatTy, IntTy, Uncertain},
+    test_db::TestDB,
+    GenericPredicate, Substs, Ty, TypeCtor,
+};
+
+#[test]
+fn ty_display_structural() {
+    let adt = AdtId::StructId(StructId::from_intern_id(InternId::from(3u32)));
+    let ty = Ty::apply(TypeCtor::Adt(adt), Substs::single(Ty::Bound(0)));
+    assert_eq!(ty.display_structural(), "Adt(#3)<Bound(0)>");
+
+    let ty = Ty::apply(
+        TypeCtor::Tuple { cardinality: 2 },
+        Substs(vec![Ty::apply_one(TypeCtor::Ref(Mutability::Shared), ty), Ty::Unknown].into()),
+    );
+    assert_eq!(ty.display_structural(), "Tuple<Ref(Shared)<Adt(#3)<Bound(0)>>, {unknown}>");
+}
+
+#[test]
+fn display_fn_where_clauses() {
+    let (db, _, decls) = single_file_decls(
+        r"
+        trait Clone {}
+        fn f<T: Clone>() {}
+        fn g() {}
+        fn h<T: ?Sized>() {}
+        fn i<T: Clone + ?Sized, U>() where U: ?Sized {}
+        ",
+    );
+    let display = |name: &str| {
+        let func = find_fn(&db, &decls, name);
+        let ty = db.value_ty(func.into()).subst(&Substs::type_params(&db, func));
+        let options = DisplayOptions { show_where_clauses: true, ..Default::default() };
+        (ty.display(&db).to_string(), ty.display_with_options(&db, options).to_string())
+    };
+
+    assert_eq!(
+        display("f"),
+        ("fn f<T>() -> ()".to_string(), "fn f<T>() -> () where T: Clone".to_string())
+    );
+    assert_eq!(display("g"), ("fn g() -> ()".to_string(), "fn g() -> ()".to_string()));
+    assert_eq!(display("h").1, "fn h<T>() -> () where T: ?Sized");
+    assert_eq!(display("i").1, "fn i<T, U>() -> () where T: Clone, T: ?Sized, U: ?Sized");
+}
+
+#[test]
+fn display_unnameable_types() {
+    let (db, _, decls) = single_file_decls(
+        r"
+        fn test() {
+            let f = |x: u32| x;
+        }
+        ",
+    );
+    let func = find_fn(&db, &decls, "test");
+    let lambda = find_closure(&db, func);
+    let infer = db.infer(func.into());
+    assert_eq!(infer[lambda].display(&db).to_string(), "|u32| -> u32");
+
+    let closure = TypeCtor::Closure { def: func.into(), expr: lambda };
+    let without_sig = Ty::apply(closure, Substs::single(Ty::Unknown));
+    assert_eq!(without_sig.display(&db).to_string(), "{closure}");
+    let without_params = Ty::apply(closure, Substs::empty());
+    assert_eq!(without_params.display(&db).to_string(), "{closure}");
+
+    let opaque = Ty::Opaque(Vec::new().into());
+    assert_eq!(opaque.display(&db).to_string(), "impl {unknown}");
+    let opaque = Ty::Opaque(vec![GenericPredicate::Error].into());
+    assert_eq!(opaque.display(&db).to_string(), "impl {error}");
+}
+
+#[test]
+fn display_literal_defaults() {
+    let (db, _) = TestDB::with_single_file("");
+    let int = Ty::simple(TypeCtor::Int(Uncertain::Unknown));
+    let float = Ty::simple(TypeCtor::Float(Uncertain::Unknown));
+    let tuple = Ty::apply(TypeCtor::Tuple { cardinality: 2 }, Substs(vec![int, float].into()));
+    let slice = Ty::apply_one(TypeCtor::Slice, tuple.clone());
+    let options = DisplayOptions { default_literal_types: true, ..Default::default() };
+
+    assert_eq!(tuple.display(&db).to_string(), "({integer}, {float})");
+    assert_eq!(tuple.display_with_options(&db, options).to_string(), "(i32, f64)");
+    assert_eq!(slice.display_with_options(&db, options).to_string(), "[(i32, f64)]");
+}
+
+#[test]
+fn display_qualified_paths() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs crate:main deps:core
+use core::option::Option;
+struct Foo;
+fn test(x: &[Option<Foo>]) {
+    x<|>;
+}
+
+//- /core.rs crate:core
+pub mod option {
+    pub enum Option<T> { Some(T), None }
+}
+"#,
+    );
+    let (func, expr_id) = expr_at_pos(&db, pos);
+    let ty = db.infer(func.into())[expr_id].clone();
+
+    let options = DisplayOptions {
+        qualify_paths_from: Some(db.module_for_file(pos.file_id).krate),
+        ..Default::default()
+    };
+
+    assert_eq!(ty.display(&db).to_string(), "&[Option<Foo>]");
+    assert_eq!(
+        ty.display_with_options(&db, options).to_string(),
+        "&[core::option::Option<crate::Foo>]"
+    );
+}
+
+#[test]
+fn display_closure_captures() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs
+fn test() {
+    let x = 1;
+    let y = 2;
+    let z = 3;
+    let f = |a: i32| { x; let z = a; y; z };
+    f<|>;
+}
+"#,
+    );
+    let (func, expr_id) = expr_at_pos(&db, pos);
+    let ty = db.infer(func.into())[expr_id].clone();
+
+    let options = DisplayOptions { show_closure_captures: true, ..Default::default() };
+
+    assert_eq!(ty.display(&db).to_string(), "|i32| -> i32");
+    assert_eq!(
+        ty.display_with_options(&db, options).to_string(),
+        "|i32| -> i32 [captures by ref: x, y]"
+    );
+}
+
+#[test]
+fn display_move_closure_captures() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs
+fn test() {
+    let x = 1;
+    let f = move || x;
+    f<|>;
+}
+"#,
+    );
+    let (func, expr_id) = expr_at_pos(&db, pos);
+    let ty = db.infer(func.into())[expr_id].clone();
+    let options = DisplayOptions { show_closure_captures: true, ..Default::default() };
+
+    assert_eq!(
+        ty.display_with_options(&db, options).to_string(),
+        "|| -> i32 [captures by move: x]"
+    );
+}
+
+#[test]
+fn display_tuples() {
+    let db = TestDB::default();
+    let int = Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::i32())));
+    let one = Ty::apply(TypeCtor::Tuple { cardinality: 1 }, Substs::single(int.clone()));
+    let two = Ty::apply(TypeCtor::Tuple { cardinality: 2 }, Substs(vec![int, Ty::unit()].into()));
+    let nested = Ty::apply(TypeCtor::Tuple { cardinality: 1 }, Substs::single(one.clone()));
+
+    assert_eq!(Ty::unit().display(&db).to_string(), "()");
+    assert_eq!(one.display(&db).to_string(), "(i32,)");
+    assert_eq!(two.display(&db).to_string(), "(i32, ())");
+    assert_eq!(nested.display(&db).to_string(), "((i32,),)");
+
+    assert_eq!(Ty::unit().display_truncated(&db, Some(0)).to_string(), "()");
+    assert_eq!(one.display_truncated(&db, Some(1)).to_string(), "(…,)");
+    assert_eq!(two.display_truncated(&db, Some(1)).to_string(), "(…, ())");
+}
+
+#[test]
+fn uncertain_primitive_defaults() {
+    assert_eq!(Uncertain::Known(IntTy::u8()).resolved_or_default(), IntTy::u8());
+    assert_eq!(Uncertain::<IntTy>::Unknown.resolved_or_default(), IntTy::i32());
+    assert_eq!(Uncertain::Known(FloatTy::f32()).resolved_or_default(), FloatTy::f32());
+    assert_eq!(Uncertain::<FloatTy>::Unknown.resolved_or_default(), FloatTy::f64());
+
+    let (db, _) = TestDB::with_single_file("");
+    let literal = Ty::simple(TypeCtor::Int(Uncertain::Unknown));
+    assert_eq!(literal.display(&db).to_string(), "{integer}");
+    assert_eq!(literal.display_truncated(&db, None).to_string(), "{integer}");
+    let options = DisplayOptions { default_literal_types: true, ..Default::default() };
+    assert_eq!(literal.display_with_options(&db, options).to_string(), "i32");
+    let u8_literal = Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::u8())));
+    assert_eq!(u8_literal.display_with_options(&db, options).to_string(), "u8");
+}
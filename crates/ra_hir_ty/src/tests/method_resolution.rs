@@ -1,7 +1,17 @@
-use super::{infer, type_at, type_at_pos};
-use crate::test_db::TestDB;
+use std::sync::Arc;
+
+use super::{
+    expr_at_pos, find_adt, find_trait, infer, receiver_adjustment_at, single_file_decls, type_at,
+    type_at_pos,
+};
+use crate::{
+    db::HirDatabase, display::HirDisplay, test_db::TestDB, traits::InEnvironment, Canonical,
+    DerefKind, ReceiverAdjustment, TraitEnvironment, Ty, TypeCtor,
+};
+use hir_def::{db::DefDatabase, type_ref::Mutability, AssocContainerId, Lookup};
 use insta::assert_snapshot;
 use ra_db::fixture::WithFixture;
+use rustc_hash::FxHashSet;
 
 #[test]
 fn infer_slice_method() {
@@ -913,6 +923,41 @@ fn test() { (&S).foo()<|>; }
     assert_eq!(t, "{unknown}");
 }
 
+#[test]
+fn method_resolution_blanket_impl() {
+    let t = type_at(
+        r#"
+//- /main.rs
+trait Display {}
+trait Ext { fn ext(&self) -> u128; }
+impl<T: Display> Ext for T { fn ext(&self) -> u128 { 0 } }
+struct S;
+impl Display for S {}
+fn test() { S.ext()<|>; }
+"#,
+    );
+    assert_eq!(t, "u128");
+}
+
+#[test]
+fn method_resolution_blanket_impl_from_dependency() {
+    let t = type_at(
+        r#"
+//- /main.rs crate:main deps:ext
+use ext::{Display, Ext};
+struct S;
+impl Display for S {}
+fn test() { S.ext()<|>; }
+
+//- /ext.rs crate:ext
+pub trait Display {}
+pub trait Ext { fn ext(&self) -> u128; }
+impl<T: Display> Ext for T { fn ext(&self) -> u128 { 0 } }
+"#,
+    );
+    assert_eq!(t, "u128");
+}
+
 #[test]
 fn method_resolution_where_clause_1() {
     let t = type_at(
@@ -1058,3 +1103,134 @@ fn test() { (S {}).method()<|>; }
     );
     assert_eq!(t, "()");
 }
+
+#[test]
+fn method_call_receiver_adjustment() {
+    let defs = r#"
+struct Vec<T> {}
+impl<T> Vec<T> {
+    fn len(&self) -> usize { 0 }
+    fn push(&mut self, x: T) {}
+    fn into_boxed(self) {}
+}
+"#;
+    let adjustment = |call: &str| {
+        receiver_adjustment_at(&format!("{}fn test(mut v: Vec<u32>) {{ {} }}", defs, call))
+    };
+    let adjusted =
+        |autoderefs, autoref| Some(ReceiverAdjustment { autoderefs, unsize: false, autoref });
+
+    assert_eq!(adjustment("(&v).len<|>();"), adjusted(1, Some(Mutability::Shared)));
+    assert_eq!(adjustment("v.len<|>();"), adjusted(0, Some(Mutability::Shared)));
+    assert_eq!(adjustment("let r = &&v; r.len<|>();"), adjusted(2, Some(Mutability::Shared)));
+    assert_eq!(adjustment("v.push<|>(1);"), adjusted(0, Some(Mutability::Mut)));
+    assert_eq!(adjustment("v.into_boxed<|>();"), adjusted(0, None));
+    assert_eq!(adjustment("v.unknown<|>();"), None);
+}
+
+#[test]
+fn method_call_receiver_adjustment_unsize() {
+    let adjustment = receiver_adjustment_at(
+        r#"
+#[lang = "slice"]
+impl<T> [T] {
+    fn len(&self) -> usize { 0 }
+}
+fn test() { [1u32, 2, 3].len<|>(); }
+"#,
+    );
+    assert_eq!(
+        adjustment,
+        Some(ReceiverAdjustment { autoderefs: 0, unsize: true, autoref: Some(Mutability::Shared) })
+    );
+}
+
+#[test]
+fn inherent_method_shadows_trait_method() {
+    let (db, module, decls) = single_file_decls(
+        r#"
+        trait Len {
+            fn len(&self) -> usize;
+            fn is_empty(&self) -> bool;
+        }
+
+        struct S {}
+
+        impl S {
+            fn len(&self) -> usize { 0 }
+        }
+
+        impl Len for S {
+            fn len(&self) -> usize { 1 }
+            fn is_empty(&self) -> bool { true }
+        }
+        "#,
+    );
+    let s = find_adt(&db, &decls, "S");
+    let len_trait = find_trait(&db, &decls, "Len");
+    let traits_in_scope = std::iter::once(len_trait).collect::<FxHashSet<_>>();
+    let ty = Canonical { num_vars: 0, value: Ty::simple(TypeCtor::Adt(s)) };
+    let env = Arc::new(TraitEnvironment { predicates: Vec::new() });
+    let trait_data = db.trait_data(len_trait);
+    let resolve = |name: &str| {
+        let (name, _) = trait_data.items.iter().find(|(it, _)| it.to_string() == name).unwrap();
+        let res = crate::method_resolution::resolve_method_with_priority(
+            &ty,
+            &db,
+            env.clone(),
+            module.krate,
+            &traits_in_scope,
+            name,
+        )
+        .unwrap();
+        let container = match res.func.lookup(&db).container {
+            AssocContainerId::ImplId(it) if db.impl_data(it).target_trait.is_none() => "inherent",
+            _ => "trait",
+        };
+        (container, res.shadows_trait_method)
+    };
+
+    assert_eq!(resolve("len"), ("inherent", true));
+    assert_eq!(resolve("is_empty"), ("trait", false));
+}
+
+#[test]
+fn autoderef_steps() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs
+#[lang = "deref"]
+pub trait Deref {
+    type Target;
+    fn deref(&self) -> &Self::Target;
+}
+struct B<T>(T);
+impl<T> Deref for B<T> {
+    type Target = T;
+    fn deref(&self) -> &T { &self.0 }
+}
+fn test(b: &mut B<*const u32>) {
+    b<|>;
+}
+"#,
+    );
+    let (func, expr_id) = expr_at_pos(&db, pos);
+    let ty = db.infer(func.into())[expr_id].clone();
+    let krate = db.module_for_file(pos.file_id).krate;
+    let env = Arc::new(TraitEnvironment { predicates: Vec::new() });
+    let steps = crate::autoderef_with_steps(
+        &db,
+        Some(krate),
+        InEnvironment::new(env, Canonical { num_vars: 0, value: ty }),
+    )
+    .map(|(ty, kind)| (ty.value.display(&db).to_string(), kind))
+    .collect::<Vec<_>>();
+    assert_eq!(
+        steps,
+        vec![
+            ("B<*const u32>".to_string(), DerefKind::Builtin(Mutability::Mut)),
+            ("*const u32".to_string(), DerefKind::Overloaded),
+            ("u32".to_string(), DerefKind::Builtin(Mutability::Shared)),
+        ]
+    );
+}
@@ -1,5 +1,8 @@
-use super::{infer, type_at, type_at_pos};
-use crate::test_db::TestDB;
+use super::{
+    find_closure, find_fn, infer, infer_with_mismatches, single_file_decls, type_at, type_at_pos,
+};
+use crate::{db::HirDatabase, display::HirDisplay, test_db::TestDB, Ty};
+use hir_def::db::DefDatabase;
 use insta::assert_snapshot;
 use ra_db::fixture::WithFixture;
 
@@ -28,7 +31,7 @@ mod boxed {
 
 "#,
     );
-    assert_eq!("(Box<i32>, Box<Box<i32>>, Box<&i32>, Box<[i32; _]>)", type_at_pos(&db, pos));
+    assert_eq!("(Box<i32>, Box<Box<i32>>, Box<&i32>, Box<[i32; 1]>)", type_at_pos(&db, pos));
 }
 
 #[test]
@@ -1061,55 +1064,96 @@ fn test(x: &str, y: isize) {
     [9; 10) 'x': &str
     [18; 19) 'y': isize
     [28; 293) '{     ... []; }': ()
-    [38; 39) 'a': [&str; _]
-    [42; 45) '[x]': [&str; _]
+    [38; 39) 'a': [&str; 1]
+    [42; 45) '[x]': [&str; 1]
     [43; 44) 'x': &str
-    [55; 56) 'b': [[&str; _]; _]
-    [59; 65) '[a, a]': [[&str; _]; _]
-    [60; 61) 'a': [&str; _]
-    [63; 64) 'a': [&str; _]
-    [75; 76) 'c': [[[&str; _]; _]; _]
-    [79; 85) '[b, b]': [[[&str; _]; _]; _]
-    [80; 81) 'b': [[&str; _]; _]
-    [83; 84) 'b': [[&str; _]; _]
-    [96; 97) 'd': [isize; _]
-    [100; 112) '[y, 1, 2, 3]': [isize; _]
+    [55; 56) 'b': [[&str; 1]; 2]
+    [59; 65) '[a, a]': [[&str; 1]; 2]
+    [60; 61) 'a': [&str; 1]
+    [63; 64) 'a': [&str; 1]
+    [75; 76) 'c': [[[&str; 1]; 2]; 2]
+    [79; 85) '[b, b]': [[[&str; 1]; 2]; 2]
+    [80; 81) 'b': [[&str; 1]; 2]
+    [83; 84) 'b': [[&str; 1]; 2]
+    [96; 97) 'd': [isize; 4]
+    [100; 112) '[y, 1, 2, 3]': [isize; 4]
     [101; 102) 'y': isize
     [104; 105) '1': isize
     [107; 108) '2': isize
     [110; 111) '3': isize
-    [122; 123) 'd': [isize; _]
-    [126; 138) '[1, y, 2, 3]': [isize; _]
+    [122; 123) 'd': [isize; 4]
+    [126; 138) '[1, y, 2, 3]': [isize; 4]
     [127; 128) '1': isize
     [130; 131) 'y': isize
     [133; 134) '2': isize
     [136; 137) '3': isize
-    [148; 149) 'e': [isize; _]
-    [152; 155) '[y]': [isize; _]
+    [148; 149) 'e': [isize; 1]
+    [152; 155) '[y]': [isize; 1]
     [153; 154) 'y': isize
-    [165; 166) 'f': [[isize; _]; _]
-    [169; 175) '[d, d]': [[isize; _]; _]
-    [170; 171) 'd': [isize; _]
-    [173; 174) 'd': [isize; _]
-    [185; 186) 'g': [[isize; _]; _]
-    [189; 195) '[e, e]': [[isize; _]; _]
-    [190; 191) 'e': [isize; _]
-    [193; 194) 'e': [isize; _]
-    [206; 207) 'h': [i32; _]
-    [210; 216) '[1, 2]': [i32; _]
+    [165; 166) 'f': [[isize; 4]; 2]
+    [169; 175) '[d, d]': [[isize; 4]; 2]
+    [170; 171) 'd': [isize; 4]
+    [173; 174) 'd': [isize; 4]
+    [185; 186) 'g': [[isize; 1]; 2]
+    [189; 195) '[e, e]': [[isize; 1]; 2]
+    [190; 191) 'e': [isize; 1]
+    [193; 194) 'e': [isize; 1]
+    [206; 207) 'h': [i32; 2]
+    [210; 216) '[1, 2]': [i32; 2]
     [211; 212) '1': i32
     [214; 215) '2': i32
-    [226; 227) 'i': [&str; _]
-    [230; 240) '["a", "b"]': [&str; _]
+    [226; 227) 'i': [&str; 2]
+    [230; 240) '["a", "b"]': [&str; 2]
     [231; 234) '"a"': &str
     [236; 239) '"b"': &str
-    [251; 252) 'b': [[&str; _]; _]
-    [255; 265) '[a, ["b"]]': [[&str; _]; _]
-    [256; 257) 'a': [&str; _]
-    [259; 264) '["b"]': [&str; _]
+    [251; 252) 'b': [[&str; 1]; 2]
+    [255; 265) '[a, ["b"]]': [[&str; 1]; 2]
+    [256; 257) 'a': [&str; 1]
+    [259; 264) '["b"]': [&str; 1]
     [260; 263) '"b"': &str
-    [275; 276) 'x': [u8; _]
-    [288; 290) '[]': [u8; _]
+    [275; 276) 'x': [u8; 0]
+    [288; 290) '[]': [u8; 0]
+    "###
+    );
+}
+
+#[test]
+fn infer_array_len() {
+    assert_snapshot!(
+        infer_with_mismatches(r#"
+fn f(x: [u8; 3]) {}
+fn test(a: [u8; 3], b: [u8; 4]) {
+    let c: [u8; LEN] = [1, 2, 3];
+    let d: [u8; 3] = a;
+    let e: [u8; 3] = b;
+    f(c);
+    f([0; 3]);
+}
+"#, true),
+        @r###"
+    [6; 7) 'x': [u8; 3]
+    [18; 20) '{}': ()
+    [29; 30) 'a': [u8; 3]
+    [41; 42) 'b': [u8; 4]
+    [53; 163) '{     ...3]); }': ()
+    [63; 64) 'c': [u8; _]
+    [78; 87) '[1, 2, 3]': [u8; 3]
+    [79; 80) '1': u8
+    [82; 83) '2': u8
+    [85; 86) '3': u8
+    [97; 98) 'd': [u8; 3]
+    [109; 110) 'a': [u8; 3]
+    [121; 122) 'e': [u8; 3]
+    [133; 134) 'b': [u8; 4]
+    [141; 142) 'f': fn f([u8; 3]) -> ()
+    [141; 145) 'f(c)': ()
+    [143; 144) 'c': [u8; _]
+    [151; 152) 'f': fn f([u8; 3]) -> ()
+    [151; 160) 'f([0; 3])': ()
+    [153; 159) '[0; 3]': [u8; 3]
+    [154; 155) '0': u8
+    [157; 158) '3': usize
+    [133; 134): expected [u8; 3], got [u8; 4]
     "###
     );
 }
@@ -1686,3 +1730,42 @@ fn foo() -> u32 {
     "###
     );
 }
+
+#[test]
+fn ty_as_closure() {
+    let (db, _, decls) = single_file_decls(
+        r"
+        fn test() {
+            let f = |x: u32| x;
+        }
+        ",
+    );
+    let func = find_fn(&db, &decls, "test");
+    let lambda = find_closure(&db, func);
+    let infer = db.infer(func.into());
+
+    assert_eq!(infer[lambda].as_closure(), Some((func.into(), lambda)));
+    assert_eq!(Ty::unit().as_closure(), None);
+}
+
+#[test]
+fn infer_closure_sig() {
+    let (db, _, decls) = single_file_decls(
+        r"
+        fn test() {
+            let f = |x: i32| x + 1;
+        }
+        ",
+    );
+    let func = find_fn(&db, &decls, "test");
+    let lambda = find_closure(&db, func);
+    let infer = db.infer(func.into());
+
+    let sig = infer.closure_sig(lambda).unwrap();
+    assert_eq!(
+        sig.params().iter().map(|it| it.display(&db).to_string()).collect::<Vec<_>>(),
+        ["i32"]
+    );
+    assert_eq!(sig.ret().display(&db).to_string(), "i32");
+    assert_eq!(infer.closure_sig(db.body(func.into()).body_expr), None);
+}
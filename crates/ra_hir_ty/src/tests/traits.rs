@@ -1,9 +1,28 @@
+use hir_def::{
+    db::DefDatabase,
+    expr::{ArithOp, BinaryOp, UnaryOp},
+    type_ref::Mutability,
+    TraitId,
+};
+use hir_expand::name::name;
 use insta::assert_snapshot;
 
-use ra_db::fixture::WithFixture;
-
-use super::{infer, infer_with_mismatches, type_at, type_at_pos};
-use crate::test_db::TestDB;
+use ra_db::{
+    fixture::WithFixture,
+    salsa::{InternId, InternKey},
+};
+
+use super::{
+    find_adt, find_trait, infer, infer_with_mismatches, single_file_decls, type_at, type_at_pos,
+};
+use crate::{
+    display::HirDisplay,
+    op,
+    primitive::{IntTy, Uncertain},
+    test_db::TestDB,
+    GenericPredicate, ProjectionPredicate, ProjectionTy, Substs, TraitEnvironment, TraitRef, Ty,
+    TypeCtor, TypeWalk,
+};
 
 #[test]
 fn infer_await() {
@@ -526,7 +545,7 @@ fn indexing_arrays() {
         @r###"
     [10; 26) '{ &mut...[2]; }': ()
     [12; 23) '&mut [9][2]': &mut {unknown}
-    [17; 20) '[9]': [i32; _]
+    [17; 20) '[9]': [i32; 1]
     [17; 23) '[9][2]': {unknown}
     [18; 19) '9': i32
     [21; 22) '2': i32
@@ -534,6 +553,23 @@ fn indexing_arrays() {
     )
 }
 
+#[test]
+fn array_impl_applies_to_unknown_length() {
+    let t = type_at(
+        r#"
+//- /main.rs
+trait Trait { type Assoc; }
+impl<T> Trait for [T; 3] { type Assoc = T; }
+fn foo<T: Trait>(t: T) -> T::Assoc {}
+
+fn test(a: [u8; LEN]) {
+    (foo(a), foo([1u16, 2, 3]))<|>;
+}
+"#,
+    );
+    assert_eq!(t, "(u8, u16)");
+}
+
 #[test]
 fn infer_ops_index() {
     let (db, pos) = TestDB::with_position(
@@ -1137,6 +1173,28 @@ fn test(x: impl Trait<u64>, y: &impl Trait<u64>) {
     );
 }
 
+#[test]
+fn dyn_trait_auto_traits_display_order() {
+    let fixture = |ty: &str| {
+        format!(
+            r#"
+//- /main.rs
+trait Error {{}}
+auto trait Send {{}}
+auto trait Sync {{}}
+fn test(x: &({})) {{
+    x<|>;
+}}
+"#,
+            ty
+        )
+    };
+    let t = type_at(&fixture("dyn Error + Sync + Send"));
+    assert_eq!(t, "&dyn Error + Send + Sync");
+    assert_eq!(type_at(&fixture("dyn Error + Sync + Send")), t);
+    assert_eq!(type_at(&fixture("dyn Sync + Send + Error")), t);
+}
+
 #[test]
 fn dyn_trait() {
     assert_snapshot!(
@@ -1910,3 +1968,172 @@ fn test() -> impl Trait<i32> {
     "###
     );
 }
+
+#[test]
+fn generic_predicate_is_fully_known() {
+    let trait_ = TraitId::from_intern_id(InternId::from(0u32));
+    let trait_ref =
+        |arg: Ty| TraitRef { trait_, substs: Substs(vec![Ty::simple(TypeCtor::Bool), arg].into()) };
+
+    let known = trait_ref(Ty::char());
+    assert!(!known.contains_unknown());
+    assert!(GenericPredicate::Implemented(known).is_fully_known());
+
+    let unknown_arg = trait_ref(Ty::Unknown);
+    assert!(unknown_arg.contains_unknown());
+    let pred = GenericPredicate::Implemented(unknown_arg);
+    assert!(!pred.is_error());
+    assert!(!pred.is_fully_known());
+
+    assert!(GenericPredicate::Error.is_error());
+    assert!(!GenericPredicate::Error.is_fully_known());
+}
+
+#[test]
+fn ty_dyn_trait() {
+    let (db, _, decls) = single_file_decls(
+        r"
+        trait Iterator {
+            type Item;
+        }
+        ",
+    );
+    let iterator = find_trait(&db, &decls, "Iterator");
+    let item = db.trait_data(iterator).associated_type_by_name(&name![Item]).unwrap();
+    let u32_ty = Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::u32())));
+
+    let trait_ref = TraitRef { trait_: iterator, substs: Substs::single(Ty::Unknown) };
+    let binding = ProjectionPredicate {
+        projection_ty: ProjectionTy {
+            associated_ty: item,
+            parameters: Substs::single(Ty::Unknown),
+        },
+        ty: u32_ty,
+    };
+    let ty = Ty::dyn_trait(trait_ref, vec![binding]);
+
+    assert_eq!(ty.display(&db).to_string(), "dyn Iterator<Item = u32>");
+    assert_eq!(
+        ty.principal_trait_ref(),
+        Some(&TraitRef { trait_: iterator, substs: Substs::single(Ty::Bound(0)) })
+    );
+    assert_eq!(ty.inherent_trait(), Some(iterator));
+    assert_eq!(Ty::unit().principal_trait_ref(), None);
+}
+
+#[test]
+fn ty_could_unify_with() {
+    let (db, _, decls) = single_file_decls(
+        r"
+        struct Vec<T> {}
+        enum Option<T> {}
+        ",
+    );
+    let adt = |name: &str, param: Ty| {
+        Ty::apply(TypeCtor::Adt(find_adt(&db, &decls, name)), Substs::single(param))
+    };
+    let i32 = Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::i32())));
+
+    assert!(adt("Vec", Ty::Unknown).could_unify_with(&adt("Vec", i32.clone())));
+    assert!(!adt("Vec", i32.clone()).could_unify_with(&adt("Option", i32.clone())));
+    assert!(!adt("Vec", i32.clone()).could_unify_with(&adt("Vec", Ty::simple(TypeCtor::Bool))));
+}
+
+#[test]
+fn resolve_overloaded_binary_op() {
+    let (db, module, decls) = single_file_decls(
+        r#"
+        #[lang = "mul"]
+        trait Mul<Rhs> {
+            type Output;
+            fn mul(self, rhs: Rhs) -> Self::Output;
+        }
+
+        struct Matrix {}
+        struct Vector {}
+
+        impl Mul<Matrix> for Matrix {
+            type Output = Matrix;
+            fn mul(self, rhs: Matrix) -> Matrix { rhs }
+        }
+
+        impl Mul<Vector> for Matrix {
+            type Output = Vector;
+            fn mul(self, rhs: Vector) -> Vector { rhs }
+        }
+        "#,
+    );
+    let adt = |name: &str| Ty::simple(TypeCtor::Adt(find_adt(&db, &decls, name)));
+    let env = TraitEnvironment { predicates: Vec::new() };
+    let mul = BinaryOp::ArithOp(ArithOp::Mul);
+    let resolve = |lhs: &str, rhs: &str| {
+        op::resolve_binary_op(&db, module.krate, &env, mul, &adt(lhs), &adt(rhs)).map(
+            |(trait_, func, output)| {
+                format!(
+                    "{}::{} -> {}",
+                    db.trait_data(trait_).name,
+                    db.function_data(func).name,
+                    output.display(&db)
+                )
+            },
+        )
+    };
+
+    assert_eq!(resolve("Matrix", "Vector"), Some("Mul::mul -> Vector".to_string()));
+    assert_eq!(resolve("Matrix", "Matrix"), Some("Mul::mul -> Matrix".to_string()));
+    assert_eq!(resolve("Vector", "Matrix"), None);
+    assert!(
+        op::resolve_binary_op(&db, module.krate, &env, mul, &Ty::Unknown, &adt("Matrix")).is_none()
+    );
+}
+
+#[test]
+fn resolve_overloaded_unary_op() {
+    let (db, module, decls) = single_file_decls(
+        r#"
+        #[lang = "neg"]
+        trait Neg {
+            type Output;
+            fn neg(self) -> Self::Output;
+        }
+        #[lang = "deref"]
+        trait Deref {
+            type Target;
+            fn deref(&self) -> &Self::Target;
+        }
+
+        struct Money {}
+        struct Debt {}
+
+        impl Neg for Money {
+            type Output = Debt;
+            fn neg(self) -> Debt { Debt {} }
+        }
+
+        impl Deref for Debt {
+            type Target = Money;
+            fn deref(&self) -> &Money { loop {} }
+        }
+        "#,
+    );
+    let adt = |name: &str| Ty::simple(TypeCtor::Adt(find_adt(&db, &decls, name)));
+    let env = TraitEnvironment { predicates: Vec::new() };
+    let resolve = |op: UnaryOp, operand: &Ty| {
+        op::resolve_unary_op(&db, module.krate, &env, op, operand).map(|(trait_, func, output)| {
+            format!(
+                "{}::{} -> {}",
+                db.trait_data(trait_).name,
+                db.function_data(func).name,
+                output.display(&db)
+            )
+        })
+    };
+
+    assert_eq!(resolve(UnaryOp::Neg, &adt("Money")), Some("Neg::neg -> Debt".to_string()));
+    assert_eq!(resolve(UnaryOp::Neg, &adt("Debt")), None);
+    assert_eq!(resolve(UnaryOp::Not, &adt("Money")), None);
+    assert_eq!(resolve(UnaryOp::Neg, &Ty::Unknown), None);
+    assert_eq!(resolve(UnaryOp::Deref, &adt("Debt")), Some("Deref::deref -> Money".to_string()));
+    let reference = Ty::apply_one(TypeCtor::Ref(Mutability::Shared), adt("Debt"));
+    assert_eq!(resolve(UnaryOp::Deref, &reference), None);
+}
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use ena::unify::UnifyKey;
+use hir_def::{adt::ReprData, builtin_type::BuiltinInt, TraitId};
+use ra_db::{
+    fixture::WithFixture,
+    salsa::{InternId, InternKey},
+};
+
+use super::{expr_at_pos, find_adt, single_file_decls};
+use crate::{
+    db::HirDatabase,
+    display::HirDisplay,
+    primitive::{IntTy, Uncertain},
+    test_db::TestDB,
+    GenericPredicate, InferTy, Substs, TraitRef, Ty, TypeCtor, TypeWalk, WalkControl,
+};
+
+#[test]
+fn ty_alpha_eq() {
+    fn tuple(tys: Vec<Ty>) -> Ty {
+        Ty::apply(TypeCtor::Tuple { cardinality: tys.len() as u16 }, Substs(tys.into()))
+    }
+
+    assert!(Ty::Bound(0).alpha_eq(&Ty::Bound(1)));
+    assert!(
+        tuple(vec![Ty::Bound(0), Ty::Bound(0)]).alpha_eq(&tuple(vec![Ty::Bound(1), Ty::Bound(1)]))
+    );
+    assert!(
+        tuple(vec![Ty::Bound(0), Ty::Bound(1)]).alpha_eq(&tuple(vec![Ty::Bound(1), Ty::Bound(0)]))
+    );
+    assert!(
+        !tuple(vec![Ty::Bound(0), Ty::Bound(1)]).alpha_eq(&tuple(vec![Ty::Bound(0), Ty::Bound(0)]))
+    );
+    assert!(
+        !tuple(vec![Ty::Bound(0), Ty::Bound(0)]).alpha_eq(&tuple(vec![Ty::Bound(0), Ty::Bound(1)]))
+    );
+    assert!(!tuple(vec![Ty::Bound(0), Ty::Unknown]).alpha_eq(&tuple(vec![Ty::Bound(0)])));
+}
+
+#[test]
+fn ty_is_resolved() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs
+struct Vec<T> {}
+fn test(v: Vec<i32>) {
+    v<|>;
+}
+"#,
+    );
+    let (func, expr_id) = expr_at_pos(&db, pos);
+    let vec_i32 = db.infer(func.into())[expr_id].clone();
+    assert_eq!(vec_i32.display(&db).to_string(), "Vec<i32>");
+    assert!(vec_i32.is_resolved());
+
+    let vec_infer = match &vec_i32 {
+        Ty::Apply(a_ty) => Ty::apply(
+            a_ty.ctor,
+            Substs::single(Ty::Infer(InferTy::TypeVar(UnifyKey::from_index(0)))),
+        ),
+        _ => panic!("expected an ADT"),
+    };
+    assert!(!vec_infer.is_resolved());
+    assert!(!Ty::Unknown.is_resolved());
+}
+
+#[test]
+fn ty_as_array_and_slice() {
+    let int = Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::i32())));
+    let array = Ty::array(int.clone(), Some(3));
+    let unknown_len = Ty::array(int.clone(), None);
+    let slice = Ty::apply_one(TypeCtor::Slice, int.clone());
+
+    assert_eq!(array.as_array(), Some((&int, Some(3))));
+    assert_eq!(unknown_len.as_array(), Some((&int, None)));
+    assert_ne!(array, unknown_len);
+    assert_eq!(array.as_slice(), None);
+    assert_eq!(slice.as_slice(), Some(&int));
+    assert_eq!(slice.as_array(), None);
+    assert_eq!(int.as_array(), None);
+}
+
+#[test]
+fn missing_generic_args() {
+    let (db, pos) = TestDB::with_position(
+        r#"
+//- /main.rs
+struct HashMap<K, V> {}
+struct Vec<T, A = ()> {}
+fn test(m: HashMap<u32>, full: HashMap<u32, bool>, v: Vec<u8>) {
+    (m, full, v)<|>;
+}
+"#,
+    );
+    let (func, expr_id) = expr_at_pos(&db, pos);
+    let infer = db.infer(func.into());
+    let tys = infer[expr_id].as_tuple().unwrap();
+    let (m, full, v) = (&tys[0], &tys[1], &tys[2]);
+
+    assert_eq!(m.expected_generic_arg_count(&db), 2);
+    assert_eq!(m.provided_generic_arg_count(), 1);
+    assert_eq!(m.missing_generic_arg_count(&db), 1);
+    assert_eq!(full.missing_generic_arg_count(&db), 0);
+    // Defaulted arguments don't need to be provided
+    assert_eq!(v.missing_generic_arg_count(&db), 0);
+    assert_eq!(Ty::Unknown.missing_generic_arg_count(&db), 0);
+}
+
+#[test]
+fn substs_type_args_skip_self() {
+    let substs = Substs(vec![Ty::Bound(0), Ty::bool(), Ty::char()].into());
+    assert_eq!(substs.type_args(), &[Ty::bool(), Ty::char()]);
+    assert!(Substs::single(Ty::Bound(0)).type_args().is_empty());
+    assert!(Substs::empty().type_args().is_empty());
+}
+
+#[test]
+fn ty_adt_repr() {
+    let (db, _, decls) = single_file_decls(
+        r"
+        #[repr(C)]
+        struct C { x: u32 }
+        #[repr(packed(2), u8)]
+        #[derive(Clone)]
+        enum E { A }
+        struct Plain;
+        ",
+    );
+    let adt_ty = |name: &str| Ty::simple(TypeCtor::Adt(find_adt(&db, &decls, name)));
+
+    assert_eq!(adt_ty("C").adt_repr(&db), Some(ReprData { c: true, ..ReprData::default() }));
+    assert_eq!(
+        adt_ty("E").adt_repr(&db),
+        Some(ReprData { packed: Some(2), int: Some(BuiltinInt::U8), ..ReprData::default() })
+    );
+    assert_eq!(adt_ty("Plain").adt_repr(&db), None);
+    assert_eq!(Ty::unit().adt_repr(&db), None);
+}
+
+#[test]
+fn ty_try_walk_stops_early() {
+    let tuple = |tys: Vec<Ty>| {
+        Ty::apply(TypeCtor::Tuple { cardinality: tys.len() as u16 }, Substs(tys.into()))
+    };
+    let inner = tuple(vec![Ty::simple(TypeCtor::Int(Uncertain::Known(IntTy::u32()))), Ty::char()]);
+    let ty = tuple(vec![Ty::bool(), Ty::Unknown, inner]);
+
+    let mut visited = 0;
+    ty.walk(&mut |_| visited += 1);
+    assert_eq!(visited, 6);
+
+    let mut visited = 0;
+    let stopped = ty.try_walk(&mut |ty| {
+        visited += 1;
+        match ty {
+            Ty::Unknown => WalkControl::Break,
+            _ => WalkControl::Continue,
+        }
+    });
+    assert!(stopped);
+    assert_eq!(visited, 2);
+
+    assert!(ty.contains_unknown());
+    assert!(!ty.contains_infer());
+    assert!(!Ty::bool().contains_unknown());
+
+    let mut visited = 0;
+    assert!(ty.any(&mut |ty| {
+        visited += 1;
+        *ty == Ty::Unknown
+    }));
+    assert_eq!(visited, 2);
+    assert!(!ty.any(&mut |ty| *ty == Ty::Bound(0)));
+}
+
+#[test]
+fn ty_any_visits_dyn_predicates() {
+    let trait_ = TraitId::from_intern_id(InternId::from(0u32));
+    let dyn_ty = |arg: Ty| {
+        let substs = Substs(vec![Ty::Bound(0), arg].into());
+        Ty::Dyn(vec![GenericPredicate::Implemented(TraitRef { trait_, substs })].into())
+    };
+
+    assert!(dyn_ty(Ty::Unknown).contains_unknown());
+    assert!(!dyn_ty(Ty::bool()).contains_unknown());
+    assert!(dyn_ty(Ty::bool()).any(&mut |ty| *ty == Ty::bool()));
+}
+
+#[test]
+fn substs_split_at() {
+    let substs = Substs(vec![Ty::bool(), Ty::char(), Ty::unit()].into());
+
+    let (parent, own) = substs.split_at(1);
+    assert_eq!(parent, Substs::single(Ty::bool()));
+    assert_eq!(own, Substs(vec![Ty::char(), Ty::unit()].into()));
+
+    let (parent, own) = substs.split_at(5);
+    assert_eq!(parent, substs);
+    assert_eq!(own, Substs::empty());
+}
+
+#[test]
+fn parameterless_types_share_substs() {
+    fn parameters(ty: &Ty) -> &Substs {
+        match ty {
+            Ty::Apply(a_ty) => &a_ty.parameters,
+            _ => panic!("not an applied type: {:?}", ty),
+        }
+    }
+
+    let unit = Ty::unit();
+    assert!(Arc::ptr_eq(&parameters(&unit).0, &parameters(&Ty::unit()).0));
+    assert!(Arc::ptr_eq(&parameters(&unit).0, &parameters(&unit.clone()).0));
+    assert!(Arc::ptr_eq(&parameters(&unit).0, &parameters(&Ty::bool()).0));
+    assert!(Arc::ptr_eq(&parameters(&unit).0, &parameters(&Ty::default_int()).0));
+
+    let mut ty = Ty::never();
+    ty.walk_mut(&mut |_| ());
+    assert!(Arc::ptr_eq(&parameters(&unit).0, &parameters(&ty).0));
+}
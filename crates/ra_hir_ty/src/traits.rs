@@ -12,7 +12,9 @@ use rustc_hash::FxHashSet;
 
 use crate::db::HirDatabase;
 
-use super::{Canonical, GenericPredicate, HirDisplay, ProjectionTy, TraitRef, Ty, TypeWalk};
+use super::{
+    Canonical, GenericPredicate, HirDisplay, ProjectionTy, TraitRef, Ty, TypeWalk, WalkControl,
+};
 
 use self::chalk::{from_chalk, ToChalk, TypeFamily};
 
@@ -204,9 +206,8 @@ pub struct ProjectionPredicate {
 }
 
 impl TypeWalk for ProjectionPredicate {
-    fn walk(&self, f: &mut impl FnMut(&Ty)) {
-        self.projection_ty.walk(f);
-        self.ty.walk(f);
+    fn try_walk(&self, f: &mut impl FnMut(&Ty) -> WalkControl) -> bool {
+        self.projection_ty.try_walk(f) || self.ty.try_walk(f)
     }
 
     fn walk_mut_binders(&mut self, f: &mut impl FnMut(&mut Ty, usize), binders: usize) {
@@ -133,7 +133,13 @@ impl ToChalk for Ty {
     fn to_chalk(self, db: &impl HirDatabase) -> chalk_ir::Ty<TypeFamily> {
         match self {
             Ty::Apply(apply_ty) => {
-                let name = apply_ty.ctor.to_chalk(db);
+                let ctor = match apply_ty.ctor {
+                    // Chalk can't express that an unknown length matches any
+                    // length, so it only sees arrays without one
+                    TypeCtor::Array(_) => TypeCtor::Array(None),
+                    ctor => ctor,
+                };
+                let name = ctor.to_chalk(db);
                 let substitution = apply_ty.parameters.to_chalk(db);
                 chalk_ir::ApplicationTy { name, substitution }.cast().intern()
             }
@@ -85,6 +85,10 @@ pub(super) fn variant_data(db: &impl DefDatabase, var: VariantId) -> Arc<Variant
 /// Helper for mutating `Arc<[T]>` (i.e. `Arc::make_mut` for Arc slices).
 /// The underlying values are cloned if there are other strong references.
 pub(crate) fn make_mut_slice<T: Clone>(a: &mut Arc<[T]>) -> &mut [T] {
+    if a.is_empty() {
+        // Don't unshare empty slices, they might be the cached `Substs::empty()`.
+        return &mut [];
+    }
     if Arc::get_mut(a).is_none() {
         *a = a.iter().cloned().collect();
     }
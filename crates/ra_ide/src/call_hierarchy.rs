@@ -13,7 +13,7 @@ use crate::{
     call_info::FnCallNode,
     display::{ShortLabel, ToNav},
     expand::descend_into_macros,
-    goto_definition, references, FilePosition, NavigationTarget, RangeInfo,
+    goto_definition, references, FilePosition, FindAllRefsConfig, NavigationTarget, RangeInfo,
 };
 
 #[derive(Debug, Clone)]
@@ -46,7 +46,7 @@ pub(crate) fn incoming_calls(db: &RootDatabase, position: FilePosition) -> Optio
     // 1. Find all refs
     // 2. Loop through refs and determine unique fndef. This will become our `from: CallHierarchyItem,` in the reply.
     // 3. Add ranges relative to the start of the fndef.
-    let refs = references::find_all_refs(db, position, None)?;
+    let refs = references::find_all_refs(db, position, FindAllRefsConfig::default())?;
 
     let mut calls = CallLocations::default();
 
@@ -56,12 +56,19 @@ fn complete_methods(acc: &mut Completions, ctx: &CompletionContext, receiver: &T
     if let Some(krate) = ctx.module.map(|it| it.krate()) {
         let mut seen_methods = FxHashSet::default();
         let traits_in_scope = ctx.analyzer.traits_in_scope(ctx.db);
-        receiver.iterate_method_candidates(ctx.db, krate, &traits_in_scope, None, |_ty, func| {
-            if func.has_self_param(ctx.db) && seen_methods.insert(func.name(ctx.db)) {
-                acc.add_function(ctx, func);
-            }
-            None::<()>
-        });
+        receiver.iterate_method_candidates(
+            ctx.db,
+            krate,
+            &traits_in_scope,
+            ctx.module,
+            None,
+            |_ty, func| {
+                if func.has_self_param(ctx.db) && seen_methods.insert(func.name(ctx.db)) {
+                    acc.add_function(ctx, func);
+                }
+                None::<()>
+            },
+        );
     }
 }
 
@@ -274,6 +281,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_method_completion_visibility() {
+        assert_debug_snapshot!(
+        do_ref_completion(
+            r"
+            mod inner {
+                pub struct A {}
+                impl A {
+                    fn private_method(&self) {}
+                    pub fn pub_method(&self) {}
+                }
+            }
+            fn foo(a: inner::A) {
+               a.<|>
+            }
+            ",
+        ),
+        @r###"
+        [
+            CompletionItem {
+                label: "pub_method()",
+                source_range: [261; 261),
+                delete: [261; 261),
+                insert: "pub_method()$0",
+                kind: Method,
+                lookup: "pub_method",
+                detail: "pub fn pub_method(&self)",
+            },
+        ]
+        "###
+        );
+    }
+
     #[test]
     fn test_method_completion_only_fitting_impls() {
         assert_debug_snapshot!(
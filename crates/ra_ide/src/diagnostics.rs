@@ -10,7 +10,7 @@ use ra_prof::profile;
 use ra_syntax::{
     algo,
     ast::{self, make, AstNode},
-    SyntaxNode, TextRange, T,
+    Direction, SyntaxNode, TextRange, T,
 };
 use ra_text_edit::{TextEdit, TextEditBuilder};
 
@@ -65,6 +65,16 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
             fix: Some(fix),
         })
     })
+    .on::<hir::diagnostics::RedundantImport, _>(|d| {
+        let edit = remove_use_tree(&d.ast(db));
+        let fix = SourceChange::source_file_edit_from("remove redundant import", file_id, edit);
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            fix: Some(fix),
+        })
+    })
     .on::<hir::diagnostics::MissingFields, _>(|d| {
         // Note that although we could add a diagnostics to
         // fill the missing tuple field, e.g :
@@ -109,6 +119,47 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
             severity: Severity::Error,
             fix: Some(fix),
         })
+    })
+    .on::<hir::diagnostics::AssocFnCalledAsMethod, _>(|d| {
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            fix: None,
+        })
+    })
+    .on::<hir::diagnostics::MissingUnsafe, _>(|d| {
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            fix: None,
+        })
+    })
+    .on::<hir::diagnostics::TypeAnnotationsNeeded, _>(|d| {
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            fix: None,
+        })
+    })
+    .on::<hir::diagnostics::UnusedSelfParam, _>(|d| {
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            fix: None,
+        })
+    })
+    .on::<hir::diagnostics::UseAfterMove, _>(|d| {
+        // Moves are only tracked approximately, so don't report them as errors
+        res.borrow_mut().push(Diagnostic {
+            range: d.highlight_range(),
+            message: d.message(),
+            severity: Severity::WeakWarning,
+            fix: None,
+        })
     });
     if let Some(m) = sb.to_module_def(file_id) {
         m.diagnostics(db, &mut sink);
@@ -117,6 +168,64 @@ pub(crate) fn diagnostics(db: &RootDatabase, file_id: FileId) -> Vec<Diagnostic>
     res.into_inner()
 }
 
+/// Removes `use_tree` together with the comma separating it from its
+/// neighbours, or the whole `use` item if it is the only tree. Braces around
+/// a single remaining tree are removed as well.
+fn remove_use_tree(use_tree: &ast::UseTree) -> TextEdit {
+    let tree = use_tree.syntax();
+    let use_tree_list = match tree.parent().and_then(ast::UseTreeList::cast) {
+        Some(it) => it,
+        None => {
+            let range = match tree.ancestors().find_map(ast::UseItem::cast) {
+                Some(use_item) => use_item.syntax().text_range(),
+                None => tree.text_range(),
+            };
+            return TextEdit::delete(range);
+        }
+    };
+
+    let mut remaining = use_tree_list.use_trees().filter(|it| it.syntax() != tree);
+    match (remaining.next(), remaining.next()) {
+        // `a::{b}` would become `a::{}`, so remove `a::{b}` altogether
+        (None, _) => {
+            if let Some(parent) = use_tree_list.syntax().parent().and_then(ast::UseTree::cast) {
+                return remove_use_tree(&parent);
+            }
+        }
+        (Some(single_use_tree), None) => {
+            return text_edit_for_remove_unnecessary_braces_with_self_in_use_statement(
+                &single_use_tree,
+            )
+            .unwrap_or_else(|| {
+                TextEdit::replace(
+                    use_tree_list.syntax().text_range(),
+                    single_use_tree.syntax().text().to_string(),
+                )
+            });
+        }
+        _ => {}
+    }
+
+    let start = tree.text_range().start();
+    let end = tree.text_range().end();
+    let range = match algo::non_trivia_sibling(tree.clone().into(), Direction::Next) {
+        Some(comma) if comma.kind() == T![,] => {
+            let next = algo::non_trivia_sibling(comma.clone(), Direction::Next);
+            TextRange::from_to(
+                start,
+                next.map_or(comma.text_range().end(), |it| it.text_range().start()),
+            )
+        }
+        _ => match algo::non_trivia_sibling(tree.clone().into(), Direction::Prev) {
+            Some(comma) if comma.kind() == T![,] => {
+                TextRange::from_to(comma.text_range().start(), end)
+            }
+            _ => tree.text_range(),
+        },
+    };
+    TextEdit::delete(range)
+}
+
 fn check_unnecessary_braces_in_use_statement(
     acc: &mut Vec<Diagnostic>,
     file_id: FileId,
@@ -444,6 +553,97 @@ mod tests {
         check_no_diagnostic_for_target_file(content);
     }
 
+    #[test]
+    fn test_remove_redundant_import_keeps_other_trees() {
+        let before = r"
+            #[prelude_import]
+            use prelude::*;
+            mod prelude {
+                pub use crate::option::Option;
+            }
+            mod option {
+                pub enum Option<T> { None, Some(T) }
+            }
+            mod io {}
+            mod m {
+                use crate::{option::Option, io};
+            }
+        ";
+        let after = r"
+            #[prelude_import]
+            use prelude::*;
+            mod prelude {
+                pub use crate::option::Option;
+            }
+            mod option {
+                pub enum Option<T> { None, Some(T) }
+            }
+            mod io {}
+            mod m {
+                use crate::io;
+            }
+        ";
+        check_apply_diagnostic_fix(before, after);
+    }
+
+    #[test]
+    fn test_remove_redundant_import_removes_use_item_without_other_trees() {
+        let before = r"
+            #[prelude_import]
+            use prelude::*;
+            mod prelude {
+                pub use crate::option::Option;
+            }
+            mod option {
+                pub enum Option<T> { None, Some(T) }
+            }
+            mod m { use crate::option::{Option}; }
+        ";
+        let after = r"
+            #[prelude_import]
+            use prelude::*;
+            mod prelude {
+                pub use crate::option::Option;
+            }
+            mod option {
+                pub enum Option<T> { None, Some(T) }
+            }
+            mod m {  }
+        ";
+        check_apply_diagnostic_fix(before, after);
+    }
+
+    #[test]
+    fn test_remove_redundant_import_keeps_self() {
+        let before = r"
+            #[prelude_import]
+            use prelude::*;
+            mod prelude {
+                pub use crate::option::Option;
+            }
+            mod option {
+                pub enum Option<T> { None, Some(T) }
+            }
+            mod m {
+                use crate::option::{self, Option};
+            }
+        ";
+        let after = r"
+            #[prelude_import]
+            use prelude::*;
+            mod prelude {
+                pub use crate::option::Option;
+            }
+            mod option {
+                pub enum Option<T> { None, Some(T) }
+            }
+            mod m {
+                use crate::option;
+            }
+        ";
+        check_apply_diagnostic_fix(before, after);
+    }
+
     #[test]
     fn test_fill_struct_fields_empty() {
         let before = r"
@@ -587,6 +787,68 @@ mod tests {
         "###);
     }
 
+    #[test]
+    fn test_use_after_move_is_weak_warning() {
+        let (analysis, file_id) = single_file(
+            r#"#[lang = "copy"]
+trait Copy {}
+struct S;
+fn consume(s: S) {}
+fn f() { let s = S; consume(s); consume(s); }"#,
+        );
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "use of moved value",
+                range: [101; 102),
+                fix: None,
+                severity: WeakWarning,
+            },
+        ]
+        "###);
+    }
+
+    #[test]
+    fn test_unused_self_param() {
+        let (analysis, file_id) = single_file(
+            r#"trait Tr {
+    fn required(&self);
+    fn provided(&self) {}
+}
+struct S { x: u32 }
+impl S {
+    fn unused(&self) -> u32 { 0 }
+    fn unused_mut(&mut self) {}
+    fn field(&self) -> u32 { self.x }
+    fn method(self) -> u32 { self.field() }
+    fn passed(self) { Self::consume(self) }
+    fn consume(s: S) {}
+    fn assoc() {}
+}
+impl Tr for S {
+    fn required(&self) {}
+}"#,
+        );
+        let diagnostics = analysis.diagnostics(file_id).unwrap();
+        assert_debug_snapshot!(diagnostics, @r###"
+        [
+            Diagnostic {
+                message: "`self` is never used, consider making this an associated function",
+                range: [106; 111),
+                fix: None,
+                severity: WeakWarning,
+            },
+            Diagnostic {
+                message: "`self` is never used, consider making this an associated function",
+                range: [144; 153),
+                fix: None,
+                severity: WeakWarning,
+            },
+        ]
+        "###);
+    }
+
     #[test]
     fn test_check_unnecessary_braces_in_use_statement() {
         check_not_applicable(
@@ -3,11 +3,11 @@
 use either::Either;
 use hir::{AssocItem, FieldSource, HasSource, InFile, ModuleSource};
 use ra_db::{FileId, SourceDatabase};
-use ra_ide_db::RootDatabase;
+use ra_ide_db::{defs::LifetimeParam, RootDatabase};
 use ra_syntax::{
     ast::{self, DocCommentsOwner, NameOwner},
     match_ast, AstNode, SmolStr,
-    SyntaxKind::{self, BIND_PAT, TYPE_PARAM},
+    SyntaxKind::{self, BIND_PAT, CONST_PARAM, LIFETIME_PARAM, TYPE_PARAM},
     TextRange,
 };
 
@@ -377,6 +377,37 @@ impl ToNav for hir::TypeParam {
     }
 }
 
+impl ToNav for hir::ConstParam {
+    fn to_nav(&self, db: &RootDatabase) -> NavigationTarget {
+        let src = self.source(db);
+        NavigationTarget {
+            file_id: src.file_id.original_file(db),
+            name: self.name(db).to_string().into(),
+            kind: CONST_PARAM,
+            full_range: src.value.syntax().text_range(),
+            focus_range: src.value.name().map(|it| it.syntax().text_range()),
+            container_name: None,
+            description: None,
+            docs: None,
+        }
+    }
+}
+
+impl ToNav for LifetimeParam {
+    fn to_nav(&self, db: &RootDatabase) -> NavigationTarget {
+        NavigationTarget {
+            file_id: self.decl.file_id.original_file(db),
+            name: self.name.clone(),
+            kind: LIFETIME_PARAM,
+            full_range: self.decl.value,
+            focus_range: Some(self.decl.value),
+            container_name: None,
+            description: None,
+            docs: None,
+        }
+    }
+}
+
 pub(crate) fn docs_from_symbol(db: &RootDatabase, symbol: &FileSymbol) -> Option<String> {
     let parse = db.parse(symbol.file_id);
     let node = symbol.ptr.to_node(parse.tree().syntax());
@@ -4,7 +4,11 @@ use std::iter::successors;
 use hir::{InFile, Origin};
 use ra_db::FileId;
 use ra_ide_db::RootDatabase;
-use ra_syntax::{ast, AstNode, SyntaxNode, SyntaxToken, TextRange};
+use ra_syntax::{
+    ast, AstNode,
+    SyntaxKind::{IDENT, NAME, NAME_REF},
+    SyntaxNode, SyntaxToken, TextRange,
+};
 
 use crate::FileRange;
 
@@ -88,7 +92,21 @@ pub(crate) fn descend_into_macros_with_analyzer(
     source_analyzer: &hir::SourceAnalyzer,
     src: InFile<SyntaxToken>,
 ) -> InFile<SyntaxToken> {
-    successors(Some(src), |token| {
+    descend_into_macros_with_depth(db, source_analyzer, src).0
+}
+
+/// Maps `src` down through all nested macro calls whose token trees contain it.
+///
+/// Returns the token in the innermost expansion together with the number of
+/// expansions that were entered; a depth of zero means `src` is not inside a
+/// macro call (or the call could not be expanded).
+pub(crate) fn descend_into_macros_with_depth(
+    db: &RootDatabase,
+    source_analyzer: &hir::SourceAnalyzer,
+    src: InFile<SyntaxToken>,
+) -> (InFile<SyntaxToken>, usize) {
+    let mut depth = 0;
+    let token = successors(Some(src), |token| {
         let macro_call = token.value.ancestors().find_map(ast::MacroCall::cast)?;
         let tt = macro_call.token_tree()?;
         if !token.value.text_range().is_subrange(&tt.syntax().text_range()) {
@@ -97,6 +115,72 @@ pub(crate) fn descend_into_macros_with_analyzer(
         let exp = source_analyzer.expand(db, token.with_value(&macro_call))?;
         exp.map_token_down(db, token.as_ref())
     })
+    .inspect(|_| depth += 1)
     .last()
-    .unwrap()
+    .unwrap();
+    (token, depth - 1)
+}
+
+/// Like [`descend_into_macros_with_depth`], but returns the `Name` or `NameRef`
+/// node owning the resolved token, if any.
+pub(crate) fn descend_into_macros_to_name(
+    db: &RootDatabase,
+    source_analyzer: &hir::SourceAnalyzer,
+    src: InFile<SyntaxToken>,
+) -> Option<(InFile<SyntaxNode>, usize)> {
+    let (token, depth) = descend_into_macros_with_depth(db, source_analyzer, src);
+    Some((name_like_parent(&token)?, depth))
+}
+
+/// Returns the `Name` or `NameRef` node if `token` is its identifier.
+pub(crate) fn name_like_parent(token: &InFile<SyntaxToken>) -> Option<InFile<SyntaxNode>> {
+    let parent = token.value.parent();
+    match (token.value.kind(), parent.kind()) {
+        (IDENT, NAME) | (IDENT, NAME_REF) => Some(token.with_value(parent)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hir::InFile;
+    use ra_db::SourceDatabase;
+    use ra_syntax::{AstNode, SyntaxKind::NAME_REF};
+
+    use crate::mock_analysis::single_file_with_position;
+
+    use super::{descend_into_macros_to_name, descend_into_macros_with_depth};
+
+    #[test]
+    fn descend_through_two_level_macro() {
+        let (analysis, position) = single_file_with_position(
+            "
+            macro_rules! id {
+                ($($tt:tt)*) => { $($tt)* };
+            }
+            fn main() {
+                let foo = 92;
+                let bar = id!(id!(f<|>oo));
+            }
+            ",
+        );
+        let db = &*analysis.db;
+        let file = db.parse(position.file_id).tree();
+        let token = file.syntax().token_at_offset(position.offset).right_biased().unwrap();
+        let src = InFile::new(position.file_id.into(), token);
+        let analyzer = hir::SourceAnalyzer::new(
+            db,
+            src.with_value(src.value.parent()).as_ref(),
+            Some(position.offset),
+        );
+
+        let (descended, depth) = descend_into_macros_with_depth(db, &analyzer, src.clone());
+        assert_eq!(depth, 2);
+        assert_eq!(descended.value.text(), "foo");
+        assert!(descended.file_id.call_node(db).is_some());
+
+        let (name, depth) = descend_into_macros_to_name(db, &analyzer, src).unwrap();
+        assert_eq!(depth, 2);
+        assert_eq!(name.value.kind(), NAME_REF);
+    }
 }
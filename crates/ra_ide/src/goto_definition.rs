@@ -78,6 +78,8 @@ pub(crate) fn reference_definition(
         Some(NameDefinition::Macro(it)) => return Exact(it.to_nav(sb.db)),
         Some(NameDefinition::StructField(it)) => return Exact(it.to_nav(sb.db)),
         Some(NameDefinition::TypeParam(it)) => return Exact(it.to_nav(sb.db)),
+        Some(NameDefinition::ConstParam(it)) => return Exact(it.to_nav(sb.db)),
+        Some(NameDefinition::Lifetime(it)) => return Exact(it.to_nav(sb.db)),
         Some(NameDefinition::Local(it)) => return Exact(it.to_nav(sb.db)),
         Some(NameDefinition::ModuleDef(def)) => match NavigationTarget::from_def(sb.db, def) {
             Some(nav) => return Exact(nav),
@@ -1,6 +1,6 @@
 //! FIXME: write short doc here
 
-use hir::{db::AstDatabase, Adt, HasSource, HirDisplay, SourceBinder};
+use hir::{db::AstDatabase, Adt, DisplayOptions, HasSource, HirDisplay, SourceBinder, Type};
 use ra_db::SourceDatabase;
 use ra_ide_db::{defs::NameDefinition, RootDatabase};
 use ra_syntax::{
@@ -123,10 +123,11 @@ fn hover_text_from_name_kind(db: &RootDatabase, def: NameDefinition) -> Option<S
             hir::ModuleDef::TypeAlias(it) => from_def_source(db, it),
             hir::ModuleDef::BuiltinType(it) => Some(it.to_string()),
         },
-        NameDefinition::Local(it) => {
-            Some(rust_code_markup(it.ty(db).display_truncated(db, None).to_string()))
-        }
-        NameDefinition::TypeParam(_) | NameDefinition::SelfType(_) => {
+        NameDefinition::Local(it) => Some(rust_code_markup(display_type(db, &it.ty(db)))),
+        NameDefinition::TypeParam(_)
+        | NameDefinition::ConstParam(_)
+        | NameDefinition::Lifetime(_)
+        | NameDefinition::SelfType(_) => {
             // FIXME: Hover for generic param
             None
         }
@@ -214,7 +215,18 @@ pub(crate) fn type_of(db: &RootDatabase, frange: FileRange) -> Option<String> {
     } else {
         return None;
     };
-    Some(ty.display_truncated(db, None).to_string())
+    Some(display_type(db, &ty))
+}
+
+/// Renders a type for hover, where literals whose type isn't known yet are
+/// shown with the type they default to.
+fn display_type(db: &RootDatabase, ty: &Type) -> String {
+    let options = DisplayOptions {
+        omit_verbose_types: true,
+        default_literal_types: true,
+        ..Default::default()
+    };
+    ty.display_with_options(db, options).to_string()
 }
 
 #[cfg(test)]
@@ -1,5 +1,7 @@
 //! FIXME: write short doc here
 
+use std::iter;
+
 use hir::{Crate, ImplBlock, SourceBinder};
 use ra_db::SourceDatabase;
 use ra_ide_db::RootDatabase;
@@ -25,7 +27,7 @@ pub(crate) fn goto_implementation(
     } else if let Some(trait_def) = find_node_at_offset::<ast::TraitDef>(&syntax, position.offset) {
         return Some(RangeInfo::new(
             trait_def.syntax().text_range(),
-            impls_for_trait(&mut sb, position, &trait_def, krate)?,
+            impls_for_trait(&mut sb, position, &trait_def)?,
         ));
     }
 
@@ -64,18 +66,20 @@ fn impls_for_def(
     )
 }
 
+/// The impls of a trait can be in its own crate or in any crate depending on it.
 fn impls_for_trait(
     sb: &mut SourceBinder<RootDatabase>,
     position: FilePosition,
     node: &ast::TraitDef,
-    krate: Crate,
 ) -> Option<Vec<NavigationTarget>> {
     let src = hir::InFile { file_id: position.file_id.into(), value: node.clone() };
     let tr = sb.to_def(src)?;
 
-    let impls = ImplBlock::for_trait(sb.db, krate, tr);
+    let krate = tr.module(sb.db).krate();
+    let crates = iter::once(krate).chain(krate.transitive_reverse_dependencies(sb.db));
+    let impls = crates.flat_map(|krate| ImplBlock::for_trait(sb.db, krate, tr));
 
-    Some(impls.into_iter().map(|imp| imp.to_nav(sb.db)).collect())
+    Some(impls.map(|imp| imp.to_nav(sb.db)).collect())
 }
 
 #[cfg(test)]
@@ -181,6 +185,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn goto_implementation_for_trait_in_dependent_crate() {
+        check_goto(
+            "
+            //- /lib.rs
+            struct Foo;
+            impl foo::T for Foo {}
+            //- /foo/lib.rs
+            pub trait T<|> {}
+            pub struct Bar;
+            impl T for Bar {}
+            ",
+            &["impl IMPL_BLOCK FileId(1) [12; 34)", "impl IMPL_BLOCK FileId(2) [31; 48)"],
+        );
+    }
+
     #[test]
     fn goto_implementation_all_impls() {
         check_goto(
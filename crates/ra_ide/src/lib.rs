@@ -70,7 +70,8 @@ pub use crate::{
     hover::HoverResult,
     inlay_hints::{InlayHint, InlayKind},
     references::{
-        Declaration, Reference, ReferenceAccess, ReferenceKind, ReferenceSearchResult, SearchScope,
+        Declaration, FindAllRefsConfig, Reference, ReferenceAccess, ReferenceKind,
+        ReferenceSearchResult, RenameConflict, SearchScope,
     },
     runnables::{Runnable, RunnableKind, TestId},
     source_change::{FileSystemEdit, SourceChange, SourceFileEdit},
@@ -367,9 +368,9 @@ impl Analysis {
     pub fn find_all_refs(
         &self,
         position: FilePosition,
-        search_scope: Option<SearchScope>,
+        config: FindAllRefsConfig,
     ) -> Cancelable<Option<ReferenceSearchResult>> {
-        self.with_db(|db| references::find_all_refs(db, position, search_scope).map(|it| it.info))
+        self.with_db(|db| references::find_all_refs(db, position, config).map(|it| it.info))
     }
 
     /// Returns a short text describing element at position.
@@ -415,6 +416,12 @@ impl Analysis {
         self.with_db(|db| db.crate_graph().edition(crate_id))
     }
 
+    /// Returns the cfg options (atoms and key-value pairs like enabled
+    /// features) the given crate is compiled with.
+    pub fn crate_cfg_options(&self, crate_id: CrateId) -> Cancelable<CfgOptions> {
+        self.with_db(|db| db.crate_graph().cfg_options(crate_id).clone())
+    }
+
     /// Returns the root file of the given crate.
     pub fn crate_root(&self, crate_id: CrateId) -> Cancelable<FileId> {
         self.with_db(|db| db.crate_graph().crate_root(crate_id))
@@ -425,11 +432,30 @@ impl Analysis {
         self.with_db(|db| runnables::runnables(db, file_id))
     }
 
+    /// Returns a runnable for all tests of the given crate, if it has any.
+    pub fn crate_runnable(&self, crate_id: CrateId) -> Cancelable<Option<Runnable>> {
+        self.with_db(|db| runnables::crate_runnable(db, crate_id))
+    }
+
     /// Computes syntax highlighting for the given file.
     pub fn highlight(&self, file_id: FileId) -> Cancelable<Vec<HighlightedRange>> {
         self.with_db(|db| syntax_highlighting::highlight(db, file_id))
     }
 
+    /// Like `highlight`, but passes each range to `sink` as soon as it is
+    /// computed. If the computation is canceled, `sink` has only seen some of
+    /// the ranges.
+    pub fn highlight_events(
+        &self,
+        file_id: FileId,
+        sink: impl FnMut(HighlightedRange),
+    ) -> Cancelable<()> {
+        let mut sink = std::panic::AssertUnwindSafe(sink);
+        self.with_db(move |db| {
+            syntax_highlighting::highlight_events(db, file_id, |range| (sink.0)(range))
+        })
+    }
+
     /// Computes syntax highlighting for the given file.
     pub fn highlight_as_html(&self, file_id: FileId, rainbow: bool) -> Cancelable<String> {
         self.with_db(|db| syntax_highlighting::highlight_as_html(db, file_id, rainbow))
@@ -466,6 +492,16 @@ impl Analysis {
         self.with_db(|db| references::rename(db, position, new_name))
     }
 
+    /// Returns the existing definitions that renaming the reference at the
+    /// position to the new name would clash with.
+    pub fn rename_conflicts(
+        &self,
+        position: FilePosition,
+        new_name: &str,
+    ) -> Cancelable<Vec<RenameConflict>> {
+        self.with_db(|db| references::rename_conflicts(db, position, new_name))
+    }
+
     pub fn structural_search_replace(
         &self,
         query: &str,
@@ -13,37 +13,63 @@ mod classify;
 mod rename;
 mod search_scope;
 
-use crate::expand::descend_into_macros_with_analyzer;
-use hir::{InFile, SourceBinder};
+use crate::expand::descend_into_macros_to_name;
+use hir::{Adt, AsAssocItem, AssocItemContainer, HasSource, InFile, ModuleDef, SourceBinder};
 use once_cell::unsync::Lazy;
-use ra_db::{SourceDatabase, SourceDatabaseExt};
+use ra_db::{FileId, SourceDatabase, SourceDatabaseExt};
 use ra_ide_db::RootDatabase;
 use ra_prof::profile;
 use ra_syntax::{
-    algo::find_node_at_offset,
+    algo::{find_covering_element, find_node_at_offset},
     ast::{self, NameOwner},
-    match_ast, AstNode, SourceFile, SyntaxKind, SyntaxNode, TextRange, TextUnit, TokenAtOffset,
+    match_ast, AstNode, NodeOrToken, SourceFile, SyntaxKind, SyntaxNode, SyntaxToken, TextRange,
+    TextUnit, TokenAtOffset,
 };
 
 use crate::{display::ToNav, FilePosition, FileRange, NavigationTarget, RangeInfo};
 
 pub(crate) use self::{
-    classify::{classify_name, classify_name_ref},
-    rename::rename,
+    classify::{classify_doc_link, classify_lifetime, classify_name, classify_name_ref, doc_links},
+    rename::{rename, rename_conflicts},
 };
-pub(crate) use ra_ide_db::defs::NameDefinition;
-
-pub use self::search_scope::SearchScope;
+pub(crate) use ra_ide_db::defs::{LifetimeParam, NameDefinition};
+
+pub use self::{rename::RenameConflict, search_scope::SearchScope};
+
+/// Options for `find_all_refs`. The default finds the references in the whole
+/// search scope of the definition.
+#[derive(Default)]
+pub struct FindAllRefsConfig {
+    /// Restricts the search to files (or ranges) of this scope.
+    pub search_scope: Option<SearchScope>,
+    /// If the definition is an operator trait method in an impl, like
+    /// `Add::add`, also include the operator expressions dispatching to it
+    /// (`a + b`).
+    pub include_operators: bool,
+    /// Fill in `Reference::container` for each of the references.
+    pub include_containers: bool,
+    /// Leave the declaration out when iterating over the result, so that only
+    /// the use sites remain.
+    pub exclude_declaration: bool,
+    /// Skip files generated into a `target` directory, like the outputs of
+    /// build scripts in `OUT_DIR`.
+    pub exclude_generated: bool,
+}
 
 #[derive(Debug, Clone)]
 pub struct ReferenceSearchResult {
     declaration: Declaration,
     references: Vec<Reference>,
+    /// Whether the declaration is yielded first when iterating over the result.
+    include_declaration: bool,
 }
 
 #[derive(Debug, Clone)]
 pub struct Declaration {
     pub nav: NavigationTarget,
+    /// Range of the declared name, like `foo` in `fn foo() {}`. The whole item
+    /// is covered by `nav.full_range()`.
+    pub name_range: Option<TextRange>,
     pub kind: ReferenceKind,
     pub access: Option<ReferenceAccess>,
 }
@@ -53,11 +79,19 @@ pub struct Reference {
     pub file_range: FileRange,
     pub kind: ReferenceKind,
     pub access: Option<ReferenceAccess>,
+    /// Path of the item the reference occurs in, like `Foo::bar` for a
+    /// reference inside `impl Foo { fn bar() {} }`. Only computed if
+    /// `FindAllRefsConfig::include_containers` is set.
+    pub container: Option<String>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ReferenceKind {
     StructLiteral,
+    /// A tuple struct used as a constructor function, like `let f = Foo;`.
+    FunctionValue,
+    /// An intra-doc link in a doc comment, like `[Foo]` or `[text](Foo)`.
+    DocLink,
     Other,
 }
 
@@ -80,11 +114,41 @@ impl ReferenceSearchResult {
         &self.references
     }
 
+    /// The use sites of the declaration, regardless of whether the declaration
+    /// itself is included when iterating over the result.
+    pub fn references_only(&self) -> &[Reference] {
+        &self.references
+    }
+
     /// Total number of references
     /// At least 1 since all valid references should
-    /// Have a declaration
+    /// Have a declaration, unless it is excluded
     pub fn len(&self) -> usize {
-        self.references.len() + 1
+        self.references.len() + if self.include_declaration { 1 } else { 0 }
+    }
+}
+
+impl Reference {
+    /// Returns the source line containing this reference, without the line
+    /// terminator, together with the range of the reference inside the line.
+    ///
+    /// If the reference spans several lines, the range is cut off at the end
+    /// of the first one.
+    pub fn preview(&self, db: &RootDatabase) -> Option<(String, TextRange)> {
+        let text = db.file_text(self.file_range.file_id);
+        let start = self.file_range.range.start().to_usize();
+        let end = self.file_range.range.end().to_usize();
+        if end > text.len() {
+            return None;
+        }
+        let line_start = text[..start].rfind('\n').map_or(0, |it| it + 1);
+        let line_end = text[start..].find('\n').map_or(text.len(), |it| start + it);
+        let line = text[line_start..line_end].trim_end_matches('\r');
+        let range = TextRange::from_to(
+            TextUnit::from_usize(start - line_start),
+            TextUnit::from_usize(end.min(line_start + line.len()) - line_start),
+        );
+        Some((line.to_string(), range))
     }
 }
 
@@ -96,14 +160,17 @@ impl IntoIterator for ReferenceSearchResult {
 
     fn into_iter(mut self) -> Self::IntoIter {
         let mut v = Vec::with_capacity(self.len());
-        v.push(Reference {
-            file_range: FileRange {
-                file_id: self.declaration.nav.file_id(),
-                range: self.declaration.nav.range(),
-            },
-            kind: self.declaration.kind,
-            access: self.declaration.access,
-        });
+        if self.include_declaration {
+            v.push(Reference {
+                file_range: FileRange {
+                    file_id: self.declaration.nav.file_id(),
+                    range: self.declaration.nav.range(),
+                },
+                kind: self.declaration.kind,
+                access: self.declaration.access,
+                container: None,
+            });
+        }
         v.append(&mut self.references);
         v.into_iter()
     }
@@ -112,7 +179,7 @@ impl IntoIterator for ReferenceSearchResult {
 pub(crate) fn find_all_refs(
     db: &RootDatabase,
     position: FilePosition,
-    search_scope: Option<SearchScope>,
+    config: FindAllRefsConfig,
 ) -> Option<RangeInfo<ReferenceSearchResult>> {
     let parse = db.parse(position.file_id);
     let syntax = parse.tree().syntax().clone();
@@ -133,11 +200,13 @@ pub(crate) fn find_all_refs(
         NameDefinition::SelfType(imp) => imp.to_nav(db),
         NameDefinition::Local(local) => local.to_nav(db),
         NameDefinition::TypeParam(_) => return None,
+        NameDefinition::ConstParam(param) => param.to_nav(db),
+        NameDefinition::Lifetime(lifetime) => lifetime.to_nav(db),
     };
 
     let search_scope = {
-        let base = SearchScope::for_def(&def, db);
-        match search_scope {
+        let base = SearchScope::for_def(&def, db, !config.exclude_generated);
+        match config.search_scope {
             None => base,
             Some(scope) => base.intersection(&scope),
         }
@@ -146,17 +215,36 @@ pub(crate) fn find_all_refs(
     let decl_range = declaration.range();
 
     let declaration = Declaration {
+        name_range: declaration.focus_range(),
         nav: declaration,
         kind: ReferenceKind::Other,
         access: decl_access(&def, &name, &syntax, decl_range),
     };
 
-    let references = process_definition(db, def, name, search_scope)
-        .into_iter()
-        .filter(|r| search_kind == ReferenceKind::Other || search_kind == r.kind)
-        .collect();
+    let mut references: Vec<_> =
+        process_definition(db, def, name, search_scope, config.include_operators)
+            .into_iter()
+            .filter(|r| match (&search_kind, &r.kind) {
+                (ReferenceKind::Other, _) => true,
+                // Calling a constructor function value constructs the struct as well
+                (ReferenceKind::StructLiteral, ReferenceKind::FunctionValue) => true,
+                (search_kind, kind) => search_kind == kind,
+            })
+            .collect();
+    if config.include_containers {
+        for reference in references.iter_mut() {
+            reference.container = reference_container(db, reference.file_range);
+        }
+    }
 
-    Some(RangeInfo::new(range, ReferenceSearchResult { declaration, references }))
+    Some(RangeInfo::new(
+        range,
+        ReferenceSearchResult {
+            declaration,
+            references,
+            include_declaration: !config.exclude_declaration,
+        },
+    ))
 }
 
 fn find_name(
@@ -171,6 +259,13 @@ fn find_name(
         let range = name.syntax().text_range();
         return Some(RangeInfo::new(range, (name.text().to_string(), def)));
     }
+    if let Some(lifetime) =
+        syntax.token_at_offset(position.offset).find(|it| it.kind() == SyntaxKind::LIFETIME)
+    {
+        let def = classify_lifetime(&mut sb, InFile::new(position.file_id.into(), &lifetime))?;
+        let range = lifetime.text_range();
+        return Some(RangeInfo::new(range, (lifetime.text().to_string(), def)));
+    }
     let name_ref = find_node_at_offset::<ast::NameRef>(&syntax, position.offset)?;
     let def = classify_name_ref(&mut sb, InFile::new(position.file_id.into(), &name_ref))?;
     let range = name_ref.syntax().text_range();
@@ -182,13 +277,23 @@ fn process_definition(
     def: NameDefinition,
     name: String,
     scope: SearchScope,
+    include_operators: bool,
 ) -> Vec<Reference> {
     let _p = profile("process_definition");
 
+    if let NameDefinition::Lifetime(lifetime) = &def {
+        return find_lifetime_refs(db, &def, lifetime, scope);
+    }
+
     let pat = name.as_str();
     let mut refs = vec![];
+    let search_operators = include_operators && is_impl_method(db, &def);
 
     for (file_id, search_range) in scope {
+        if search_operators {
+            refs.extend(find_operator_refs(db, &def, file_id, search_range));
+        }
+
         let text = db.file_text(file_id);
 
         let parse = Lazy::new(|| SourceFile::parse(&text));
@@ -210,19 +315,30 @@ fn process_definition(
                     TokenAtOffset::Single(t) => t,
                     TokenAtOffset::Between(_, t) => t,
                 };
+                if t.kind() == SyntaxKind::COMMENT {
+                    let range = TextRange::offset_len(offset, TextUnit::of_str(pat));
+                    let in_scope = search_range.map_or(true, |it| range.is_subrange(&it));
+                    let comment = InFile::new(file_id.into(), &t);
+                    if in_scope && is_doc_link_ref(&mut sb, &def, comment, range) {
+                        refs.push(Reference {
+                            file_range: FileRange { file_id, range },
+                            kind: ReferenceKind::DocLink,
+                            access: None,
+                            container: None,
+                        });
+                    }
+                    continue;
+                }
                 let range = t.text_range();
                 let analyzer = analyzer.get_or_insert_with(|| {
                     sb.analyze(InFile::new(file_id.into(), parse.tree().syntax()), None)
                 });
-                let expanded = descend_into_macros_with_analyzer(
-                    db,
-                    &analyzer,
-                    InFile::new(file_id.into(), t),
-                );
-                if let Some(token) = ast::NameRef::cast(expanded.value.parent()) {
-                    (expanded.with_value(token), range)
-                } else {
-                    continue;
+                let name_ref =
+                    descend_into_macros_to_name(db, &analyzer, InFile::new(file_id.into(), t))
+                        .and_then(|(node, _depth)| node.map(ast::NameRef::cast).transpose());
+                match name_ref {
+                    Some(name_ref) => (name_ref, range),
+                    None => continue,
                 }
             };
 
@@ -234,12 +350,16 @@ fn process_definition(
             // FIXME: reuse sb
             // See https://github.com/rust-lang/rust/pull/68198#issuecomment-574269098
 
+            // `NameDefinition`s are compared by ids (a `Local` by its body and
+            // `PatId`), which don't depend on the particular parse of the file.
             if let Some(d) = classify_name_ref(&mut sb, name_ref.as_ref()) {
-                if d == def {
+                if d == def || is_trait_method_impl(db, &def, &d) {
                     let kind = if is_record_lit_name_ref(&name_ref.value)
                         || is_call_expr_name_ref(&name_ref.value)
                     {
                         ReferenceKind::StructLiteral
+                    } else if is_tuple_struct_value_name_ref(db, &d, &name_ref.value) {
+                        ReferenceKind::FunctionValue
                     } else {
                         ReferenceKind::Other
                     };
@@ -248,6 +368,7 @@ fn process_definition(
                         file_range: FileRange { file_id, range },
                         kind,
                         access: reference_access(&d, &name_ref.value),
+                        container: None,
                     });
                 }
             }
@@ -256,6 +377,182 @@ fn process_definition(
     refs
 }
 
+/// Checks whether `range` is the last segment of an intra-doc link in
+/// `comment` that resolves to `def`.
+fn is_doc_link_ref(
+    sb: &mut SourceBinder<RootDatabase>,
+    def: &NameDefinition,
+    comment: InFile<&SyntaxToken>,
+    range: TextRange,
+) -> bool {
+    doc_links(comment.value).into_iter().any(|(path, path_range)| {
+        if path_range.end() != range.end() || !range.is_subrange(&path_range) {
+            return false;
+        }
+        let qualifier = &path[..(range.start() - path_range.start()).to_usize()];
+        (qualifier.is_empty() || qualifier.ends_with("::"))
+            && classify_doc_link(sb, comment, &path).as_ref() == Some(def)
+    })
+}
+
+/// Lifetimes are tokens rather than `NameRef`s, so their uses are found by
+/// walking the tokens in the scope, which is just the declaring item.
+fn find_lifetime_refs(
+    db: &RootDatabase,
+    def: &NameDefinition,
+    lifetime: &LifetimeParam,
+    scope: SearchScope,
+) -> Vec<Reference> {
+    let mut sb = SourceBinder::new(db);
+    let mut refs = vec![];
+    for (file_id, search_range) in scope {
+        let parse = db.parse(file_id);
+        let tokens = parse
+            .tree()
+            .syntax()
+            .descendants_with_tokens()
+            .filter_map(|it| it.into_token())
+            .filter(|it| it.kind() == SyntaxKind::LIFETIME && *it.text() == lifetime.name);
+        for token in tokens {
+            let range = token.text_range();
+            if let Some(search_range) = search_range {
+                if !range.is_subrange(&search_range) {
+                    continue;
+                }
+            }
+            let token = InFile::new(file_id.into(), &token);
+            // The declaration itself is reported separately
+            if token.file_id == lifetime.decl.file_id && range == lifetime.decl.value {
+                continue;
+            }
+            if classify_lifetime(&mut sb, token).as_ref() == Some(def) {
+                refs.push(Reference {
+                    file_range: FileRange { file_id, range },
+                    kind: ReferenceKind::Other,
+                    access: None,
+                    container: None,
+                });
+            }
+        }
+    }
+    refs
+}
+
+/// Checks whether `candidate` is a method from an impl block implementing the
+/// trait method `def`. Method calls on concrete types resolve to the impl's
+/// method, but they should still count as references to the trait method.
+fn is_trait_method_impl(
+    db: &RootDatabase,
+    def: &NameDefinition,
+    candidate: &NameDefinition,
+) -> bool {
+    let (trait_fn, impl_fn) = match (def, candidate) {
+        (
+            NameDefinition::ModuleDef(ModuleDef::Function(trait_fn)),
+            NameDefinition::ModuleDef(ModuleDef::Function(impl_fn)),
+        ) => (*trait_fn, *impl_fn),
+        _ => return false,
+    };
+    let trait_ = match trait_fn.as_assoc_item(db).map(|it| it.container(db)) {
+        Some(AssocItemContainer::Trait(it)) => it,
+        _ => return false,
+    };
+    let impl_block = match impl_fn.as_assoc_item(db).map(|it| it.container(db)) {
+        Some(AssocItemContainer::ImplBlock(it)) => it,
+        _ => return false,
+    };
+    impl_block.target_trait_def(db) == Some(trait_) && impl_fn.name(db) == trait_fn.name(db)
+}
+
+fn is_impl_method(db: &RootDatabase, def: &NameDefinition) -> bool {
+    match def {
+        NameDefinition::ModuleDef(ModuleDef::Function(function)) => {
+            match function.as_assoc_item(db).map(|it| it.container(db)) {
+                Some(AssocItemContainer::ImplBlock(_)) => true,
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Finds the binary and prefix operator expressions whose overloaded operator
+/// resolves to `def`. Text search can't find these, since they don't mention
+/// the method name.
+fn find_operator_refs(
+    db: &RootDatabase,
+    def: &NameDefinition,
+    file_id: FileId,
+    search_range: Option<TextRange>,
+) -> Vec<Reference> {
+    let mut sb = SourceBinder::new(db);
+    let parse = db.parse(file_id);
+    let mut refs = Vec::new();
+
+    for node in parse.tree().syntax().descendants() {
+        let (op_token, function) = match_ast! {
+            match (node) {
+                ast::BinExpr(it) => {
+                    let analyzer = sb.analyze(InFile::new(file_id.into(), it.syntax()), None);
+                    (it.op_details().map(|(token, _)| token), analyzer.resolve_bin_expr(db, &it))
+                },
+                ast::PrefixExpr(it) => {
+                    let analyzer = sb.analyze(InFile::new(file_id.into(), it.syntax()), None);
+                    (it.op_token(), analyzer.resolve_prefix_expr(db, &it))
+                },
+                _ => continue,
+            }
+        };
+        let (op_token, function) = match (op_token, function) {
+            (Some(op_token), Some(function)) => (op_token, function),
+            _ => continue,
+        };
+        let range = op_token.text_range();
+        if let Some(search_range) = search_range {
+            if !range.is_subrange(&search_range) {
+                continue;
+            }
+        }
+        if NameDefinition::ModuleDef(function.into()) == *def {
+            refs.push(Reference {
+                file_range: FileRange { file_id, range },
+                kind: ReferenceKind::Other,
+                access: None,
+                container: None,
+            });
+        }
+    }
+    refs
+}
+
+/// Renders the path of the functions, impls and inline modules enclosing
+/// `file_range`, outermost first.
+fn reference_container(db: &RootDatabase, file_range: FileRange) -> Option<String> {
+    let parse = db.parse(file_range.file_id);
+    let node = match find_covering_element(parse.tree().syntax(), file_range.range) {
+        NodeOrToken::Node(node) => node,
+        NodeOrToken::Token(token) => token.parent(),
+    };
+    let mut segments = node
+        .ancestors()
+        .filter_map(|node| {
+            match_ast! {
+                match node {
+                    ast::FnDef(it) => { it.name().map(|name| name.text().to_string()) },
+                    ast::ImplBlock(it) => { it.target_type().map(|ty| ty.syntax().text().to_string()) },
+                    ast::Module(it) => { it.name().map(|name| name.text().to_string()) },
+                    _ => { None },
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+    if segments.is_empty() {
+        return None;
+    }
+    segments.reverse();
+    Some(segments.join("::"))
+}
+
 fn decl_access(
     def: &NameDefinition,
     name: &str,
@@ -354,13 +651,81 @@ fn is_call_expr_name_ref(name_ref: &ast::NameRef) -> bool {
         .unwrap_or(false)
 }
 
+fn is_tuple_struct_value_name_ref(
+    db: &RootDatabase,
+    def: &NameDefinition,
+    name_ref: &ast::NameRef,
+) -> bool {
+    let is_tuple_struct = match def {
+        NameDefinition::ModuleDef(ModuleDef::Adt(Adt::Struct(it))) => {
+            match it.source(db).value.kind() {
+                ast::StructKind::Tuple(_) => true,
+                _ => false,
+            }
+        }
+        _ => false,
+    };
+    is_tuple_struct
+        && name_ref
+            .syntax()
+            .ancestors()
+            .find_map(ast::PathExpr::cast)
+            .and_then(|p| p.path()?.segment()?.name_ref())
+            .as_ref()
+            == Some(name_ref)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use hir::{InFile, SourceBinder};
+    use ra_db::SourceDatabase;
+    use ra_ide_db::RootDatabase;
+    use ra_syntax::{algo::find_node_at_offset, ast, AstNode, TextRange};
+
+    use super::{classify_name_ref, NameDefinition};
     use crate::{
         mock_analysis::{analysis_and_position, single_file_with_position, MockAnalysis},
-        Declaration, Reference, ReferenceSearchResult, SearchScope,
+        AnalysisChange, AnalysisHost, Declaration, FindAllRefsConfig, Reference,
+        ReferenceSearchResult, SearchScope,
     };
 
+    #[test]
+    fn test_local_def_is_stable_across_reparse() {
+        let code = r#"
+    fn main() {
+        let x = 92;
+        let y = x<|> + 1;
+    }"#;
+
+        let mut mock = MockAnalysis::new();
+        let position = mock.add_file_with_position("/main.rs", code);
+        let mut host = mock.analysis_host();
+        let classify = |host: &AnalysisHost| {
+            let analysis = host.analysis();
+            let db: &RootDatabase = &analysis.db;
+            let file = db.parse(position.file_id).tree();
+            let name_ref =
+                find_node_at_offset::<ast::NameRef>(file.syntax(), position.offset).unwrap();
+            let mut sb = SourceBinder::new(db);
+            classify_name_ref(&mut sb, InFile::new(position.file_id.into(), &name_ref)).unwrap()
+        };
+
+        let before = classify(&host);
+        match before {
+            NameDefinition::Local(_) => (),
+            _ => panic!("expected a local, got {:?}", before),
+        }
+
+        let text = host.analysis().file_text(position.file_id).unwrap();
+        let mut change = AnalysisChange::new();
+        change.change_file(position.file_id, Arc::new(format!("{}\n", text)));
+        host.apply_change(change);
+
+        assert_eq!(classify(&host), before);
+    }
+
     #[test]
     fn test_struct_literal_after_space() {
         let code = r#"
@@ -435,6 +800,211 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tuple_struct_constructor_refs() {
+        let code = r#"
+    struct Foo<|>(i32);
+
+    fn main() {
+        let f: Foo;
+        f = Foo(1);
+        let g = Foo;
+    }"#;
+
+        let refs = get_all_refs(code);
+        check_result(
+            refs,
+            "Foo STRUCT_DEF FileId(1) [5; 21) [12; 15) Other",
+            &["FileId(1) [71; 74) StructLiteral", "FileId(1) [95; 98) FunctionValue"],
+        );
+    }
+
+    #[test]
+    fn test_find_all_refs_for_tuple_struct_field_index() {
+        let code = r#"
+    struct Foo(i32, i32);
+
+    fn main() {
+        let foo = Foo(1, 2);
+        foo.0;
+        foo.<|>1;
+        foo.1 = 3;
+    }"#;
+
+        let refs = get_all_refs(code);
+        check_result(
+            refs,
+            "TUPLE_FIELD_DEF FileId(1) [21; 24) Other",
+            &["FileId(1) [100; 101) Other Read", "FileId(1) [115; 116) Other Write"],
+        );
+    }
+
+    #[test]
+    fn test_find_all_refs_with_operators() {
+        let code = r#"
+    #[lang = "add"]
+    pub trait Add<Rhs = Self> {
+        type Output;
+        fn add(self, rhs: Rhs) -> Self::Output;
+    }
+
+    struct Foo;
+
+    impl Add for Foo {
+        type Output = Foo;
+        fn add<|>(self, rhs: Foo) -> Foo { Foo }
+    }
+
+    fn main() {
+        let a = Foo;
+        let b = Foo;
+        let c = a + b;
+        let d = -a;
+    }"#;
+
+        let (analysis, position) = single_file_with_position(code);
+        let refs = analysis.find_all_refs(position, FindAllRefsConfig::default()).unwrap().unwrap();
+        check_result(refs, "add FN_DEF FileId(1) [204; 241) [207; 210) Other", &[]);
+
+        let refs = analysis
+            .find_all_refs(
+                position,
+                FindAllRefsConfig { include_operators: true, ..Default::default() },
+            )
+            .unwrap()
+            .unwrap();
+        check_result(
+            refs,
+            "add FN_DEF FileId(1) [204; 241) [207; 210) Other",
+            &["FileId(1) [325; 326) Other"],
+        );
+    }
+
+    #[test]
+    fn test_find_all_refs_trait_method_through_impls() {
+        let code = r#"
+    trait Foo {
+        fn bar<|>(&self);
+    }
+
+    struct A;
+    struct B;
+
+    impl Foo for A {
+        fn bar(&self) {}
+    }
+
+    impl Foo for B {
+        fn bar(&self) {}
+    }
+
+    fn main() {
+        A.bar();
+        B.bar();
+    }"#;
+
+        let (analysis, position) = single_file_with_position(code);
+        let refs = analysis.find_all_refs(position, FindAllRefsConfig::default()).unwrap().unwrap();
+        check_result(
+            refs,
+            "bar FN_DEF FileId(1) [25; 39) [28; 31) Other",
+            &["FileId(1) [208; 211) Other", "FileId(1) [225; 228) Other"],
+        );
+    }
+
+    #[test]
+    fn test_find_all_refs_with_containers() {
+        let code = r#"
+    struct Foo;
+
+    fn foo() {
+        let f = Foo<|>;
+    }
+
+    impl Foo {
+        fn bar() -> Foo { Foo }
+    }
+
+    mod m {
+        fn baz() { super::Foo; }
+    }"#;
+
+        let (analysis, position) = single_file_with_position(code);
+        let refs = analysis.find_all_refs(position, FindAllRefsConfig::default()).unwrap().unwrap();
+        assert!(refs.references().iter().all(|it| it.container.is_none()));
+
+        let refs = analysis
+            .find_all_refs(
+                position,
+                FindAllRefsConfig { include_containers: true, ..Default::default() },
+            )
+            .unwrap()
+            .unwrap();
+        let containers =
+            refs.references().iter().map(|it| it.container.as_deref()).collect::<Vec<_>>();
+        assert_eq!(
+            containers,
+            vec![Some("foo"), Some("Foo"), Some("Foo::bar"), Some("Foo::bar"), Some("m::baz")]
+        );
+    }
+
+    #[test]
+    fn test_reference_preview() {
+        let code = "fn main() {\r\n    let café = 1;\r\n    let x = café<|> + 1;\r\n}\r\n";
+        let (analysis, position) = single_file_with_position(code);
+        let refs = analysis.find_all_refs(position, FindAllRefsConfig::default()).unwrap().unwrap();
+        assert_eq!(refs.references().len(), 1);
+
+        let (line, range) = refs.references()[0].preview(&analysis.db).unwrap();
+        assert_eq!(line, "    let x = café + 1;");
+        assert_eq!(&line[range], "café");
+    }
+
+    #[test]
+    fn test_find_all_refs_excluding_decl() {
+        let code = r#"
+    fn main() {
+        let mut i = 1;
+        i = i<|> + 1;
+    }"#;
+
+        let (analysis, pos) = single_file_with_position(code);
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
+        assert_eq!(refs.len(), 3);
+        assert_eq!(refs.into_iter().count(), 3);
+
+        let refs = analysis
+            .find_all_refs(
+                pos,
+                FindAllRefsConfig { exclude_declaration: true, ..Default::default() },
+            )
+            .unwrap()
+            .unwrap();
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs.references_only().len(), 2);
+        let ranges: Vec<_> = refs.into_iter().map(|it| it.file_range.range).collect();
+        assert_eq!(format!("{:?}", ranges), "[[48; 49), [52; 53)]");
+    }
+
+    #[test]
+    fn test_find_all_refs_from_use_alias() {
+        let code = r#"
+    mod foo {
+        pub struct Bar;
+    }
+    use foo::Bar as Baz<|>;
+    fn f(baz: Baz) -> Baz {
+        baz
+    }"#;
+
+        let (analysis, pos) = single_file_with_position(code);
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
+        assert_eq!(refs.decl_target().name().as_str(), "Bar");
+        let text = analysis.file_text(pos.file_id).unwrap();
+        let texts: Vec<_> = refs.references().iter().map(|it| &text[it.file_range.range]).collect();
+        assert_eq!(texts, vec!["Baz", "Baz"]);
+    }
+
     #[test]
     fn test_find_all_refs_for_local() {
         let code = r#"
@@ -543,6 +1113,21 @@ mod tests {
         check_result(refs, "B ENUM_VARIANT FileId(1) [83; 84) [83; 84) Other", &[]);
     }
 
+    #[test]
+    fn test_find_all_refs_const_param() {
+        let code = r#"
+    fn f<const N<|>: usize>() -> [u8; N] {
+        [0; N]
+    }"#;
+
+        let refs = get_all_refs(code);
+        check_result(
+            refs,
+            "N CONST_PARAM FileId(1) [10; 24) [16; 17) Other",
+            &["FileId(1) [36; 37) Other", "FileId(1) [53; 54) Other"],
+        );
+    }
+
     #[test]
     fn test_find_all_refs_two_modules() {
         let code = r#"
@@ -578,7 +1163,7 @@ mod tests {
         "#;
 
         let (analysis, pos) = analysis_and_position(code);
-        let refs = analysis.find_all_refs(pos, None).unwrap().unwrap();
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
         check_result(
             refs,
             "Foo STRUCT_DEF FileId(2) [16; 50) [27; 30) Other",
@@ -608,7 +1193,7 @@ mod tests {
         "#;
 
         let (analysis, pos) = analysis_and_position(code);
-        let refs = analysis.find_all_refs(pos, None).unwrap().unwrap();
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
         check_result(
             refs,
             "foo SOURCE_FILE FileId(2) [0; 35) Other",
@@ -637,7 +1222,7 @@ mod tests {
         "#;
 
         let (analysis, pos) = analysis_and_position(code);
-        let refs = analysis.find_all_refs(pos, None).unwrap().unwrap();
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
         check_result(
             refs,
             "Foo STRUCT_DEF FileId(3) [0; 41) [18; 21) Other",
@@ -645,6 +1230,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_all_refs_doc_links() {
+        let code = r#"
+    struct Foo<|>;
+
+    /// Makes a [Foo], see [`Foo`] and [the type](crate::Foo).
+    /// [FooBar] and [text](Foo::new) don't link to it.
+    fn make(foo: Foo) {}"#;
+
+        let refs = get_all_refs(code);
+        check_result(
+            refs,
+            "Foo STRUCT_DEF FileId(1) [5; 16) [12; 15) Other",
+            &[
+                "FileId(1) [35; 38) DocLink",
+                "FileId(1) [47; 50) DocLink",
+                "FileId(1) [75; 78) DocLink",
+                "FileId(1) [154; 157) Other",
+            ],
+        );
+    }
+
     #[test]
     fn test_find_all_refs_with_scope() {
         let code = r#"
@@ -665,15 +1272,23 @@ mod tests {
         let bar = mock.id_of("/bar.rs");
         let analysis = mock.analysis();
 
-        let refs = analysis.find_all_refs(pos, None).unwrap().unwrap();
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
         check_result(
             refs,
             "quux FN_DEF FileId(1) [18; 34) [25; 29) Other",
             &["FileId(2) [16; 20) StructLiteral", "FileId(3) [16; 20) StructLiteral"],
         );
 
-        let refs =
-            analysis.find_all_refs(pos, Some(SearchScope::single_file(bar))).unwrap().unwrap();
+        let refs = analysis
+            .find_all_refs(
+                pos,
+                FindAllRefsConfig {
+                    search_scope: Some(SearchScope::single_file(bar)),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .unwrap();
         check_result(
             refs,
             "quux FN_DEF FileId(1) [18; 34) [25; 29) Other",
@@ -681,6 +1296,82 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_all_refs_excluding_generated() {
+        let code = r#"
+            //- /lib.rs
+            mod foo;
+            mod target {
+                mod gen;
+            }
+
+            pub fn quux<|>() {}
+
+            //- /foo.rs
+            fn f() { super::quux(); }
+
+            //- /target/gen.rs
+            fn f() { crate::quux(); }
+        "#;
+
+        let (analysis, pos) = analysis_and_position(code);
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
+        check_result(
+            refs,
+            "quux FN_DEF FileId(1) [38; 54) [45; 49) Other",
+            &["FileId(2) [16; 20) StructLiteral", "FileId(3) [16; 20) StructLiteral"],
+        );
+
+        let refs = analysis
+            .find_all_refs(pos, FindAllRefsConfig { exclude_generated: true, ..Default::default() })
+            .unwrap()
+            .unwrap();
+        check_result(
+            refs,
+            "quux FN_DEF FileId(1) [38; 54) [45; 49) Other",
+            &["FileId(2) [16; 20) StructLiteral"],
+        );
+    }
+
+    #[test]
+    fn test_find_all_refs_pub_crate_stays_in_crate() {
+        let code = r#"
+            //- /lib.rs
+            fn f() { foo::quux(); }
+
+            //- /foo/lib.rs
+            mod bar;
+            pub(crate) fn quux<|>() {}
+
+            //- /foo/bar.rs
+            fn f() { super::quux(); }
+        "#;
+
+        let (mock, pos) = MockAnalysis::with_files_and_position(code);
+        let lib = mock.id_of("/lib.rs");
+        let analysis = mock.analysis();
+
+        let refs = analysis.find_all_refs(pos, FindAllRefsConfig::default()).unwrap().unwrap();
+        check_result(
+            refs,
+            "quux FN_DEF FileId(2) [9; 32) [23; 27) Other",
+            &["FileId(3) [16; 20) StructLiteral"],
+        );
+
+        let main_crate = analysis.crate_for(lib).unwrap()[0];
+        let refs = analysis
+            .find_all_refs(
+                pos,
+                FindAllRefsConfig {
+                    search_scope: Some(SearchScope::for_crate(main_crate, &analysis.db)),
+                    ..Default::default()
+                },
+            )
+            .unwrap()
+            .unwrap();
+        check_result(refs, "quux FN_DEF FileId(2) [9; 32) [23; 27) Other", &[]);
+    }
+
     #[test]
     fn test_find_all_refs_macro_def() {
         let code = r#"
@@ -752,9 +1443,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_all_refs_decl_name_range() {
+        let code = r#"
+        struct Foo<|> {
+            a: i32,
+        }"#;
+
+        let refs = get_all_refs(code);
+        let decl = refs.declaration();
+        assert_eq!(decl.nav.full_range(), TextRange::from_to(9.into(), 51.into()));
+        assert_eq!(decl.name_range, Some(TextRange::from_to(16.into(), 19.into())));
+    }
+
     fn get_all_refs(text: &str) -> ReferenceSearchResult {
         let (analysis, position) = single_file_with_position(text);
-        analysis.find_all_refs(position, None).unwrap().unwrap()
+        analysis.find_all_refs(position, FindAllRefsConfig::default()).unwrap().unwrap()
     }
 
     fn check_result(res: ReferenceSearchResult, expected_decl: &str, expected_refs: &[&str]) {
@@ -765,7 +1469,21 @@ mod tests {
 
     impl Declaration {
         fn debug_render(&self) -> String {
-            let mut s = format!("{} {:?}", self.nav.debug_render(), self.kind);
+            let nav = &self.nav;
+            let mut s = format!(
+                "{} {:?} {:?} {:?}",
+                nav.name(),
+                nav.kind(),
+                nav.file_id(),
+                nav.full_range()
+            );
+            if let Some(name_range) = self.name_range {
+                s.push_str(&format!(" {:?}", name_range));
+            }
+            if let Some(container_name) = nav.container_name() {
+                s.push_str(&format!(" {}", container_name));
+            }
+            s.push_str(&format!(" {:?}", self.kind));
             if let Some(access) = self.access {
                 s.push_str(&format!(" {:?}", access));
             }
@@ -2,13 +2,16 @@
 
 use hir::{InFile, PathResolution, SourceBinder};
 use ra_prof::profile;
-use ra_syntax::{ast, AstNode};
+use ra_syntax::{
+    ast::{self, AstToken},
+    AstNode, SourceFile, SyntaxToken, TextRange, TextUnit,
+};
 use test_utils::tested_by;
 
 use super::NameDefinition;
 use ra_ide_db::RootDatabase;
 
-pub use ra_ide_db::defs::{classify_name, from_module_def, from_struct_field};
+pub use ra_ide_db::defs::{classify_lifetime, classify_name, from_module_def, from_struct_field};
 
 pub(crate) fn classify_name_ref(
     sb: &mut SourceBinder<RootDatabase>,
@@ -26,6 +29,8 @@ pub(crate) fn classify_name_ref(
         }
     }
 
+    // This also covers positional fields like `foo.0` of tuple structs. The
+    // fields of anonymous tuples have no definition to resolve to.
     if let Some(field_expr) = ast::FieldExpr::cast(parent.clone()) {
         tested_by!(goto_def_for_fields);
         if let Some(field) = analyzer.resolve_field(&field_expr) {
@@ -52,7 +57,80 @@ pub(crate) fn classify_name_ref(
 
     let path = name_ref.value.syntax().ancestors().find_map(ast::Path::cast)?;
     let resolved = analyzer.resolve_path(sb.db, &path)?;
-    let res = match resolved {
+    Some(from_path_resolution(resolved))
+}
+
+/// Resolves the path of an intra-doc link, like `Foo` in `/// See [Foo]`, in
+/// the scope of the item the doc comment belongs to.
+pub(crate) fn classify_doc_link(
+    sb: &mut SourceBinder<RootDatabase>,
+    comment: InFile<&SyntaxToken>,
+    link: &str,
+) -> Option<NameDefinition> {
+    let _p = profile("classify_doc_link");
+
+    if !is_path(link) {
+        return None;
+    }
+    let parse = SourceFile::parse(&format!("use {};", link));
+    let path = parse.tree().syntax().descendants().find_map(ast::Path::cast)?;
+    let owner = comment.value.parent();
+    let analyzer = sb.analyze(comment.with_value(&owner), None);
+    let resolved = analyzer.resolve_path(sb.db, &path)?;
+    Some(from_path_resolution(resolved))
+}
+
+/// Returns the intra-doc links of a doc comment, both of the `[path]` and the
+/// `[text](path)` form, as their path and its range. Backticks around the path
+/// are allowed.
+pub(crate) fn doc_links(comment: &SyntaxToken) -> Vec<(String, TextRange)> {
+    let is_doc = ast::Comment::cast(comment.clone()).map_or(false, |it| it.kind().doc.is_some());
+    if !is_doc {
+        return Vec::new();
+    }
+    let text = comment.text().as_str();
+    let mut res = Vec::new();
+    let mut pos = 0;
+    while let Some(open) = text[pos..].find('[') {
+        let open = pos + open;
+        let close = match text[open..].find(']') {
+            Some(it) => open + it,
+            None => break,
+        };
+        let (mut start, mut end) = (open + 1, close);
+        pos = close + 1;
+        if text[pos..].starts_with('(') {
+            if let Some(paren) = text[pos..].find(')') {
+                start = pos + 1;
+                end = pos + paren;
+                pos = end + 1;
+            }
+        }
+        let link = &text[start..end];
+        let path = link.trim_matches('`');
+        if is_path(path) {
+            let offset = start + (link.len() - link.trim_start_matches('`').len());
+            let range = TextRange::offset_len(
+                comment.text_range().start() + TextUnit::from_usize(offset),
+                TextUnit::of_str(path),
+            );
+            res.push((path.to_string(), range));
+        }
+    }
+    res
+}
+
+fn is_path(text: &str) -> bool {
+    !text.is_empty()
+        && text.split("::").all(|segment| {
+            let mut chars = segment.chars();
+            chars.next().map_or(false, |c| c.is_alphabetic() || c == '_')
+                && chars.all(|c| c.is_alphanumeric() || c == '_')
+        })
+}
+
+fn from_path_resolution(resolved: PathResolution) -> NameDefinition {
+    match resolved {
         PathResolution::Def(def) => from_module_def(def),
         PathResolution::AssocItem(item) => {
             let def = match item {
@@ -64,8 +142,8 @@ pub(crate) fn classify_name_ref(
         }
         PathResolution::Local(local) => NameDefinition::Local(local),
         PathResolution::TypeParam(par) => NameDefinition::TypeParam(par),
+        PathResolution::ConstParam(par) => NameDefinition::ConstParam(par),
         PathResolution::Macro(def) => NameDefinition::Macro(def),
         PathResolution::SelfType(impl_block) => NameDefinition::SelfType(impl_block),
-    };
-    Some(res)
+    }
 }
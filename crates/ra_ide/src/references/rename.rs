@@ -1,6 +1,8 @@
 //! FIXME: write short doc here
 
-use hir::ModuleSource;
+use std::iter;
+
+use hir::{Adt, InFile, ModuleDef, ModuleSource, ScopeDef, SourceBinder, StructKind};
 use ra_db::{RelativePath, RelativePathBuf, SourceDatabase, SourceDatabaseExt};
 use ra_ide_db::RootDatabase;
 use ra_syntax::{
@@ -9,22 +11,40 @@ use ra_syntax::{
 use ra_text_edit::TextEdit;
 
 use crate::{
-    FileId, FilePosition, FileSystemEdit, RangeInfo, SourceChange, SourceFileEdit, TextRange,
+    display::ToNav, FileId, FilePosition, FileRange, FileSystemEdit, NavigationTarget, RangeInfo,
+    SourceChange, SourceFileEdit, TextRange,
 };
 
-use super::find_all_refs;
+use super::{find_all_refs, find_name, FindAllRefsConfig, NameDefinition};
+
+/// An existing definition which is already called like the new name, so that
+/// renaming would change what some name refers to.
+#[derive(Debug)]
+pub struct RenameConflict {
+    /// The reference (or, for fields, the declaration) that would clash with
+    /// `existing` after the rename.
+    pub file_range: FileRange,
+    /// The existing definition, if it is something we can navigate to.
+    pub existing: Option<NavigationTarget>,
+}
 
 pub(crate) fn rename(
     db: &RootDatabase,
     position: FilePosition,
     new_name: &str,
 ) -> Option<RangeInfo<SourceChange>> {
+    let parse = db.parse(position.file_id);
+    let at_lifetime = parse
+        .tree()
+        .syntax()
+        .token_at_offset(position.offset)
+        .any(|it| it.kind() == SyntaxKind::LIFETIME);
     match lex_single_valid_syntax_kind(new_name)? {
-        SyntaxKind::IDENT | SyntaxKind::UNDERSCORE => (),
+        SyntaxKind::IDENT | SyntaxKind::UNDERSCORE if !at_lifetime => (),
+        SyntaxKind::LIFETIME if at_lifetime && new_name != "'static" && new_name != "'_" => (),
         _ => return None,
     }
 
-    let parse = db.parse(position.file_id);
     if let Some((ast_name, ast_module)) =
         find_name_and_module_at_offset(parse.tree().syntax(), position)
     {
@@ -36,6 +56,133 @@ pub(crate) fn rename(
     }
 }
 
+/// Checks whether renaming the definition at `position` to `new_name` would
+/// clash with another definition visible at one of its references.
+pub(crate) fn rename_conflicts(
+    db: &RootDatabase,
+    position: FilePosition,
+    new_name: &str,
+) -> Vec<RenameConflict> {
+    let new_name = new_name.trim_start_matches("r#");
+    let parse = db.parse(position.file_id);
+    let syntax = parse.tree().syntax().clone();
+    let opt_name = find_node_at_offset::<ast::Name>(&syntax, position.offset);
+    let (name, def) = match find_name(db, &syntax, position, opt_name) {
+        Some(it) => it.info,
+        None => return Vec::new(),
+    };
+    if name == new_name {
+        return Vec::new();
+    }
+    let namespaces = match &def {
+        NameDefinition::Macro(_) => &[Namespace::Macros][..],
+        NameDefinition::StructField(_) => &[],
+        NameDefinition::ModuleDef(it) => module_def_namespaces(db, *it),
+        NameDefinition::SelfType(_) | NameDefinition::TypeParam(_) => &[Namespace::Types],
+        NameDefinition::Local(_) | NameDefinition::ConstParam(_) => &[Namespace::Values],
+        // Lifetimes can't clash with any of the names in scope
+        NameDefinition::Lifetime(_) => return Vec::new(),
+    };
+    let refs = match find_all_refs(db, position, FindAllRefsConfig::default()) {
+        Some(it) => it.info,
+        None => return Vec::new(),
+    };
+
+    // Fields aren't in scope anywhere, they can only clash with their siblings
+    if let NameDefinition::StructField(field) = def {
+        let decl = &refs.declaration().nav;
+        let file_range = FileRange { file_id: decl.file_id(), range: decl.range() };
+        return field
+            .parent_def(db)
+            .fields(db)
+            .into_iter()
+            .filter(|it| *it != field && it.name(db).to_string() == new_name)
+            .map(|it| RenameConflict { file_range, existing: Some(it.to_nav(db)) })
+            .collect();
+    }
+
+    let mut sb = SourceBinder::new(db);
+    let mut conflicts = Vec::new();
+    for reference in refs.references() {
+        let FileRange { file_id, range } = reference.file_range;
+        let parse = db.parse(file_id);
+        let name_ref =
+            match find_node_at_offset::<ast::NameRef>(parse.tree().syntax(), range.start()) {
+                Some(it) => it,
+                None => continue,
+            };
+        let analyzer =
+            sb.analyze(InFile::new(file_id.into(), name_ref.syntax()), Some(range.start()));
+        analyzer.process_all_names(db, &mut |name, scope_def| {
+            let same_namespace = match scope_def_namespaces(db, scope_def) {
+                Some(it) => it.iter().any(|ns| namespaces.contains(ns)),
+                None => true,
+            };
+            if same_namespace && name.to_string() == new_name {
+                conflicts.push(RenameConflict {
+                    file_range: reference.file_range,
+                    existing: scope_def_to_nav(db, scope_def),
+                });
+            }
+        });
+    }
+    conflicts
+}
+
+/// Names only clash within a namespace, e.g. a local `foo` can coexist with a
+/// module `foo`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Namespace {
+    Types,
+    Values,
+    Macros,
+}
+
+fn module_def_namespaces(db: &RootDatabase, def: ModuleDef) -> &'static [Namespace] {
+    // Tuple and unit structs also define a constructor in the value namespace
+    let struct_kind_namespaces = |kind| match kind {
+        StructKind::Record => &[Namespace::Types][..],
+        StructKind::Tuple | StructKind::Unit => &[Namespace::Types, Namespace::Values],
+    };
+    match def {
+        ModuleDef::Module(_)
+        | ModuleDef::Adt(Adt::Union(_))
+        | ModuleDef::Adt(Adt::Enum(_))
+        | ModuleDef::Trait(_)
+        | ModuleDef::TypeAlias(_)
+        | ModuleDef::BuiltinType(_) => &[Namespace::Types],
+        ModuleDef::Adt(Adt::Struct(it)) => struct_kind_namespaces(it.kind(db)),
+        ModuleDef::EnumVariant(it) => struct_kind_namespaces(it.kind(db)),
+        ModuleDef::Function(_) | ModuleDef::Const(_) | ModuleDef::Static(_) => &[Namespace::Values],
+    }
+}
+
+/// Returns `None` if we don't know what `def` is, so it might clash with anything.
+fn scope_def_namespaces(db: &RootDatabase, def: ScopeDef) -> Option<&'static [Namespace]> {
+    let res = match def {
+        ScopeDef::ModuleDef(it) => module_def_namespaces(db, it),
+        ScopeDef::MacroDef(_) => &[Namespace::Macros],
+        ScopeDef::GenericParam(_) | ScopeDef::ImplSelfType(_) | ScopeDef::AdtSelfType(_) => {
+            &[Namespace::Types]
+        }
+        ScopeDef::Local(_) => &[Namespace::Values],
+        ScopeDef::Unknown => return None,
+    };
+    Some(res)
+}
+
+fn scope_def_to_nav(db: &RootDatabase, def: ScopeDef) -> Option<NavigationTarget> {
+    match def {
+        ScopeDef::ModuleDef(it) => NavigationTarget::from_def(db, it),
+        ScopeDef::MacroDef(it) => Some(it.to_nav(db)),
+        ScopeDef::Local(it) => Some(it.to_nav(db)),
+        ScopeDef::GenericParam(_)
+        | ScopeDef::ImplSelfType(_)
+        | ScopeDef::AdtSelfType(_)
+        | ScopeDef::Unknown => None,
+    }
+}
+
 fn find_name_and_module_at_offset(
     syntax: &SyntaxNode,
     position: FilePosition,
@@ -106,16 +253,29 @@ fn rename_reference(
     position: FilePosition,
     new_name: &str,
 ) -> Option<RangeInfo<SourceChange>> {
-    let RangeInfo { range, info: refs } = find_all_refs(db, position, None)?;
+    let RangeInfo { range, info: refs } =
+        find_all_refs(db, position, FindAllRefsConfig::default())?;
 
-    let edit = refs
+    // Renaming `Baz` in `use foo::Bar as Baz;` only renames the alias and its
+    // uses, the imported item keeps its name
+    let parse = db.parse(position.file_id);
+    let alias = find_node_at_offset::<ast::Name>(parse.tree().syntax(), position.offset)
+        .filter(|name| name.syntax().parent().and_then(ast::Alias::cast).is_some());
+    let file_ranges: Vec<FileRange> = match alias {
+        Some(alias) => {
+            let alias_range =
+                FileRange { file_id: position.file_id, range: alias.syntax().text_range() };
+            iter::once(alias_range)
+                .chain(refs.references().iter().map(|it| it.file_range))
+                .collect()
+        }
+        None => refs.into_iter().map(|it| it.file_range).collect(),
+    };
+
+    let edit = file_ranges
         .into_iter()
-        .map(|reference| {
-            source_edit_from_file_id_range(
-                reference.file_range.file_id,
-                reference.file_range.range,
-                new_name,
-            )
+        .map(|file_range| {
+            source_edit_from_file_id_range(file_range.file_id, file_range.range, new_name)
         })
         .collect::<Vec<_>>();
 
@@ -179,6 +339,51 @@ mod tests {
         assert!(source_change.is_none());
     }
 
+    #[test]
+    fn test_rename_conflicts_with_local_in_inner_scope() {
+        let (analysis, position) = single_file_with_position(
+            r#"
+    fn main() {
+        let i<|> = 1;
+        {
+            let j = 2;
+            i + j;
+        }
+    }"#,
+        );
+        assert!(analysis.rename_conflicts(position, "k").unwrap().is_empty());
+
+        let conflicts = analysis.rename_conflicts(position, "j").unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].file_range.file_id, FileId(1));
+        assert_eq!(format!("{:?}", conflicts[0].file_range.range), "[81; 82)");
+        conflicts[0].existing.as_ref().unwrap().assert_match("j BIND_PAT FileId(1) [62; 63)");
+    }
+
+    #[test]
+    fn test_rename_conflicts_only_within_namespace() {
+        let (analysis, position) = single_file_with_position(
+            r#"
+    mod m {}
+    struct Record { x: i32 }
+    struct Unit;
+    fn main() {
+        let i<|> = 1;
+        i;
+    }"#,
+        );
+        assert!(analysis.rename_conflicts(position, "m").unwrap().is_empty());
+        assert!(analysis.rename_conflicts(position, "Record").unwrap().is_empty());
+
+        let conflicts = analysis.rename_conflicts(position, "Unit").unwrap();
+        assert_eq!(conflicts.len(), 1);
+        conflicts[0]
+            .existing
+            .as_ref()
+            .unwrap()
+            .assert_match("Unit STRUCT_DEF FileId(1) [47; 59) [54; 58)");
+    }
+
     #[test]
     fn test_rename_for_local() {
         test_rename(
@@ -210,6 +415,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rename_use_alias() {
+        test_rename(
+            r#"
+    mod foo {
+        pub struct Bar;
+    }
+    use foo::Bar as Baz<|>;
+    fn f(baz: Baz) -> Baz {
+        baz
+    }"#,
+            "Qux",
+            r#"
+    mod foo {
+        pub struct Bar;
+    }
+    use foo::Bar as Qux;
+    fn f(baz: Qux) -> Qux {
+        baz
+    }"#,
+        );
+    }
+
     #[test]
     fn test_rename_for_macro_args() {
         test_rename(
@@ -383,6 +611,58 @@ mod tests {
                );
     }
 
+    #[test]
+    fn test_rename_lifetime() {
+        test_rename(
+            r#"
+    struct Foo<'a> { s: &'a str }
+    fn foo<'a>(x: &'<|>a str, y: Foo<'a>) -> &'a str where 'a: 'a {
+        let z: &'a str = x;
+        z
+    }
+    fn bar<'a>(x: &'a str) {}"#,
+            "'b",
+            r#"
+    struct Foo<'a> { s: &'a str }
+    fn foo<'b>(x: &'b str, y: Foo<'b>) -> &'b str where 'b: 'b {
+        let z: &'b str = x;
+        z
+    }
+    fn bar<'a>(x: &'a str) {}"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_impl_lifetime() {
+        test_rename(
+            r#"
+    struct Foo<'a>(&'a str);
+    impl<'a<|>> Foo<'a> {
+        fn get<'b>(&'b self) -> &'a str { self.0 }
+    }"#,
+            "'c",
+            r#"
+    struct Foo<'a>(&'a str);
+    impl<'c> Foo<'c> {
+        fn get<'b>(&'b self) -> &'c str { self.0 }
+    }"#,
+        );
+    }
+
+    #[test]
+    fn test_rename_lifetime_invalid() {
+        let (analysis, position) = single_file_with_position("fn foo<'a>(x: &'<|>a str) {}");
+        assert!(analysis.rename(position, "b").unwrap().is_none());
+        assert!(analysis.rename(position, "'static").unwrap().is_none());
+        assert!(analysis.rename(position, "'_").unwrap().is_none());
+
+        let (analysis, position) = single_file_with_position("fn foo(x: &'<|>static str) {}");
+        assert!(analysis.rename(position, "'a").unwrap().is_none());
+
+        let (analysis, position) = single_file_with_position("fn foo(x: &'<|>_ str) {}");
+        assert!(analysis.rename(position, "'a").unwrap().is_none());
+    }
+
     fn test_rename(text: &str, new_name: &str, expected: &str) {
         let (analysis, position) = single_file_with_position(text);
         let source_change = analysis.rename(position, new_name).unwrap();
@@ -4,8 +4,8 @@
 //! e.g. for things like local variables.
 use std::mem;
 
-use hir::{DefWithBody, HasSource, ModuleSource};
-use ra_db::{FileId, SourceDatabaseExt};
+use hir::{Crate, DefWithBody, HasSource, Module, ModuleSource};
+use ra_db::{CrateId, FileId, SourceDatabaseExt};
 use ra_prof::profile;
 use ra_syntax::{AstNode, TextRange};
 use rustc_hash::FxHashMap;
@@ -23,8 +23,27 @@ impl SearchScope {
         SearchScope { entries: FxHashMap::default() }
     }
 
-    pub(crate) fn for_def(def: &NameDefinition, db: &RootDatabase) -> SearchScope {
+    /// Unless `include_generated` is set, files generated into a `target`
+    /// directory (like the `OUT_DIR` of build scripts) are left out.
+    pub(crate) fn for_def(
+        def: &NameDefinition,
+        db: &RootDatabase,
+        include_generated: bool,
+    ) -> SearchScope {
+        let mut res = SearchScope::for_def_impl(def, db);
+        if !include_generated {
+            res.entries.retain(|&file_id, _| !is_generated(db, file_id));
+        }
+        res
+    }
+
+    fn for_def_impl(def: &NameDefinition, db: &RootDatabase) -> SearchScope {
         let _p = profile("search_scope");
+        if let NameDefinition::Lifetime(it) = def {
+            let mut res = FxHashMap::default();
+            res.insert(it.decl.file_id.original_file(db), Some(it.owner_range));
+            return SearchScope::new(res);
+        }
         let module = match def.module(db) {
             Some(it) => it,
             None => return SearchScope::empty(),
@@ -68,16 +87,16 @@ impl SearchScope {
             }
         }
 
+        // FIXME: narrow "pub(in path)" down to the module it names
+        if vis.as_str() == "pub(crate)" || vis.starts_with("pub(in ") {
+            return SearchScope::for_crate(module.krate().into(), db);
+        }
+
         if vis.as_str() != "" {
             let source_root_id = db.file_source_root(file_id);
             let source_root = db.source_root(source_root_id);
             let mut res = source_root.walk().map(|id| (id, None)).collect::<FxHashMap<_, _>>();
 
-            // FIXME: add "pub(in path)"
-
-            if vis.as_str() == "pub(crate)" {
-                return SearchScope::new(res);
-            }
             if vis.as_str() == "pub" {
                 let krate = module.krate();
                 for rev_dep in krate.reverse_dependencies(db) {
@@ -105,6 +124,21 @@ impl SearchScope {
     pub fn single_file(file: FileId) -> SearchScope {
         SearchScope::new(std::iter::once((file, None)).collect())
     }
+    /// The files of all the modules of `crate_id`. Unlike the source root of
+    /// its root file, this leaves out other crates sharing the same directory.
+    pub fn for_crate(crate_id: CrateId, db: &RootDatabase) -> SearchScope {
+        let krate: Crate = crate_id.into();
+        let mut res = FxHashMap::default();
+        let mut modules: Vec<Module> = krate.root_module(db).into_iter().collect();
+        while let Some(module) = modules.pop() {
+            let src = module.definition_source(db);
+            if let ModuleSource::SourceFile(_) = src.value {
+                res.insert(src.file_id.original_file(db), None);
+            }
+            modules.extend(module.children(db));
+        }
+        SearchScope::new(res)
+    }
     pub(crate) fn intersection(&self, other: &SearchScope) -> SearchScope {
         let (mut small, mut large) = (&self.entries, &other.entries);
         if small.len() > large.len() {
@@ -143,3 +177,10 @@ impl IntoIterator for SearchScope {
         self.entries.into_iter()
     }
 }
+
+/// Build scripts write their output into `OUT_DIR`, which lives in the
+/// `target` directory of the package. Like the project model's package root
+/// filters, we classify everything under `target` as generated.
+fn is_generated(db: &RootDatabase, file_id: FileId) -> bool {
+    db.file_relative_path(file_id).as_str().starts_with("target/")
+}
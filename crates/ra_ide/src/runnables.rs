@@ -1,13 +1,17 @@
 //! FIXME: write short doc here
 
-use hir::{InFile, SourceBinder};
+use hir::{HasSource, InFile, SourceBinder};
 use itertools::Itertools;
-use ra_db::SourceDatabase;
+use ra_db::{CrateId, SourceDatabase};
 use ra_ide_db::RootDatabase;
 use ra_syntax::{
-    ast::{self, AstNode, AttrsOwner, ModuleItemOwner, NameOwner},
-    match_ast, SyntaxNode, TextRange,
+    ast::{
+        self, ArgListOwner, AstNode, AstToken, AttrsOwner, DocCommentsOwner, ModuleItemOwner,
+        NameOwner,
+    },
+    match_ast, NodeOrToken, SyntaxKind, SyntaxNode, SyntaxToken, TextRange, T,
 };
+use rustc_hash::FxHashSet;
 
 use crate::FileId;
 use std::fmt::Display;
@@ -35,29 +39,99 @@ impl Display for TestId {
 
 #[derive(Debug)]
 pub enum RunnableKind {
-    Test { test_id: TestId },
-    TestMod { path: String },
-    Bench { test_id: TestId },
+    Test {
+        test_id: TestId,
+    },
+    TestMod {
+        path: String,
+    },
+    Bench {
+        test_id: TestId,
+    },
+    /// The code blocks in the doc comment of the item at `test_id`.
+    DocTest {
+        test_id: TestId,
+    },
     Bin,
 }
 
 pub(crate) fn runnables(db: &RootDatabase, file_id: FileId) -> Vec<Runnable> {
     let parse = db.parse(file_id);
     let mut sb = SourceBinder::new(db);
-    parse.tree().syntax().descendants().filter_map(|i| runnable(db, &mut sb, file_id, i)).collect()
+    let criterion_benches = criterion_benches(parse.tree().syntax());
+    parse
+        .tree()
+        .syntax()
+        .descendants()
+        .flat_map(|i| runnable(db, &mut sb, file_id, &criterion_benches, i))
+        .collect()
+}
+
+/// Returns a runnable for all tests of the crate, spanning its root file, if
+/// the crate has any tests. `TestMod` with an empty path matches every test.
+pub(crate) fn crate_runnable(db: &RootDatabase, crate_id: CrateId) -> Option<Runnable> {
+    let krate = hir::Crate::from(crate_id);
+    let mut modules: Vec<_> = krate.root_module(db).into_iter().collect();
+    let mut has_tests = false;
+    while let Some(module) = modules.pop() {
+        has_tests = module.declarations(db).into_iter().any(|def| match def {
+            hir::ModuleDef::Function(it) => has_test_related_attribute(&it.source(db).value),
+            _ => false,
+        });
+        if has_tests {
+            break;
+        }
+        modules.extend(module.children(db));
+    }
+    if !has_tests {
+        return None;
+    }
+    let range = db.parse(krate.root_file(db)).tree().syntax().text_range();
+    Some(Runnable { range, kind: RunnableKind::TestMod { path: String::new() } })
 }
 
 fn runnable(
     db: &RootDatabase,
     source_binder: &mut SourceBinder<RootDatabase>,
     file_id: FileId,
+    criterion_benches: &FxHashSet<String>,
     item: SyntaxNode,
-) -> Option<Runnable> {
+) -> Vec<Runnable> {
     match_ast! {
         match item {
-            ast::FnDef(it) => { runnable_fn(db, source_binder, file_id, it) },
-            ast::Module(it) => { runnable_mod(db, source_binder, file_id, it) },
-            _ => { None },
+            ast::FnDef(it) => {
+                let docs = outer_doc_comment_text(&it);
+                let doc_test = runnable_doc_test(db, source_binder, file_id, &it, docs);
+                let mut res =
+                    runnable_fn(db, source_binder, file_id, criterion_benches, it);
+                res.extend(doc_test);
+                res
+            },
+            ast::StructDef(it) => {
+                runnable_doc_test(db, source_binder, file_id, &it, outer_doc_comment_text(&it))
+                    .into_iter()
+                    .collect()
+            },
+            ast::ImplBlock(it) => {
+                runnable_doc_test(db, source_binder, file_id, &it, impl_doc_comment_text(&it))
+                    .into_iter()
+                    .collect()
+            },
+            ast::Module(it) => {
+                let docs = it.item_list().and_then(|items| inner_doc_comment_text(items.syntax()));
+                let doc_test = runnable_module_doc_test(db, source_binder, file_id, &it, docs);
+                let mut res: Vec<_> =
+                    runnable_mod(db, source_binder, file_id, it).into_iter().collect();
+                res.extend(doc_test);
+                res
+            },
+            ast::SourceFile(it) => {
+                let docs = inner_doc_comment_text(it.syntax());
+                runnable_module_doc_test(db, source_binder, file_id, &it, docs)
+                    .into_iter()
+                    .collect()
+            },
+            _ => { Vec::new() },
         }
     }
 }
@@ -66,17 +140,33 @@ fn runnable_fn(
     db: &RootDatabase,
     source_binder: &mut SourceBinder<RootDatabase>,
     file_id: FileId,
+    criterion_benches: &FxHashSet<String>,
     fn_def: ast::FnDef,
-) -> Option<Runnable> {
-    let name_string = fn_def.name()?.text().to_string();
+) -> Vec<Runnable> {
+    let name_string = match fn_def.name() {
+        Some(name) => name.text().to_string(),
+        None => return Vec::new(),
+    };
+    let is_criterion_bench =
+        !fn_def.has_atom_attr("bench") && criterion_benches.contains(&name_string);
+    let is_bench = fn_def.has_atom_attr("bench") || is_criterion_bench;
 
+    let module = source_binder
+        .to_def(InFile::new(file_id.into(), fn_def.clone()))
+        .map(|def: hir::Function| def.module(db));
+
+    // Only a free `main` in the crate root is an entry point. The crate graph
+    // doesn't know the kind of cargo target a crate comes from, so that's all
+    // we can check.
     let kind = if name_string == "main" {
-        RunnableKind::Bin
+        let is_top_level =
+            fn_def.syntax().parent().map(|it| it.kind()) == Some(SyntaxKind::SOURCE_FILE);
+        match module {
+            Some(module) if is_top_level && module.parent(db).is_none() => RunnableKind::Bin,
+            _ => return Vec::new(),
+        }
     } else {
-        let test_id = if let Some(module) = source_binder
-            .to_def(InFile::new(file_id.into(), fn_def.clone()))
-            .map(|def| def.module(db))
-        {
+        let test_id = if let Some(module) = module {
             let path = module
                 .path_to_root(db)
                 .into_iter()
@@ -91,14 +181,318 @@ fn runnable_fn(
         };
 
         if has_test_related_attribute(&fn_def) {
-            RunnableKind::Test { test_id }
-        } else if fn_def.has_atom_attr("bench") {
+            let cases = test_case_runnables(&fn_def, &test_id);
+            let whole_fn = Runnable {
+                range: fn_def.syntax().text_range(),
+                kind: RunnableKind::Test { test_id },
+            };
+            return std::iter::once(whole_fn).chain(cases).collect();
+        } else if is_criterion_bench {
+            RunnableKind::Bench { test_id: TestId::Name(criterion_bench_filter(&fn_def)) }
+        } else if is_bench {
             RunnableKind::Bench { test_id }
         } else {
-            return None;
+            return Vec::new();
         }
     };
-    Some(Runnable { range: fn_def.syntax().text_range(), kind })
+    vec![Runnable { range: fn_def.syntax().text_range(), kind }]
+}
+
+/// Returns a runnable for the doctests of `item` if its docs contain a code
+/// block that `cargo test --doc` would compile. Doctests are named after the
+/// item path, with methods qualified by the self type of their impl.
+fn runnable_doc_test(
+    db: &RootDatabase,
+    source_binder: &mut SourceBinder<RootDatabase>,
+    file_id: FileId,
+    item: &impl AstNode,
+    docs: Option<String>,
+) -> Option<Runnable> {
+    if !has_doc_test(&docs?) {
+        return None;
+    }
+    let item = item.syntax();
+    let impl_self_ty = |impl_block: ast::ImplBlock| {
+        impl_block.target_type().map(|it| it.syntax().text().to_string())
+    };
+    let name = match_ast! {
+        match item {
+            ast::ImplBlock(it) => { impl_self_ty(it)? },
+            _ => {
+                let name = item.children().find_map(ast::Name::cast)?.text().to_string();
+                match item.ancestors().skip(1).find_map(ast::ImplBlock::cast) {
+                    Some(impl_block) => format!("{}::{}", impl_self_ty(impl_block)?, name),
+                    None => name,
+                }
+            },
+        }
+    };
+
+    let module = match item.ancestors().skip(1).find_map(ast::Module::cast) {
+        Some(module) => source_binder.to_def(InFile::new(file_id.into(), module)),
+        None => source_binder.to_module_def(file_id),
+    };
+    let test_id = match module {
+        Some(module) => TestId::Path(
+            module
+                .path_to_root(db)
+                .into_iter()
+                .rev()
+                .filter_map(|it| it.name(db))
+                .map(|name| name.to_string())
+                .chain(std::iter::once(name))
+                .join("::"),
+        ),
+        None => TestId::Name(name),
+    };
+    Some(Runnable { range: item.text_range(), kind: RunnableKind::DocTest { test_id } })
+}
+
+/// Returns a runnable for the doctests in the `//!` docs of `module`, which is
+/// either an inline module or a whole file. Rustdoc names these doctests after
+/// the module path, so that's what the filter matches.
+fn runnable_module_doc_test(
+    db: &RootDatabase,
+    source_binder: &mut SourceBinder<RootDatabase>,
+    file_id: FileId,
+    module: &impl AstNode,
+    docs: Option<String>,
+) -> Option<Runnable> {
+    if !has_doc_test(&docs?) {
+        return None;
+    }
+    let def = match ast::Module::cast(module.syntax().clone()) {
+        Some(it) => source_binder.to_def(InFile::new(file_id.into(), it))?,
+        None => source_binder.to_module_def(file_id)?,
+    };
+    let path = def.path_to_root(db).into_iter().rev().filter_map(|it| it.name(db)).join("::");
+    Some(Runnable {
+        range: module.syntax().text_range(),
+        kind: RunnableKind::DocTest { test_id: TestId::Path(path) },
+    })
+}
+
+/// The `///` docs of `item`. The parser attaches the `//!` docs of a module to
+/// the item following them, so these are skipped.
+fn outer_doc_comment_text(item: &impl DocCommentsOwner) -> Option<String> {
+    let lines: Vec<String> = item
+        .doc_comments()
+        .filter(|comment| comment.kind().doc == Some(ast::CommentPlacement::Outer))
+        .map(|comment| comment.text()[comment.prefix().len()..].to_string())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    Some(lines.join("\n"))
+}
+
+/// Collects the `//!` comments of a file or of the item list of an inline
+/// module. The parser attaches comments to the item following them, so the
+/// leading trivia of the items are searched as well.
+fn inner_doc_comment_text(items: &SyntaxNode) -> Option<String> {
+    let lines: Vec<String> = items
+        .children_with_tokens()
+        .flat_map(|element| match element {
+            NodeOrToken::Token(token) => vec![token],
+            NodeOrToken::Node(node) => node
+                .children_with_tokens()
+                .take_while(|it| it.kind().is_trivia())
+                .filter_map(|it| it.into_token())
+                .collect(),
+        })
+        .filter_map(ast::Comment::cast)
+        .filter(|comment| comment.kind().doc == Some(ast::CommentPlacement::Inner))
+        .map(|comment| comment.text()[comment.prefix().len()..].to_string())
+        .collect();
+    if lines.is_empty() {
+        return None;
+    }
+    Some(lines.join("\n"))
+}
+
+/// Impl blocks don't own the comments preceding them in the syntax tree, so
+/// their doc comments are collected from the preceding siblings instead.
+fn impl_doc_comment_text(impl_block: &ast::ImplBlock) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut prev = impl_block.syntax().prev_sibling_or_token();
+    while let Some(element) = prev {
+        match element.kind() {
+            SyntaxKind::WHITESPACE
+                if element.as_token().map_or(false, |it| !it.text().contains("\n\n")) => {}
+            SyntaxKind::COMMENT => {
+                let comment = element.as_token().cloned().and_then(ast::Comment::cast)?;
+                if comment.kind().doc == Some(ast::CommentPlacement::Outer) {
+                    lines.push(comment.text()[comment.prefix().len()..].to_string());
+                }
+            }
+            _ => break,
+        }
+        prev = element.prev_sibling_or_token();
+    }
+    if lines.is_empty() {
+        return None;
+    }
+    lines.reverse();
+    Some(lines.join("\n"))
+}
+
+/// Whether `docs` contain a fenced code block that rustdoc tests. Blocks
+/// tagged `ignore` or with a language other than Rust (like `text`) are
+/// skipped, while `no_run` blocks are still compiled and thus count.
+fn has_doc_test(docs: &str) -> bool {
+    const FENCE: &str = "```";
+
+    let mut in_code_block = false;
+    for line in docs.lines().map(str::trim_start) {
+        if !line.starts_with(FENCE) {
+            continue;
+        }
+        if in_code_block {
+            in_code_block = false;
+            continue;
+        }
+        let is_doc_test = line[FENCE.len()..]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|tag| !tag.is_empty())
+            .all(|tag| match tag {
+                "rust" | "no_run" | "should_panic" | "compile_fail" | "allow_fail"
+                | "test_harness" => true,
+                tag => tag.starts_with("edition"),
+            });
+        if is_doc_test {
+            return true;
+        }
+        in_code_block = true;
+    }
+    false
+}
+
+/// Finds the names of the benchmark functions registered with criterion's
+/// `criterion_group!` macro in `file`.
+fn criterion_benches(file: &SyntaxNode) -> FxHashSet<String> {
+    file.descendants()
+        .filter_map(ast::MacroCall::cast)
+        .filter(|call| {
+            call.path()
+                .and_then(|path| path.segment())
+                .and_then(|segment| segment.name_ref())
+                .map_or(false, |name_ref| name_ref.text() == "criterion_group")
+        })
+        .filter_map(|call| call.token_tree())
+        .flat_map(|tt| criterion_group_targets(&tt))
+        .collect()
+}
+
+/// Criterion filters benchmarks by the ids passed to `bench_function`, not by
+/// the name of the function registering them. Returns a filter matching the ids
+/// used in `fn_def`, or an empty filter, which matches the whole bench target,
+/// if any of them isn't a string literal.
+fn criterion_bench_filter(fn_def: &ast::FnDef) -> String {
+    let ids: Option<Vec<String>> = fn_def
+        .syntax()
+        .descendants()
+        .filter_map(ast::MethodCallExpr::cast)
+        .filter(|call| call.name_ref().map_or(false, |it| it.text() == "bench_function"))
+        .map(|call| match call.arg_list()?.args().next()? {
+            ast::Expr::Literal(lit) => ast::String::cast(lit.token())?.value(),
+            _ => None,
+        })
+        .collect();
+    ids.map(|ids| ids.iter().map(|id| escape_regex(id)).join("|")).unwrap_or_default()
+}
+
+/// Criterion matches the filter as a regex against the benchmark ids.
+fn escape_regex(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$#&-~".contains(c) {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+    res
+}
+
+/// `criterion_group!` is either called as `criterion_group!(name, targets...)`
+/// or as `criterion_group! { name = ...; config = ...; targets = targets... }`.
+fn criterion_group_targets(tt: &ast::TokenTree) -> Vec<String> {
+    let tokens: Vec<SyntaxToken> = tt
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .filter(|it| !it.kind().is_trivia())
+        .collect();
+    let targets_start =
+        tokens.windows(2).position(|it| it[0].text() == "targets" && it[1].kind() == T![=]);
+    let segments: Vec<&[SyntaxToken]> = match targets_start {
+        Some(idx) => {
+            let targets = &tokens[idx + 2..];
+            let end = targets.iter().position(|it| it.kind() == T![;]).unwrap_or(targets.len());
+            targets[..end].split(|it| it.kind() == T![,]).collect()
+        }
+        // The first argument is the name of the group
+        None => tokens.split(|it| it.kind() == T![,]).skip(1).collect(),
+    };
+    segments
+        .into_iter()
+        .filter_map(|segment| segment.iter().rev().find(|it| it.kind() == SyntaxKind::IDENT))
+        .map(|it| it.text().to_string())
+        .collect()
+}
+
+/// The `test_case` crate expands every `#[test_case(...)]` attribute into a
+/// separate test function, nested in a module named after the annotated
+/// function. We emit a runnable per case, anchored at its attribute.
+fn test_case_runnables(fn_def: &ast::FnDef, fn_test_id: &TestId) -> Vec<Runnable> {
+    fn_def
+        .attrs()
+        .filter_map(|attr| {
+            let (name, args) = attr.as_simple_call()?;
+            if name != "test_case" {
+                return None;
+            }
+            let case_name = test_case_name(&args)?;
+            let test_id = match fn_test_id {
+                TestId::Name(name) => TestId::Name(format!("{}::{}", name, case_name)),
+                TestId::Path(path) => TestId::Path(format!("{}::{}", path, case_name)),
+            };
+            Some(Runnable {
+                range: attr.syntax().text_range(),
+                kind: RunnableKind::Test { test_id },
+            })
+        })
+        .collect()
+}
+
+/// Mirrors the naming scheme of `test_case`: an explicit `; "description"`
+/// wins, otherwise the arguments themselves are used. Either way the name is
+/// lowercased, non-alphanumeric runs become `_`, and a leading digit gets a
+/// `_` prefix.
+fn test_case_name(args: &ast::TokenTree) -> Option<String> {
+    let text = args.syntax().text().to_string();
+    let text = text.trim_start_matches('(').trim_end_matches(')');
+    let raw = match text.rfind(';') {
+        Some(idx) => text[idx + 1..].trim().trim_matches('"').to_string(),
+        None => text.to_string(),
+    };
+
+    let mut res = String::new();
+    for c in raw.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            res.push(c);
+        } else if !res.ends_with('_') {
+            res.push('_');
+        }
+    }
+    let res = res.trim_end_matches('_');
+    if res.is_empty() {
+        return None;
+    }
+    if res.starts_with(|c: char| c.is_ascii_digit()) {
+        Some(format!("_{}", res))
+    } else {
+        Some(res.to_string())
+    }
 }
 
 /// This is a method with a heuristics to support test methods annotated with custom test annotations, such as
@@ -142,6 +536,7 @@ fn runnable_mod(
 #[cfg(test)]
 mod tests {
     use insta::assert_debug_snapshot;
+    use ra_syntax::{TextRange, TextUnit};
 
     use crate::mock_analysis::analysis_and_position;
 
@@ -190,6 +585,280 @@ mod tests {
                 );
     }
 
+    #[test]
+    fn test_runnables_no_bin_for_nested_main() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /main.rs
+        <|> //empty
+        mod tests {
+            fn main() {}
+        }
+        struct S;
+        impl S {
+            fn main() {}
+        }
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert!(runnables.is_empty(), "{:?}", runnables);
+    }
+
+    #[test]
+    fn test_runnables_test_case() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|> //empty
+        #[test_case(1, 2 ; "small numbers")]
+        #[test_case(-1, 7)]
+        fn test_add(a: i32, b: i32) {}
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert_debug_snapshot!(&runnables,
+        @r###"
+        [
+            Runnable {
+                range: [1; 96),
+                kind: Test {
+                    test_id: Path(
+                        "test_add",
+                    ),
+                },
+            },
+            Runnable {
+                range: [9; 45),
+                kind: Test {
+                    test_id: Path(
+                        "test_add::small_numbers",
+                    ),
+                },
+            },
+            Runnable {
+                range: [46; 65),
+                kind: Test {
+                    test_id: Path(
+                        "test_add::_1_7",
+                    ),
+                },
+            },
+        ]
+        "###
+                );
+    }
+
+    #[test]
+    fn test_crate_runnable() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|>mod foo;
+
+        //- /foo.rs
+        mod tests {
+            #[test]
+            fn it_works() {}
+        }
+        "#,
+        );
+        let crate_id = analysis.crate_for(pos.file_id).unwrap()[0];
+        let runnable = analysis.crate_runnable(crate_id).unwrap().unwrap();
+        let root_text = analysis.file_text(pos.file_id).unwrap();
+        assert_eq!(runnable.range, TextRange::offset_len(0.into(), TextUnit::of_str(&root_text)));
+        assert_debug_snapshot!(&runnable.kind,
+        @r###"
+        TestMod {
+            path: "",
+        }
+        "###
+                );
+    }
+
+    #[test]
+    fn test_runnables_criterion_benches() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|>fn bench_foo(c: &mut Criterion) { c.bench_function("foo 20", |b| {}); }
+        fn bench_bar(c: &mut Criterion) { c.bench_function(&name(), |b| {}); }
+        fn helper() {}
+
+        criterion_group!(benches, bench_foo);
+        criterion_group! {
+            name = more_benches;
+            config = Criterion::default();
+            targets = bench_bar
+        }
+        criterion_main!(benches, more_benches);
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert_debug_snapshot!(&runnables,
+        @r###"
+        [
+            Runnable {
+                range: [0; 71),
+                kind: Bench {
+                    test_id: Name(
+                        "foo 20",
+                    ),
+                },
+            },
+            Runnable {
+                range: [72; 142),
+                kind: Bench {
+                    test_id: Name(
+                        "",
+                    ),
+                },
+            },
+        ]
+        "###
+                );
+    }
+
+    #[test]
+    fn test_runnables_doc_test() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|>/// ```
+        /// let x = 5;
+        /// ```
+        fn foo() {}
+
+        /// ```text
+        /// not rust
+        /// ```
+        fn bar() {}
+
+        /// ```ignore
+        /// broken
+        /// ```
+        struct Broken;
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert_debug_snapshot!(&runnables,
+        @r###"
+        [
+            Runnable {
+                range: [0; 42),
+                kind: DocTest {
+                    test_id: Path(
+                        "foo",
+                    ),
+                },
+            },
+        ]
+        "###
+                );
+    }
+
+    #[test]
+    fn test_runnables_doc_test_in_impl() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|>mod data {
+            struct Data;
+
+            /// ```
+            /// let data = data::Data::new();
+            /// ```
+            impl Data {
+                /// ```no_run
+                /// let data = Data::new();
+                /// ```
+                fn new() -> Data { Data }
+
+                fn helper() {}
+            }
+        }
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert_debug_snapshot!(&runnables,
+        @r###"
+        [
+            Runnable {
+                range: [95; 244),
+                kind: DocTest {
+                    test_id: Path(
+                        "data::Data",
+                    ),
+                },
+            },
+            Runnable {
+                range: [115; 214),
+                kind: DocTest {
+                    test_id: Path(
+                        "data::Data::new",
+                    ),
+                },
+            },
+        ]
+        "###
+                );
+    }
+
+    #[test]
+    fn test_runnables_module_doc_test() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|>//! ```
+        //! let x = 5;
+        //! ```
+        mod data {
+            //! ```
+            //! let data = 1;
+            //! ```
+            fn helper() {}
+        }
+        "#,
+        );
+        let runnables = analysis.runnables(pos.file_id).unwrap();
+        assert_debug_snapshot!(&runnables,
+        @r###"
+        [
+            Runnable {
+                range: [0; 110),
+                kind: DocTest {
+                    test_id: Path(
+                        "",
+                    ),
+                },
+            },
+            Runnable {
+                range: [0; 106),
+                kind: DocTest {
+                    test_id: Path(
+                        "data",
+                    ),
+                },
+            },
+        ]
+        "###
+                );
+    }
+
+    #[test]
+    fn test_no_crate_runnable_without_tests() {
+        let (analysis, pos) = analysis_and_position(
+            r#"
+        //- /lib.rs
+        <|>fn main() {}
+
+        #[bench]
+        fn bench_foo() {}
+        "#,
+        );
+        let crate_id = analysis.crate_for(pos.file_id).unwrap()[0];
+        assert!(analysis.crate_runnable(crate_id).unwrap().is_none());
+    }
+
     #[test]
     fn test_runnables_module() {
         let (analysis, pos) = analysis_and_position(
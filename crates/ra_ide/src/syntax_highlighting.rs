@@ -11,7 +11,7 @@ use ra_syntax::{
 use rustc_hash::FxHashMap;
 
 use crate::{
-    expand::descend_into_macros_with_analyzer,
+    expand::{descend_into_macros_with_depth, name_like_parent},
     references::{classify_name, classify_name_ref},
     FileId,
 };
@@ -19,6 +19,8 @@ use crate::{
 pub mod tags {
     pub(crate) const FIELD: &str = "field";
     pub(crate) const FUNCTION: &str = "function";
+    pub(crate) const FUNCTION_METHOD: &str = "function.method";
+    pub(crate) const FUNCTION_UNSAFE: &str = "function.unsafe";
     pub(crate) const MODULE: &str = "module";
     pub(crate) const CONSTANT: &str = "constant";
     pub(crate) const MACRO: &str = "macro";
@@ -30,11 +32,14 @@ pub mod tags {
     pub(crate) const TYPE_BUILTIN: &str = "type.builtin";
     pub(crate) const TYPE_SELF: &str = "type.self";
     pub(crate) const TYPE_PARAM: &str = "type.param";
+    pub(crate) const TYPE_TRAIT: &str = "type.trait";
     pub(crate) const TYPE_LIFETIME: &str = "type.lifetime";
 
     pub(crate) const LITERAL_BYTE: &str = "literal.byte";
     pub(crate) const LITERAL_NUMERIC: &str = "literal.numeric";
     pub(crate) const LITERAL_CHAR: &str = "literal.char";
+    pub(crate) const LITERAL_ESCAPE: &str = "literal.escape";
+    pub(crate) const FORMAT_SPECIFIER: &str = "format_specifier";
 
     pub(crate) const LITERAL_COMMENT: &str = "comment";
     pub(crate) const LITERAL_STRING: &str = "string";
@@ -42,10 +47,13 @@ pub mod tags {
 
     pub(crate) const KEYWORD: &str = "keyword";
     pub(crate) const KEYWORD_UNSAFE: &str = "keyword.unsafe";
+    pub(crate) const KEYWORD_SELF: &str = "keyword.self";
+
+    pub(crate) const OPERATOR_UNSAFE: &str = "operator.unsafe";
     pub(crate) const KEYWORD_CONTROL: &str = "keyword.control";
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub struct HighlightedRange {
     pub range: TextRange,
     pub tag: &'static str,
@@ -68,24 +76,37 @@ fn is_control_keyword(kind: SyntaxKind) -> bool {
 }
 
 pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRange> {
+    let mut res = Vec::new();
+    highlight_events(db, file_id, |range| res.push(range));
+    res
+}
+
+/// Like `highlight`, but passes each range to `sink` as soon as it is
+/// produced instead of collecting them.
+pub(crate) fn highlight_events(
+    db: &RootDatabase,
+    file_id: FileId,
+    mut sink: impl FnMut(HighlightedRange),
+) {
     let _p = profile("highlight");
     let parse = db.parse(file_id);
     let root = parse.tree().syntax().clone();
 
     let mut sb = SourceBinder::new(db);
     let mut bindings_shadow_count: FxHashMap<Name, u32> = FxHashMap::default();
-    let mut res = Vec::new();
     let analyzer = sb.analyze(InFile::new(file_id.into(), &root), None);
 
     let mut in_macro_call = None;
+    let mut format_string = None;
 
     for event in root.preorder_with_tokens() {
         match event {
             WalkEvent::Enter(node) => match node.kind() {
                 MACRO_CALL => {
                     in_macro_call = Some(node.clone());
+                    format_string = find_format_string(&node);
                     if let Some(range) = highlight_macro(InFile::new(file_id.into(), node)) {
-                        res.push(HighlightedRange { range, tag: tags::MACRO, binding_hash: None });
+                        sink(HighlightedRange { range, tag: tags::MACRO, binding_hash: None });
                     }
                 }
                 _ if in_macro_call.is_some() => {
@@ -96,11 +117,11 @@ pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRa
                             &mut bindings_shadow_count,
                             InFile::new(file_id.into(), token.clone()),
                         ) {
-                            res.push(HighlightedRange {
-                                range: node.text_range(),
-                                tag,
-                                binding_hash,
-                            });
+                            sink(HighlightedRange { range: node.text_range(), tag, binding_hash });
+                        }
+                        highlight_escapes(token, &mut sink);
+                        if format_string.as_ref() == Some(token) {
+                            highlight_format_specifiers(token, &mut sink);
                         }
                     }
                 }
@@ -110,7 +131,10 @@ pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRa
                         &mut bindings_shadow_count,
                         InFile::new(file_id.into(), node.clone()),
                     ) {
-                        res.push(HighlightedRange { range: node.text_range(), tag, binding_hash });
+                        sink(HighlightedRange { range: node.text_range(), tag, binding_hash });
+                    }
+                    if let Some(token) = node.as_token() {
+                        highlight_escapes(token, &mut sink);
                     }
                 }
             },
@@ -118,13 +142,55 @@ pub(crate) fn highlight(db: &RootDatabase, file_id: FileId) -> Vec<HighlightedRa
                 if let Some(m) = in_macro_call.as_ref() {
                     if *m == node {
                         in_macro_call = None;
+                        format_string = None;
                     }
                 }
             }
         }
     }
+}
 
-    res
+fn highlight_escapes(token: &SyntaxToken, sink: &mut impl FnMut(HighlightedRange)) {
+    for range in ast::literal_escape_ranges(token) {
+        sink(HighlightedRange { range, tag: tags::LITERAL_ESCAPE, binding_hash: None });
+    }
+}
+
+fn highlight_format_specifiers(token: &SyntaxToken, sink: &mut impl FnMut(HighlightedRange)) {
+    for range in ast::format_specifier_ranges(token) {
+        sink(HighlightedRange { range, tag: tags::FORMAT_SPECIFIER, binding_hash: None });
+    }
+}
+
+const FORMAT_MACROS: &[&str] = &[
+    "format",
+    "format_args",
+    "print",
+    "println",
+    "eprint",
+    "eprintln",
+    "write",
+    "writeln",
+    "panic",
+    "unreachable",
+    "unimplemented",
+    "todo",
+];
+
+/// Returns the format string passed to one of the std formatting macros: its
+/// first string literal argument.
+fn find_format_string(macro_call: &SyntaxElement) -> Option<SyntaxToken> {
+    let macro_call = ast::MacroCall::cast(macro_call.as_node()?.clone())?;
+    let name_ref = macro_call.path()?.segment()?.name_ref()?;
+    if !FORMAT_MACROS.contains(&name_ref.text().as_str()) {
+        return None;
+    }
+    macro_call
+        .token_tree()?
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|it| it.into_token())
+        .find(|it| it.kind() == STRING || it.kind() == RAW_STRING)
 }
 
 fn highlight_macro(node: InFile<SyntaxElement>) -> Option<TextRange> {
@@ -153,14 +219,11 @@ fn highlight_token_tree(
     if token.value.parent().kind() != TOKEN_TREE {
         return None;
     }
-    let token = descend_into_macros_with_analyzer(sb.db, analyzer, token);
-    let expanded = {
-        let parent = token.value.parent();
-        // We only care Name and Name_ref
-        match (token.value.kind(), parent.kind()) {
-            (IDENT, NAME) | (IDENT, NAME_REF) => token.with_value(parent.into()),
-            _ => token.map(|it| it.into()),
-        }
+    let (token, _depth) = descend_into_macros_with_depth(sb.db, analyzer, token);
+    // We only care Name and Name_ref
+    let expanded = match name_like_parent(&token) {
+        Some(name) => name.map(|it| it.into()),
+        None => token.map(|it| it.into()),
     };
 
     highlight_node(sb, bindings_shadow_count, expanded)
@@ -184,6 +247,9 @@ fn highlight_node(
         // Special-case field init shorthand
         NAME_REF if node.value.parent().and_then(ast::RecordField::cast).is_some() => tags::FIELD,
         NAME_REF if node.value.ancestors().any(|it| it.kind() == ATTR) => return None,
+        // `Self` in traits resolves to a type parameter, but we want the same
+        // tag as in impls
+        NAME_REF if node.value.as_node().map_or(false, |it| it.text() == "Self") => tags::TYPE_SELF,
         NAME_REF => {
             let name_ref = node.value.as_node().cloned().and_then(ast::NameRef::cast).unwrap();
             let name_kind = classify_name_ref(sb, node.with_value(&name_ref));
@@ -230,6 +296,15 @@ fn highlight_node(
         CHAR => tags::LITERAL_CHAR,
         LIFETIME => tags::TYPE_LIFETIME,
         T![unsafe] => tags::KEYWORD_UNSAFE,
+        T![*] if is_raw_ptr_deref(sb, node.as_ref()) => tags::OPERATOR_UNSAFE,
+        T![self]
+            if node
+                .value
+                .parent()
+                .map_or(false, |it| it.kind() == SELF_PARAM || it.kind() == PATH_SEGMENT) =>
+        {
+            tags::KEYWORD_SELF
+        }
         k if is_control_keyword(k) => tags::KEYWORD_CONTROL,
         k if k.is_keyword() => tags::KEYWORD,
 
@@ -251,6 +326,23 @@ fn highlight_node(
     }
 }
 
+/// Checks whether `node` is the `*` of a dereference of a raw pointer.
+fn is_raw_ptr_deref(sb: &mut SourceBinder<RootDatabase>, node: InFile<&SyntaxElement>) -> bool {
+    let prefix_expr = match node.value.parent().and_then(ast::PrefixExpr::cast) {
+        Some(it) => it,
+        None => return false,
+    };
+    if prefix_expr.op_kind() != Some(ast::PrefixOp::Deref) {
+        return false;
+    }
+    let operand = match prefix_expr.expr() {
+        Some(it) => it,
+        None => return false,
+    };
+    let analyzer = sb.analyze(node.with_value(prefix_expr.syntax()), None);
+    analyzer.type_of(sb.db, &operand).map_or(false, |ty| ty.is_raw_ptr())
+}
+
 pub(crate) fn highlight_as_html(db: &RootDatabase, file_id: FileId, rainbow: bool) -> String {
     let parse = db.parse(file_id);
 
@@ -285,7 +377,7 @@ pub(crate) fn highlight_as_html(db: &RootDatabase, file_id: FileId, rainbow: boo
                 break;
             }
         }
-        let text = html_escape(&token.text());
+        let text = token_html(&token, &could_intersect);
         let ranges = could_intersect
             .iter()
             .filter(|it| token.text_range().is_subrange(&it.range))
@@ -315,16 +407,26 @@ fn highlight_name(db: &RootDatabase, def: NameDefinition) -> &'static str {
         NameDefinition::Macro(_) => tags::MACRO,
         NameDefinition::StructField(_) => tags::FIELD,
         NameDefinition::ModuleDef(hir::ModuleDef::Module(_)) => tags::MODULE,
-        NameDefinition::ModuleDef(hir::ModuleDef::Function(_)) => tags::FUNCTION,
+        NameDefinition::ModuleDef(hir::ModuleDef::Function(it)) => {
+            if it.is_unsafe(db) {
+                tags::FUNCTION_UNSAFE
+            } else if it.has_self_param(db) {
+                tags::FUNCTION_METHOD
+            } else {
+                tags::FUNCTION
+            }
+        }
         NameDefinition::ModuleDef(hir::ModuleDef::Adt(_)) => tags::TYPE,
         NameDefinition::ModuleDef(hir::ModuleDef::EnumVariant(_)) => tags::CONSTANT,
         NameDefinition::ModuleDef(hir::ModuleDef::Const(_)) => tags::CONSTANT,
         NameDefinition::ModuleDef(hir::ModuleDef::Static(_)) => tags::CONSTANT,
-        NameDefinition::ModuleDef(hir::ModuleDef::Trait(_)) => tags::TYPE,
+        NameDefinition::ModuleDef(hir::ModuleDef::Trait(_)) => tags::TYPE_TRAIT,
         NameDefinition::ModuleDef(hir::ModuleDef::TypeAlias(_)) => tags::TYPE,
         NameDefinition::ModuleDef(hir::ModuleDef::BuiltinType(_)) => tags::TYPE_BUILTIN,
         NameDefinition::SelfType(_) => tags::TYPE_SELF,
         NameDefinition::TypeParam(_) => tags::TYPE_PARAM,
+        NameDefinition::ConstParam(_) => tags::CONSTANT,
+        NameDefinition::Lifetime(_) => tags::TYPE_LIFETIME,
         NameDefinition::Local(local) => {
             if local.is_mut(db) || local.ty(db).is_mutable_reference() {
                 tags::VARIABLE_MUT
@@ -340,6 +442,37 @@ fn html_escape(text: &str) -> String {
     text.replace("<", "&lt;").replace(">", "&gt;")
 }
 
+/// Escapes the text of `token`, putting the ranges highlighted inside of it,
+/// like escape sequences, into spans of their own.
+fn token_html(token: &SyntaxToken, ranges: &[&HighlightedRange]) -> String {
+    let token_range = token.text_range();
+    let mut inner = ranges
+        .iter()
+        .filter(|it| it.range != token_range && it.range.is_subrange(&token_range))
+        .collect::<Vec<_>>();
+    inner.sort_by_key(|it| it.range.start());
+
+    let text = token.text().as_str();
+    let mut buf = String::new();
+    let mut pos = 0;
+    for it in inner {
+        let start = (it.range.start() - token_range.start()).to_usize();
+        let end = (it.range.end() - token_range.start()).to_usize();
+        if start < pos {
+            continue;
+        }
+        buf.push_str(&html_escape(&text[pos..start]));
+        buf.push_str(&format!(
+            "<span class=\"{}\">{}</span>",
+            it.tag,
+            html_escape(&text[start..end])
+        ));
+        pos = end;
+    }
+    buf.push_str(&html_escape(&text[pos..]));
+    buf
+}
+
 const STYLE: &str = "
 <style>
 body                { margin: 0; }
@@ -354,9 +487,11 @@ pre                 { color: #DCDCCC; background: #3F3F3F; font-size: 22px; padd
 .type               { color: #7CB8BB; }
 .type\\.builtin     { color: #8CD0D3; }
 .type\\.param       { color: #20999D; }
+.type\\.trait       { color: #7CB8BB; font-style: italic; }
 .attribute          { color: #94BFF3; }
 .literal            { color: #BFEBBF; }
 .literal\\.numeric  { color: #6A8759; }
+.format_specifier   { color: #CC696B; }
 .macro              { color: #94BFF3; }
 .module             { color: #AFD8AF; }
 .variable           { color: #DCDCCC; }
@@ -376,6 +511,19 @@ mod tests {
 
     use crate::mock_analysis::{single_file, MockAnalysis};
 
+    use super::tags;
+
+    /// Highlights `fixture` and returns a function listing the tags of the
+    /// ranges whose text is `name`.
+    fn highlight_tags(fixture: &str) -> impl Fn(&str) -> Vec<&'static str> {
+        let (analysis, file_id) = single_file(fixture);
+        let highlights = analysis.highlight(file_id).unwrap();
+        let text = analysis.file_text(file_id).unwrap();
+        move |name: &str| {
+            highlights.iter().filter(|it| &text[it.range] == name).map(|it| it.tag).collect()
+        }
+    }
+
     #[test]
     fn test_highlighting() {
         let (analysis, file_id) = single_file(
@@ -462,6 +610,159 @@ fn bar() {
         assert_eq_text!(expected_html, actual_html);
     }
 
+    #[test]
+    fn test_highlight_format_strings() {
+        let (analysis, file_id) = single_file(
+            r#"
+fn main() {
+    let name = 92;
+    println!("Hello {{world}}, {} {0:?} {name:>8}", 1);
+    format!(r"{}{{", name);
+    let s = "{not a placeholder}";
+}
+"#
+            .trim(),
+        );
+        let dst_file =
+            project_dir().join("crates/ra_ide/src/snapshots/highlight_format_strings.html");
+        let actual_html = &analysis.highlight_as_html(file_id, false).unwrap();
+        let expected_html = &read_text(&dst_file);
+        fs::write(dst_file, &actual_html).unwrap();
+        assert_eq_text!(expected_html, actual_html);
+    }
+
+    #[test]
+    fn test_highlight_trait_names() {
+        let tagged = highlight_tags(
+            r#"
+trait Shape {}
+struct Square;
+impl Shape for Square {}
+fn draw<T: Shape>(shape: &dyn Shape) {}
+"#,
+        );
+        assert_eq!(tagged("Shape"), vec![tags::TYPE_TRAIT; 4]);
+        assert_eq!(tagged("Square"), vec![tags::TYPE; 2]);
+    }
+
+    #[test]
+    fn test_highlight_methods() {
+        let tagged = highlight_tags(
+            r#"
+struct S;
+impl S {
+    fn method(&self) {}
+    fn assoc() -> S { S }
+}
+fn main() {
+    let s = S::assoc();
+    s.method();
+    S::method(&s);
+}
+"#,
+        );
+        assert_eq!(tagged("method"), vec![tags::FUNCTION_METHOD; 3]);
+        assert_eq!(tagged("assoc"), vec![tags::FUNCTION; 2]);
+    }
+
+    #[test]
+    fn test_highlight_unsafe() {
+        let tagged = highlight_tags(
+            r#"
+struct S;
+impl S {
+    unsafe fn unsafe_method(&self) {}
+    fn safe_method(&self) {}
+}
+trait T {
+    unsafe fn trait_unsafe(&self);
+}
+impl T for S {
+    unsafe fn trait_unsafe(&self) {}
+}
+unsafe fn unsafe_fn() {}
+fn safe_fn() {}
+fn main(p: *const u8, r: &u8) {
+    unsafe {
+        unsafe_fn();
+        S.unsafe_method();
+        S.trait_unsafe();
+        *p;
+    }
+    safe_fn();
+    S.safe_method();
+    *r;
+}
+"#,
+        );
+        assert_eq!(tagged("unsafe_fn"), vec![tags::FUNCTION_UNSAFE; 2]);
+        assert_eq!(tagged("unsafe_method"), vec![tags::FUNCTION_UNSAFE; 2]);
+        assert_eq!(tagged("trait_unsafe"), vec![tags::FUNCTION_UNSAFE; 3]);
+        assert_eq!(tagged("safe_fn"), vec![tags::FUNCTION; 2]);
+        assert_eq!(tagged("safe_method"), vec![tags::FUNCTION_METHOD; 2]);
+        assert_eq!(tagged("*"), vec![tags::OPERATOR_UNSAFE]);
+    }
+
+    #[test]
+    fn test_highlight_self() {
+        let tagged = highlight_tags(
+            r#"
+macro_rules! id { ($($tt:tt)*) => { $($tt)* } }
+struct S { x: i32 }
+trait Tr {
+    fn new() -> Self;
+}
+impl S {
+    fn get(&self) -> i32 { self.x }
+    fn in_macro(self) -> Self { id!(self) }
+}
+"#,
+        );
+        assert_eq!(tagged("self"), vec![tags::KEYWORD_SELF; 4]);
+        assert_eq!(tagged("Self"), vec![tags::TYPE_SELF; 2]);
+    }
+
+    #[test]
+    fn test_highlight_events() {
+        let (analysis, file_id) = single_file(r#"fn main() { let s = "a\nb"; }"#);
+        let text = analysis.file_text(file_id).unwrap();
+        let mut events = Vec::new();
+        analysis.highlight_events(file_id, |it| events.push((&text[it.range], it.tag))).unwrap();
+        assert_eq!(
+            events,
+            vec![
+                ("fn", tags::KEYWORD),
+                ("main", tags::FUNCTION),
+                ("let", tags::KEYWORD),
+                ("s", tags::VARIABLE),
+                (r#""a\nb""#, tags::LITERAL_STRING),
+                (r"\n", tags::LITERAL_ESCAPE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highlight_escapes() {
+        let (analysis, file_id) = single_file(
+            r#"
+fn main() {
+    let s = "a\nb\t";
+    let r = r"a\nb";
+    let c = '\u{1F600}';
+    println!("{}\n", b"\x7f");
+}
+"#,
+        );
+        let highlights = analysis.highlight(file_id).unwrap();
+        let text = analysis.file_text(file_id).unwrap();
+        let escapes = highlights
+            .iter()
+            .filter(|it| it.tag == tags::LITERAL_ESCAPE)
+            .map(|it| &text[it.range])
+            .collect::<Vec<_>>();
+        assert_eq!(escapes, vec![r"\n", r"\t", r"\u{1F600}", r"\n", r"\x7f"]);
+    }
+
     #[test]
     fn accidentally_quadratic() {
         let file = project_dir().join("crates/ra_syntax/test_data/accidentally_quadratic");
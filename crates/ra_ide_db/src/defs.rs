@@ -6,13 +6,13 @@
 // FIXME: this badly needs rename/rewrite (matklad, 2020-02-06).
 
 use hir::{
-    Adt, FieldSource, HasSource, ImplBlock, InFile, Local, MacroDef, Module, ModuleDef,
-    SourceBinder, StructField, TypeParam,
+    Adt, ConstParam, FieldSource, HasSource, ImplBlock, InFile, Local, MacroDef, Module, ModuleDef,
+    ScopeDef, SourceBinder, StructField, TypeParam,
 };
 use ra_prof::profile;
 use ra_syntax::{
     ast::{self, AstNode, VisibilityOwner},
-    match_ast,
+    match_ast, SmolStr, SyntaxKind, SyntaxNode, SyntaxToken, TextRange,
 };
 
 use crate::RootDatabase;
@@ -25,6 +25,20 @@ pub enum NameDefinition {
     SelfType(ImplBlock),
     Local(Local),
     TypeParam(TypeParam),
+    ConstParam(ConstParam),
+    Lifetime(LifetimeParam),
+}
+
+/// A lifetime parameter, like `'a` in `fn foo<'a>()`. Lifetimes aren't lowered
+/// to HIR, so the parameter is identified by the range of its declaration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LifetimeParam {
+    pub module: Module,
+    pub name: SmolStr,
+    /// Range of the declaring lifetime token.
+    pub decl: InFile<TextRange>,
+    /// Range of the item declaring the parameter, which contains all its uses.
+    pub owner_range: TextRange,
 }
 
 impl NameDefinition {
@@ -36,6 +50,8 @@ impl NameDefinition {
             NameDefinition::SelfType(it) => Some(it.module(db)),
             NameDefinition::Local(it) => Some(it.module(db)),
             NameDefinition::TypeParam(it) => Some(it.module(db)),
+            NameDefinition::ConstParam(it) => Some(it.module(db)),
+            NameDefinition::Lifetime(it) => Some(it.module),
         }
     }
 
@@ -64,6 +80,8 @@ impl NameDefinition {
             NameDefinition::SelfType(_) => None,
             NameDefinition::Local(_) => None,
             NameDefinition::TypeParam(_) => None,
+            NameDefinition::ConstParam(_) => None,
+            NameDefinition::Lifetime(_) => None,
         }
     }
 }
@@ -142,11 +160,71 @@ pub fn classify_name(
                 let def = sb.to_def(src)?;
                 Some(NameDefinition::TypeParam(def))
             },
+            ast::ConstParam(it) => {
+                let src = name.with_value(it);
+                let def = sb.to_def(src)?;
+                Some(NameDefinition::ConstParam(def))
+            },
+            ast::Alias(it) => {
+                // `use foo::Bar as Baz;` puts `Baz` into the scope of the
+                // module, referring to `Bar`
+                let module = containing_module(sb, name.with_value(it.syntax()))?;
+                let alias = name.value.text();
+                let (_, def) = module
+                    .scope(sb.db)
+                    .into_iter()
+                    .find(|(scope_name, _)| scope_name.to_string() == alias.as_str())?;
+                match def {
+                    ScopeDef::ModuleDef(def) => Some(from_module_def(def)),
+                    ScopeDef::MacroDef(def) => Some(NameDefinition::Macro(def)),
+                    _ => None,
+                }
+            },
             _ => None,
         }
     }
 }
 
+/// Classifies a `LIFETIME` token by the lifetime parameter it refers to.
+/// `'static` and the anonymous `'_` don't refer to any parameter.
+pub fn classify_lifetime(
+    sb: &mut SourceBinder<RootDatabase>,
+    lifetime: InFile<&SyntaxToken>,
+) -> Option<NameDefinition> {
+    let _p = profile("classify_lifetime");
+    let name = lifetime.value.text();
+    if lifetime.value.kind() != SyntaxKind::LIFETIME || name == "'static" || name == "'_" {
+        return None;
+    }
+
+    let (decl, decl_token) = lifetime.value.parent().ancestors().find_map(|node| {
+        node.children().find_map(ast::TypeParamList::cast)?.lifetime_params().find_map(|param| {
+            let token = param.lifetime_token().filter(|it| it.text() == name)?;
+            Some((param, token))
+        })
+    })?;
+    let owner = decl.syntax().parent()?.parent()?;
+
+    let module = containing_module(sb, lifetime.with_value(&owner))?;
+    Some(NameDefinition::Lifetime(LifetimeParam {
+        module,
+        name: name.clone(),
+        decl: lifetime.with_value(decl_token.text_range()),
+        owner_range: owner.text_range(),
+    }))
+}
+
+/// Finds the module `node` is in, which may be an inline module of its file.
+fn containing_module(
+    sb: &mut SourceBinder<RootDatabase>,
+    node: InFile<&SyntaxNode>,
+) -> Option<Module> {
+    match node.value.ancestors().find_map(ast::Module::cast) {
+        Some(module) => sb.to_def(node.with_value(module)),
+        None => sb.to_module_def(node.file_id.original_file(sb.db)),
+    }
+}
+
 pub fn from_struct_field(field: StructField) -> NameDefinition {
     NameDefinition::StructField(field)
 }
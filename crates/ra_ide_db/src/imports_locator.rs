@@ -1,7 +1,7 @@
 //! This module contains an import search funcionality that is provided to the ra_assists module.
 //! Later, this should be moved away to a separate crate that is accessible from the ra_assists module.
 
-use hir::{db::HirDatabase, ModuleDef, SourceBinder};
+use hir::{db::HirDatabase, AsAssocItem, Module, ModuleDef, ScopeDef, SourceBinder};
 use ra_prof::profile;
 use ra_syntax::{ast, AstNode, SyntaxKind::NAME};
 
@@ -20,9 +20,32 @@ impl<'a> ImportsLocator<'a> {
         Self { source_binder: SourceBinder::new(db) }
     }
 
-    pub fn find_imports(&mut self, name_to_import: &str) -> Vec<ModuleDef> {
+    /// Finds items named `name_to_import` which can be imported into
+    /// `importing_module`, directly or through a re-export. Items which are
+    /// already in scope through the crate's prelude are skipped, as importing
+    /// them would be redundant.
+    pub fn find_imports(
+        &mut self,
+        name_to_import: &str,
+        importing_module: Module,
+    ) -> Vec<ModuleDef> {
         let _p = profile("search_for_imports");
         let db = self.source_binder.db;
+        let prelude_defs = importing_module
+            .krate()
+            .prelude(db)
+            .map(|prelude| {
+                prelude
+                    .scope(db)
+                    .into_iter()
+                    .filter(|(name, _)| name.to_string() == name_to_import)
+                    .filter_map(|(_, def)| match def {
+                        ScopeDef::ModuleDef(it) => Some(it),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
 
         let project_results = {
             let mut query = Query::new(name_to_import.to_string());
@@ -46,6 +69,8 @@ impl<'a> ImportsLocator<'a> {
                 NameDefinition::ModuleDef(module_def) => Some(module_def),
                 _ => None,
             })
+            .filter(|module_def| is_importable(db, importing_module, *module_def))
+            .filter(|module_def| !prelude_defs.contains(module_def))
             .collect()
     }
 
@@ -68,3 +93,136 @@ impl<'a> ImportsLocator<'a> {
         )
     }
 }
+
+fn is_importable(db: &impl HirDatabase, importing_module: Module, def: ModuleDef) -> bool {
+    match def {
+        // Associated items are imported through their trait, callers look for
+        // a path to that instead
+        ModuleDef::Function(it) if it.as_assoc_item(db).is_some() => true,
+        ModuleDef::Const(it) if it.as_assoc_item(db).is_some() => true,
+        _ => importing_module.find_use_path(db, def).is_some(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use hir::{Adt, ModuleDef, SourceBinder};
+    use ra_db::{fixture::WithFixture, SourceDatabaseExt};
+
+    use super::ImportsLocator;
+    use crate::{symbol_index::SymbolsDatabase, RootDatabase};
+
+    /// Returns the names of the modules of the `name` structs that can be
+    /// imported into the module at the cursor of `fixture`.
+    fn import_modules(fixture: &str, name: &str) -> Vec<String> {
+        let (mut db, pos) = RootDatabase::with_position(fixture);
+        let local_roots = vec![db.file_source_root(pos.file_id)];
+        db.set_local_roots(Arc::new(local_roots));
+        let module = SourceBinder::new(&db).to_module_def(pos.file_id).unwrap();
+
+        ImportsLocator::new(&db)
+            .find_imports(name, module)
+            .into_iter()
+            .map(|def| match def {
+                ModuleDef::Adt(Adt::Struct(it)) => it.module(&db).name(&db).unwrap().to_string(),
+                _ => panic!("unexpected import {:?}", def),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn private_items_of_dependencies_are_not_suggested() {
+        let modules = import_modules(
+            r#"
+            //- /main.rs crate:main deps:dep
+            <|>
+            //- /dep.rs crate:dep
+            pub mod a {
+                pub struct Formatter;
+            }
+            pub mod b {
+                struct Formatter;
+            }
+            mod c {
+                pub struct Formatter;
+            }
+            "#,
+            "Formatter",
+        );
+        assert_eq!(modules, vec!["a"]);
+    }
+
+    #[test]
+    fn visibility_is_checked_from_the_importing_module() {
+        let modules = import_modules(
+            r#"
+            //- /main.rs crate:main
+            mod foo;
+            //- /foo.rs
+            <|>
+            mod inner {
+                pub(super) struct Thing;
+            }
+            "#,
+            "Thing",
+        );
+        assert_eq!(modules, vec!["inner"]);
+
+        let modules = import_modules(
+            r#"
+            //- /main.rs crate:main
+            <|>
+            mod foo;
+            //- /foo.rs
+            mod inner {
+                pub(super) struct Thing;
+            }
+            "#,
+            "Thing",
+        );
+        assert!(modules.is_empty());
+    }
+
+    #[test]
+    fn reexported_items_of_private_modules_are_suggested() {
+        let modules = import_modules(
+            r#"
+            //- /main.rs crate:main deps:dep
+            <|>
+            //- /dep.rs crate:dep
+            mod imp {
+                pub struct Thing;
+            }
+            pub use imp::Thing;
+            "#,
+            "Thing",
+        );
+        assert_eq!(modules, vec!["imp"]);
+    }
+
+    #[test]
+    fn items_in_prelude_are_not_suggested() {
+        let modules = import_modules(
+            r#"
+            //- /main.rs crate:main deps:std
+            <|>
+            //- /std.rs crate:std
+            pub mod vec {
+                pub struct Vec;
+            }
+            pub mod other {
+                pub struct Vec;
+            }
+            pub mod prelude {
+                pub use crate::vec::Vec;
+            }
+            #[prelude_import]
+            pub use prelude::*;
+            "#,
+            "Vec",
+        );
+        assert_eq!(modules, vec!["other"]);
+    }
+}
@@ -3,10 +3,10 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
-use cargo_metadata::{CargoOpt, MetadataCommand};
+use cargo_metadata::MetadataCommand;
 use ra_arena::{impl_arena_id, Arena, RawId};
 use ra_db::Edition;
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::Deserialize;
 
 /// `CargoWorkspace` represents the logical structure of, well, a Cargo
@@ -21,6 +21,7 @@ pub struct CargoWorkspace {
     packages: Arena<Package, PackageData>,
     targets: Arena<Target, TargetData>,
     workspace_root: PathBuf,
+    target: Option<String>,
 }
 
 #[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
@@ -32,8 +33,8 @@ pub struct CargoFeatures {
     /// Activate all available features
     pub all_features: bool,
 
-    /// List of features to activate.
-    /// This will be ignored if `cargo_all_features` is true.
+    /// List of features to activate, in addition to the `default` one unless
+    /// `no_default_features` is set. Ignored if `all_features` is set.
     pub features: Vec<String>,
 }
 
@@ -43,6 +44,29 @@ impl Default for CargoFeatures {
     }
 }
 
+impl CargoFeatures {
+    /// The arguments to pass to `cargo` to activate these features.
+    pub fn cargo_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        if self.all_features && !self.features.is_empty() {
+            log::warn!(
+                "ignoring `features` ({}), `all_features` already activates every feature",
+                self.features.join(", ")
+            );
+        } else if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(" "));
+        }
+        args
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Package(RawId);
 impl_arena_id!(Package);
@@ -66,6 +90,40 @@ struct PackageData {
 pub struct PackageDependency {
     pub pkg: Package,
     pub name: String,
+    pub kinds: Vec<DepKind>,
+}
+
+impl PackageDependency {
+    /// Whether a target of the given kind in the depending package can use
+    /// this dependency.
+    pub fn is_used_by(&self, target: TargetKind) -> bool {
+        self.kinds.iter().any(|kind| match kind {
+            DepKind::Build => target == TargetKind::BuildScript,
+            // Dev-dependencies are also used by unit tests, so we can't
+            // restrict them to test targets
+            DepKind::Normal | DepKind::Dev => target != TargetKind::BuildScript,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepKind {
+    /// `[dependencies]`
+    Normal,
+    /// `[dev-dependencies]`
+    Dev,
+    /// `[build-dependencies]`
+    Build,
+}
+
+impl DepKind {
+    fn new(kind: cargo_metadata::DependencyKind) -> DepKind {
+        match kind {
+            cargo_metadata::DependencyKind::Development => DepKind::Dev,
+            cargo_metadata::DependencyKind::Build => DepKind::Build,
+            _ => DepKind::Normal,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -75,6 +133,8 @@ struct TargetData {
     root: PathBuf,
     kind: TargetKind,
     is_proc_macro: bool,
+    crate_types: Vec<String>,
+    required_features: Vec<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -85,6 +145,8 @@ pub enum TargetKind {
     Example,
     Test,
     Bench,
+    /// A `build.rs` script.
+    BuildScript,
     Other,
 }
 
@@ -96,6 +158,7 @@ impl TargetKind {
                 "test" => TargetKind::Test,
                 "bench" => TargetKind::Bench,
                 "example" => TargetKind::Example,
+                "custom-build" => TargetKind::BuildScript,
                 "proc-macro" => TargetKind::Lib,
                 _ if kind.contains("lib") => TargetKind::Lib,
                 _ => continue,
@@ -112,6 +175,9 @@ impl Package {
     pub fn root(self, ws: &CargoWorkspace) -> &Path {
         ws.packages[self].manifest.parent().unwrap()
     }
+    pub fn manifest(self, ws: &CargoWorkspace) -> &Path {
+        ws.packages[self].manifest.as_path()
+    }
     pub fn edition(self, ws: &CargoWorkspace) -> Edition {
         ws.packages[self].edition
     }
@@ -121,7 +187,6 @@ impl Package {
     pub fn targets<'a>(self, ws: &'a CargoWorkspace) -> impl Iterator<Item = Target> + 'a {
         ws.packages[self].targets.iter().cloned()
     }
-    #[allow(unused)]
     pub fn is_member(self, ws: &CargoWorkspace) -> bool {
         ws.packages[self].is_member
     }
@@ -146,8 +211,22 @@ impl Target {
     pub fn kind(self, ws: &CargoWorkspace) -> TargetKind {
         ws.targets[self].kind
     }
+    /// Prefers the declared crate types, falling back to the target kind
+    /// for metadata which doesn't report them.
     pub fn is_proc_macro(self, ws: &CargoWorkspace) -> bool {
-        ws.targets[self].is_proc_macro
+        let data = &ws.targets[self];
+        if data.crate_types.is_empty() {
+            return data.is_proc_macro;
+        }
+        data.crate_types.iter().any(|it| it == "proc-macro")
+    }
+    /// The `crate-type`s of the target, e.g. `cdylib` or `proc-macro`.
+    pub fn crate_types(self, ws: &CargoWorkspace) -> &[String] {
+        &ws.targets[self].crate_types
+    }
+    /// The features that must be enabled for the target to be built.
+    pub fn required_features(self, ws: &CargoWorkspace) -> &[String] {
+        &ws.targets[self].required_features
     }
 }
 
@@ -155,32 +234,39 @@ impl CargoWorkspace {
     pub fn from_cargo_metadata(
         cargo_toml: &Path,
         cargo_features: &CargoFeatures,
+        target: Option<&str>,
     ) -> Result<CargoWorkspace> {
         let mut meta = MetadataCommand::new();
         meta.manifest_path(cargo_toml);
-        if cargo_features.all_features {
-            meta.features(CargoOpt::AllFeatures);
-        } else if cargo_features.no_default_features {
-            // FIXME: `NoDefaultFeatures` is mutual exclusive with `SomeFeatures`
-            // https://github.com/oli-obk/cargo_metadata/issues/79
-            meta.features(CargoOpt::NoDefaultFeatures);
-        } else if !cargo_features.features.is_empty() {
-            meta.features(CargoOpt::SomeFeatures(cargo_features.features.clone()));
-        }
+        // The features are passed as raw arguments, as `MetadataCommand::features`
+        // can't combine `NoDefaultFeatures` with `SomeFeatures`, see
+        // https://github.com/oli-obk/cargo_metadata/issues/79
+        let mut other_options = cargo_features.cargo_args();
         if let Some(parent) = cargo_toml.parent() {
             meta.current_dir(parent);
         }
+        if let Some(target) = target {
+            // `cargo metadata` has no `--target`, `--filter-platform` is its
+            // equivalent for resolving target-specific dependencies.
+            other_options.push("--filter-platform".to_string());
+            other_options.push(target.to_string());
+        }
+        meta.other_options(other_options);
         let meta = meta.exec().with_context(|| {
             format!("Failed to run `cargo metadata --manifest-path {}`", cargo_toml.display())
         })?;
         let mut pkg_by_id = FxHashMap::default();
         let mut packages = Arena::default();
         let mut targets = Arena::default();
+        // The kinds of each dependency of a package, by the name the package
+        // refers to it with (which differs per kind if it's renamed)
+        let mut dep_kinds = FxHashMap::default();
 
         let ws_members = &meta.workspace_members;
 
         for meta_pkg in meta.packages {
-            let cargo_metadata::Package { id, edition, name, manifest_path, .. } = meta_pkg;
+            let cargo_metadata::Package { id, edition, name, manifest_path, dependencies, .. } =
+                meta_pkg;
             let is_member = ws_members.contains(&id);
             let edition = edition
                 .parse::<Edition>()
@@ -196,6 +282,14 @@ impl CargoWorkspace {
             });
             let pkg_data = &mut packages[pkg];
             pkg_by_id.insert(id, pkg);
+            let kinds: Vec<(String, DepKind)> = dependencies
+                .into_iter()
+                .map(|dep| {
+                    let name = dep.rename.unwrap_or(dep.name).replace('-', "_");
+                    (name, DepKind::new(dep.kind))
+                })
+                .collect();
+            dep_kinds.insert(pkg, kinds);
             for meta_tgt in meta_pkg.targets {
                 let is_proc_macro = meta_tgt.kind.as_slice() == ["proc-macro"];
                 let tgt = targets.alloc(TargetData {
@@ -204,6 +298,8 @@ impl CargoWorkspace {
                     root: meta_tgt.src_path.clone(),
                     kind: TargetKind::new(meta_tgt.kind.as_slice()),
                     is_proc_macro,
+                    crate_types: meta_tgt.crate_types,
+                    required_features: meta_tgt.required_features,
                 });
                 pkg_data.targets.push(tgt);
             }
@@ -231,13 +327,27 @@ impl CargoWorkspace {
                         continue;
                     }
                 };
-                let dep = PackageDependency { name: dep_node.name, pkg };
+                let mut kinds = Vec::new();
+                for (name, kind) in dep_kinds.get(&source).into_iter().flatten() {
+                    if *name == dep_node.name && !kinds.contains(kind) {
+                        kinds.push(*kind);
+                    }
+                }
+                if kinds.is_empty() {
+                    kinds.push(DepKind::Normal);
+                }
+                let dep = PackageDependency { name: dep_node.name, pkg, kinds };
                 packages[source].dependencies.push(dep);
             }
             packages[source].features.extend(node.features);
         }
 
-        Ok(CargoWorkspace { packages, targets, workspace_root: meta.workspace_root })
+        Ok(CargoWorkspace {
+            packages,
+            targets,
+            workspace_root: meta.workspace_root,
+            target: target.map(|it| it.to_string()),
+        })
     }
 
     pub fn packages<'a>(&'a self) -> impl Iterator<Item = Package> + ExactSizeIterator + 'a {
@@ -248,7 +358,410 @@ impl CargoWorkspace {
         self.packages().filter_map(|pkg| pkg.targets(self).find(|it| it.root(self) == root)).next()
     }
 
+    /// All example targets, across every package of the workspace.
+    pub fn examples(&self) -> Vec<Target> {
+        self.packages()
+            .flat_map(|pkg| pkg.targets(self))
+            .filter(|tgt| tgt.kind(self) == TargetKind::Example)
+            .collect()
+    }
+
+    /// The directory of the workspace's root manifest. For a virtual manifest
+    /// (one without a `[package]`) this is not the root of any package.
     pub fn workspace_root(&self) -> &Path {
         &self.workspace_root
     }
+
+    /// The target triple this workspace is built for, if it is not the host.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    /// Returns the workspace members ordered so that each package comes after
+    /// the members it depends on.
+    ///
+    /// Dev-dependencies are ignored, as they may legitimately form cycles. Any
+    /// remaining cycle is broken at the edge leading back to a package that is
+    /// still being visited. Ties are resolved by package name, so the order is
+    /// deterministic.
+    pub fn members_in_topo_order(&self) -> Vec<Package> {
+        fn visit(
+            ws: &CargoWorkspace,
+            pkg: Package,
+            visiting: &mut FxHashSet<Package>,
+            done: &mut FxHashSet<Package>,
+            res: &mut Vec<Package>,
+        ) {
+            if done.contains(&pkg) || !visiting.insert(pkg) {
+                return;
+            }
+            let mut deps = pkg
+                .dependencies(ws)
+                .filter(|dep| dep.pkg.is_member(ws))
+                .filter(|dep| dep.kinds.iter().any(|&kind| kind != DepKind::Dev))
+                .map(|dep| dep.pkg)
+                .collect::<Vec<_>>();
+            deps.sort_by_key(|&it| it.name(ws));
+            for dep in deps {
+                visit(ws, dep, visiting, done, res);
+            }
+            visiting.remove(&pkg);
+            done.insert(pkg);
+            res.push(pkg);
+        }
+
+        let mut members = self.packages().filter(|pkg| pkg.is_member(self)).collect::<Vec<_>>();
+        members.sort_by_key(|&it| it.name(self));
+
+        let mut res = Vec::with_capacity(members.len());
+        let mut visiting = FxHashSet::default();
+        let mut done = FxHashSet::default();
+        for pkg in members {
+            visit(self, pkg, &mut visiting, &mut done, &mut res);
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ra_cfg::{CfgExpr, CfgOptions};
+    use ra_db::{CrateId, Env, FileId};
+
+    use super::*;
+    use crate::{ProjectWorkspace, Sysroot, WorkspaceCrateGraph};
+
+    /// Builds a `CargoWorkspace` by hand. Packages are 2018 edition workspace
+    /// members, tests tweak the data afterwards when they need anything else.
+    #[derive(Default)]
+    struct WorkspaceBuilder {
+        packages: Arena<Package, PackageData>,
+        targets: Arena<Target, TargetData>,
+    }
+
+    impl WorkspaceBuilder {
+        /// Adds a package in `dir`, named after its last component.
+        fn package(&mut self, dir: &str) -> Package {
+            self.packages.alloc(PackageData {
+                name: dir.rsplit('/').next().unwrap().to_string(),
+                manifest: PathBuf::from(format!("{}/Cargo.toml", dir)),
+                targets: Vec::new(),
+                is_member: true,
+                dependencies: Vec::new(),
+                edition: Edition::Edition2018,
+                features: Vec::new(),
+            })
+        }
+
+        /// Adds a target named after `pkg`.
+        fn target(&mut self, pkg: Package, kind: TargetKind, root: &str) -> Target {
+            let tgt = self.targets.alloc(TargetData {
+                pkg,
+                name: self.packages[pkg].name.clone(),
+                root: PathBuf::from(root),
+                kind,
+                is_proc_macro: false,
+                crate_types: Vec::new(),
+                required_features: Vec::new(),
+            });
+            self.packages[pkg].targets.push(tgt);
+            tgt
+        }
+
+        /// Adds a package in `dir` with a single lib target.
+        fn lib(&mut self, dir: &str) -> Package {
+            let pkg = self.package(dir);
+            self.target(pkg, TargetKind::Lib, &format!("{}/src/lib.rs", dir));
+            pkg
+        }
+
+        fn dep(&mut self, from: Package, to: Package, name: &str, kind: DepKind) {
+            self.packages[from].dependencies.push(PackageDependency {
+                pkg: to,
+                name: name.to_string(),
+                kinds: vec![kind],
+            });
+        }
+
+        fn cargo(self, workspace_root: &str) -> CargoWorkspace {
+            CargoWorkspace {
+                packages: self.packages,
+                targets: self.targets,
+                workspace_root: PathBuf::from(workspace_root),
+                target: None,
+            }
+        }
+
+        fn project(self, workspace_root: &str) -> ProjectWorkspace {
+            ProjectWorkspace::Cargo {
+                cargo: self.cargo(workspace_root),
+                sysroot: Sysroot::default(),
+            }
+        }
+    }
+
+    #[test]
+    fn dependency_renamed_for_build_script_only() {
+        let mut builder = WorkspaceBuilder::default();
+        let log_0_4 = builder.lib("/log-0.4");
+        let log_0_3 = builder.lib("/log-0.3");
+        let pkg = builder.lib("/pkg");
+        builder.target(pkg, TargetKind::BuildScript, "/pkg/build.rs");
+        builder.dep(pkg, log_0_4, "log", DepKind::Normal);
+        builder.dep(pkg, log_0_3, "old_log", DepKind::Build);
+
+        let mut files = Vec::new();
+        let crate_graph = builder
+            .project("/")
+            .to_crate_graph(&CfgOptions::default(), &Env::default(), &mut |path: &Path| {
+                files.push(path.to_path_buf());
+                Some(FileId(files.len() as u32 - 1))
+            })
+            .crate_graph;
+        let deps = |root: &str| {
+            let file_id = files.iter().position(|it| it == Path::new(root)).unwrap();
+            let krate = crate_graph.crate_id_for_crate_root(FileId(file_id as u32)).unwrap();
+            crate_graph.dependencies(krate).map(|dep| dep.name.to_string()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(deps("/pkg/src/lib.rs"), vec!["log"]);
+        assert_eq!(deps("/pkg/build.rs"), vec!["old_log"]);
+    }
+
+    #[test]
+    fn resolve_renamed_dependency() {
+        let mut builder = WorkspaceBuilder::default();
+        let log_0_3 = builder.lib("/log-0.3");
+        let pkg = builder.lib("/pkg");
+        builder.dep(pkg, log_0_3, "old_log", DepKind::Normal);
+        let ws = builder.project("/");
+
+        let crate_root = |krate: CrateId| match krate.0 {
+            0 => Some(PathBuf::from("/pkg/src/lib.rs")),
+            _ => None,
+        };
+        let resolved = ws.resolve_dep(CrateId(0), "old-log", &crate_root);
+        assert_eq!(resolved, Some(log_0_3));
+        assert_eq!(ws.resolve_dep(CrateId(0), "log", &crate_root), None);
+        assert_eq!(ws.resolve_dep(CrateId(1), "old_log", &crate_root), None);
+    }
+
+    #[test]
+    fn target_crate_types_and_required_features() {
+        let mut builder = WorkspaceBuilder::default();
+        let pkg = builder.package("/pkg");
+        let mut add_target = |kind: &str, crate_types: &[&str]| {
+            let tgt = builder.target(pkg, TargetKind::new(&[kind.to_string()]), "/pkg/src/lib.rs");
+            builder.targets[tgt].crate_types =
+                crate_types.iter().map(|it| it.to_string()).collect();
+            builder.targets[tgt].required_features = vec!["ffi".to_string()];
+            tgt
+        };
+        let cdylib = add_target("cdylib", &["cdylib"]);
+        let proc_macro = add_target("lib", &["proc-macro"]);
+        let cargo = builder.cargo("/");
+
+        assert_eq!(cdylib.kind(&cargo), TargetKind::Lib);
+        assert_eq!(cdylib.crate_types(&cargo), ["cdylib"]);
+        assert_eq!(cdylib.required_features(&cargo), ["ffi"]);
+        assert!(!cdylib.is_proc_macro(&cargo));
+        // The kind based heuristic doesn't recognize this as a proc-macro
+        assert!(proc_macro.is_proc_macro(&cargo));
+    }
+
+    #[test]
+    fn examples_lists_example_targets() {
+        let mut builder = WorkspaceBuilder::default();
+        let pkg = builder.package("/pkg");
+        builder.target(pkg, TargetKind::Lib, "/pkg/src/lib.rs");
+        let hello = builder.target(pkg, TargetKind::Example, "/pkg/examples/hello.rs");
+        builder.target(pkg, TargetKind::Bin, "/pkg/src/main.rs");
+        let world = builder.target(pkg, TargetKind::Example, "/pkg/examples/world.rs");
+        let cargo = builder.cargo("/");
+
+        assert_eq!(cargo.examples(), vec![hello, world]);
+    }
+
+    #[test]
+    fn members_in_topo_order() {
+        let mut builder = WorkspaceBuilder::default();
+        let a = builder.package("/a");
+        let b = builder.package("/b");
+        let c = builder.package("/c");
+        builder.dep(a, b, "b", DepKind::Normal);
+        builder.dep(b, c, "c", DepKind::Normal);
+        // Dev-dependencies don't constrain the order
+        builder.dep(c, a, "a", DepKind::Dev);
+        let cargo = builder.cargo("/");
+
+        let order = cargo.members_in_topo_order();
+        let names = order.iter().map(|it| it.name(&cargo)).collect::<Vec<_>>();
+        assert_eq!(names, vec!["c", "b", "a"]);
+    }
+
+    #[test]
+    fn workspace_diff_after_adding_package() {
+        // Packages are given by their directory, and named after its last component
+        let workspace = |dirs: &[&str], deps: &[(usize, usize)]| {
+            let mut builder = WorkspaceBuilder::default();
+            let ids =
+                dirs.iter().map(|dir| builder.package(&format!("/{}", dir))).collect::<Vec<_>>();
+            for &(from, to) in deps {
+                let name = builder.packages[ids[to]].name.clone();
+                builder.dep(ids[from], ids[to], &name, DepKind::Normal);
+            }
+            builder.project("/")
+        };
+
+        let dep =
+            |from: &str, to: &str| (format!("/{}/Cargo.toml", from), format!("/{}/Cargo.toml", to));
+        let before = workspace(&["a", "b"], &[(0, 1)]);
+        let after = workspace(&["a", "b", "c"], &[(0, 2), (2, 1)]);
+
+        assert!(before.diff(&before).is_empty());
+        let diff = before.diff(&after);
+        assert_eq!(diff.added_packages, vec!["/c/Cargo.toml"]);
+        assert!(diff.removed_packages.is_empty());
+        assert_eq!(diff.added_deps, vec![dep("a", "c"), dep("c", "b")]);
+        assert_eq!(diff.removed_deps, vec![dep("a", "b")]);
+
+        let diff = after.diff(&before);
+        assert_eq!(diff.removed_packages, vec!["/c/Cargo.toml"]);
+        assert_eq!(diff.added_deps, vec![dep("a", "b")]);
+
+        // A package with the same name as another one is still a new package
+        let vendored = workspace(&["a", "b", "vendor/b"], &[(0, 1), (0, 2)]);
+        let diff = before.diff(&vendored);
+        assert_eq!(diff.added_packages, vec!["/vendor/b/Cargo.toml"]);
+        assert_eq!(diff.added_deps, vec![dep("a", "vendor/b")]);
+    }
+
+    #[test]
+    fn virtual_workspace_with_glob_members() {
+        // `members = ["crates/*"]` in a virtual `/ws/Cargo.toml`; `cargo metadata`
+        // expands the glob, so each matched package is simply a member.
+        let mut builder = WorkspaceBuilder::default();
+        builder.lib("/ws/crates/a");
+        builder.lib("/ws/crates/b");
+        let dep = builder.lib("/registry/dep");
+        builder.packages[dep].is_member = false;
+        let ws = builder.project("/ws");
+
+        let roots = ws
+            .to_roots()
+            .into_iter()
+            .map(|root| (root.path().clone(), root.is_member()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            roots,
+            vec![
+                (PathBuf::from("/ws/crates/a"), true),
+                (PathBuf::from("/ws/crates/b"), true),
+                (PathBuf::from("/registry/dep"), false),
+            ]
+        );
+
+        let mut files = Vec::new();
+        let WorkspaceCrateGraph { crate_graph, crate_roots, .. } =
+            ws.to_crate_graph(&CfgOptions::default(), &Env::default(), &mut |path: &Path| {
+                files.push(path.to_path_buf());
+                Some(FileId(files.len() as u32 - 1))
+            });
+        for (root, pkg_root) in &[
+            ("/ws/crates/a/src/lib.rs", "/ws/crates/a"),
+            ("/ws/crates/b/src/lib.rs", "/ws/crates/b"),
+        ] {
+            let file_id = files.iter().position(|it| it == Path::new(root)).unwrap();
+            let krate = crate_graph.crate_id_for_crate_root(FileId(file_id as u32)).unwrap();
+            let crate_root = &crate_roots[&krate];
+            assert_eq!(crate_root.path(), &PathBuf::from(pkg_root));
+            assert!(crate_root.is_member());
+        }
+
+        assert_eq!(
+            ws.workspace_root_for(Path::new("/ws/crates/b/src/lib.rs")),
+            Some(Path::new("/ws"))
+        );
+        assert_eq!(ws.workspace_root_for(Path::new("/registry/dep/src/lib.rs")), None);
+    }
+
+    #[test]
+    fn cargo_args_for_features() {
+        let args = |no_default_features: bool, all_features: bool, features: &[&str]| {
+            let features = features.iter().map(|it| it.to_string()).collect();
+            CargoFeatures { no_default_features, all_features, features }.cargo_args()
+        };
+
+        assert_eq!(args(false, false, &[]), Vec::<String>::new());
+        assert_eq!(args(false, true, &[]), ["--all-features"]);
+        assert_eq!(args(true, false, &[]), ["--no-default-features"]);
+        assert_eq!(args(true, true, &[]), ["--all-features", "--no-default-features"]);
+        // Explicit features are added to the default ones
+        assert_eq!(args(false, false, &["a", "b"]), ["--features", "a b"]);
+        assert_eq!(args(true, false, &["a"]), ["--no-default-features", "--features", "a"]);
+        // `features` are ignored when every feature is activated anyway
+        assert_eq!(args(false, true, &["a"]), ["--all-features"]);
+        assert_eq!(args(true, true, &["a"]), ["--all-features", "--no-default-features"]);
+    }
+
+    #[test]
+    fn extra_env_is_added_to_crates() {
+        let mut builder = WorkspaceBuilder::default();
+        builder.lib("/pkg");
+
+        let mut extra_env = Env::default();
+        extra_env.set("CARGO_PKG_VERSION", "0.1.0".to_string());
+        let crate_graph = builder
+            .project("/")
+            .to_crate_graph(&CfgOptions::default(), &extra_env, &mut |_: &Path| Some(FileId(0)))
+            .crate_graph;
+
+        let krate = crate_graph.crate_id_for_crate_root(FileId(0)).unwrap();
+        assert_eq!(crate_graph.env(krate).get("CARGO_PKG_VERSION"), Some("0.1.0"));
+        assert_eq!(crate_graph.env(krate).get("CARGO_PKG_NAME"), None);
+    }
+
+    #[test]
+    fn enabled_features_are_kept_in_cfg_options() {
+        let mut builder = WorkspaceBuilder::default();
+        let pkg = builder.lib("/pkg");
+        builder.packages[pkg].features = vec!["serde".to_string()];
+        let crate_graph = builder
+            .project("/")
+            .to_crate_graph(&CfgOptions::default(), &Env::default(), &mut |_: &Path| {
+                Some(FileId(0))
+            })
+            .crate_graph;
+
+        let krate = crate_graph.crate_id_for_crate_root(FileId(0)).unwrap();
+        let cfg_options = crate_graph.cfg_options(krate);
+        let feature = |name: &str| CfgExpr::KeyValue { key: "feature".into(), value: name.into() };
+        assert_eq!(cfg_options.check(&feature("serde")), Some(true));
+        assert_eq!(cfg_options.check(&feature("std")), Some(false));
+    }
+
+    #[test]
+    fn cargo_features_match_crate_cfgs() {
+        let mut builder = WorkspaceBuilder::default();
+        let foo = builder.lib("/foo");
+        builder.packages[foo].features = vec!["default".to_string(), "std".to_string()];
+        let bar = builder.lib("/bar");
+        let ws = builder.project("/");
+
+        let features = ws.cargo_features();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features[&foo], ["default", "std"]);
+        assert!(features[&bar].is_empty());
+
+        let crate_graph = ws
+            .to_crate_graph(&CfgOptions::default(), &Env::default(), &mut |path: &Path| {
+                Some(FileId(if path.starts_with("/foo") { 0 } else { 1 }))
+            })
+            .crate_graph;
+        let krate = crate_graph.crate_id_for_crate_root(FileId(0)).unwrap();
+        let feature = |name: &str| CfgExpr::KeyValue { key: "feature".into(), value: name.into() };
+        for name in &features[&foo] {
+            assert_eq!(crate_graph.cfg_options(krate).check(&feature(name)), Some(true));
+        }
+    }
 }
@@ -5,8 +5,10 @@ mod json_project;
 mod sysroot;
 
 use std::{
+    collections::BTreeSet,
+    env,
     error::Error,
-    fs::File,
+    fs::{self, File},
     io::BufReader,
     path::{Path, PathBuf},
     process::Command,
@@ -19,7 +21,9 @@ use rustc_hash::FxHashMap;
 use serde_json::from_reader;
 
 pub use crate::{
-    cargo_workspace::{CargoFeatures, CargoWorkspace, Package, Target, TargetKind},
+    cargo_workspace::{
+        CargoFeatures, CargoWorkspace, DepKind, Package, PackageDependency, Target, TargetKind,
+    },
     json_project::JsonProject,
     sysroot::Sysroot,
 };
@@ -68,6 +72,16 @@ impl PackageRoot {
     }
 }
 
+/// The crate graph of a `ProjectWorkspace`, built by `to_crate_graph`, with
+/// what the workspace knows about its crates.
+pub struct WorkspaceCrateGraph {
+    pub crate_graph: CrateGraph,
+    /// The debug names of the crates.
+    pub crate_names: FxHashMap<CrateId, String>,
+    /// The `PackageRoot` each crate belongs to.
+    pub crate_roots: FxHashMap<CrateId, PackageRoot>,
+}
+
 impl ProjectWorkspace {
     pub fn discover(path: &Path, cargo_features: &CargoFeatures) -> Result<ProjectWorkspace> {
         ProjectWorkspace::discover_with_sysroot(path, true, cargo_features)
@@ -93,15 +107,21 @@ impl ProjectWorkspace {
                 let cargo_toml = find_cargo_toml(path).with_context(|| {
                     format!("Failed to find Cargo.toml for path {}", path.display())
                 })?;
-                let cargo = CargoWorkspace::from_cargo_metadata(&cargo_toml, cargo_features)
-                    .with_context(|| {
-                        format!(
-                            "Failed to read Cargo metadata from Cargo.toml file {}",
-                            cargo_toml.display()
-                        )
-                    })?;
+                let target = cargo_config_target(&cargo_toml);
+                let cargo = CargoWorkspace::from_cargo_metadata(
+                    &cargo_toml,
+                    cargo_features,
+                    target.as_deref(),
+                )
+                .with_context(|| {
+                    format!(
+                        "Failed to read Cargo metadata from Cargo.toml file {}",
+                        cargo_toml.display()
+                    )
+                })?;
                 let sysroot = if with_sysroot {
-                    Sysroot::discover(&cargo_toml).with_context(|| {
+                    let build_std = cargo_config_build_std(&cargo_toml);
+                    Sysroot::discover(&cargo_toml, build_std).with_context(|| {
                         format!(
                             "Failed to find sysroot for Cargo.toml file {}",
                             cargo_toml.display()
@@ -142,6 +162,38 @@ impl ProjectWorkspace {
         }
     }
 
+    /// Returns the target triple configured for this workspace, `None` if it
+    /// is built for the host.
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            ProjectWorkspace::Cargo { cargo, .. } => cargo.target(),
+            ProjectWorkspace::Json { .. } => None,
+        }
+    }
+
+    /// Returns the sysroot of a Cargo workspace. `rust-project.json` projects
+    /// list the sysroot crates explicitly, so they don't have one.
+    pub fn sysroot(&self) -> Option<&Sysroot> {
+        match self {
+            ProjectWorkspace::Cargo { sysroot, .. } => Some(sysroot),
+            ProjectWorkspace::Json { .. } => None,
+        }
+    }
+
+    /// Returns the features enabled for each package of a Cargo workspace, as
+    /// resolved by `cargo metadata` for the `CargoFeatures` passed to
+    /// `discover`. These are the features that end up in the `feature` cfgs of
+    /// the package's crates. `rust-project.json` projects specify their cfgs
+    /// directly, so the map is empty for them.
+    pub fn cargo_features(&self) -> FxHashMap<Package, Vec<String>> {
+        match self {
+            ProjectWorkspace::Cargo { cargo, .. } => {
+                cargo.packages().map(|pkg| (pkg, pkg.features(cargo).to_vec())).collect()
+            }
+            ProjectWorkspace::Json { .. } => FxHashMap::default(),
+        }
+    }
+
     pub fn n_packages(&self) -> usize {
         match self {
             ProjectWorkspace::Json { project } => project.crates.len(),
@@ -151,13 +203,17 @@ impl ProjectWorkspace {
         }
     }
 
+    /// Lowers the workspace to a `CrateGraph`. Every crate gets the variables
+    /// from `extra_env` in its `Env`.
     pub fn to_crate_graph(
         &self,
         default_cfg_options: &CfgOptions,
+        extra_env: &Env,
         load: &mut dyn FnMut(&Path) -> Option<FileId>,
-    ) -> (CrateGraph, FxHashMap<CrateId, String>) {
+    ) -> WorkspaceCrateGraph {
         let mut crate_graph = CrateGraph::default();
         let mut names = FxHashMap::default();
+        let mut crate_roots = FxHashMap::default();
         match self {
             ProjectWorkspace::Json { project } => {
                 let mut crates = FxHashMap::default();
@@ -178,15 +234,23 @@ impl ProjectWorkspace {
                             }
                             opts
                         };
-                        crates.insert(
-                            crate_id,
-                            crate_graph.add_crate_root(
-                                file_id,
-                                edition,
-                                cfg_options,
-                                Env::default(),
-                            ),
+                        let graph_crate_id = crate_graph.add_crate_root(
+                            file_id,
+                            edition,
+                            cfg_options,
+                            extra_env.clone(),
                         );
+                        crates.insert(crate_id, graph_crate_id);
+                        // Roots may be nested, the crate belongs to the innermost one
+                        if let Some(root) = project
+                            .roots
+                            .iter()
+                            .filter(|root| krate.root_module.starts_with(&root.path))
+                            .max_by_key(|root| root.path.components().count())
+                        {
+                            crate_roots
+                                .insert(graph_crate_id, PackageRoot::new(root.path.clone(), true));
+                        }
                     }
                 }
 
@@ -213,7 +277,7 @@ impl ProjectWorkspace {
             }
             ProjectWorkspace::Cargo { cargo, sysroot } => {
                 let mut sysroot_crates = FxHashMap::default();
-                for krate in sysroot.crates() {
+                for (krate, edition) in sysroot.crates_with_edition() {
                     if let Some(file_id) = load(krate.root(&sysroot)) {
                         // Crates from sysroot have `cfg(test)` disabled
                         let cfg_options = {
@@ -224,12 +288,16 @@ impl ProjectWorkspace {
 
                         let crate_id = crate_graph.add_crate_root(
                             file_id,
-                            Edition::Edition2018,
+                            edition,
                             cfg_options,
-                            Env::default(),
+                            extra_env.clone(),
                         );
                         sysroot_crates.insert(krate, crate_id);
                         names.insert(crate_id, krate.name(&sysroot).to_string());
+                        crate_roots.insert(
+                            crate_id,
+                            PackageRoot::new(krate.root_dir(&sysroot).to_path_buf(), false),
+                        );
                     }
                 }
                 for from in sysroot.crates() {
@@ -270,9 +338,16 @@ impl ProjectWorkspace {
                                 file_id,
                                 edition,
                                 cfg_options,
-                                Env::default(),
+                                extra_env.clone(),
                             );
                             names.insert(crate_id, pkg.name(&cargo).to_string());
+                            crate_roots.insert(
+                                crate_id,
+                                PackageRoot::new(
+                                    pkg.root(&cargo).to_path_buf(),
+                                    pkg.is_member(&cargo),
+                                ),
+                            );
                             if tgt.kind(&cargo) == TargetKind::Lib {
                                 lib_tgt = Some(crate_id);
                                 pkg_to_lib_crate.insert(pkg, crate_id);
@@ -295,14 +370,19 @@ impl ProjectWorkspace {
                                 }
                             }
 
-                            pkg_crates.entry(pkg).or_insert_with(Vec::new).push(crate_id);
+                            pkg_crates
+                                .entry(pkg)
+                                .or_insert_with(Vec::new)
+                                .push((crate_id, tgt.kind(&cargo)));
                         }
                     }
 
                     // Set deps to the core, std and to the lib target of the current package
-                    for &from in pkg_crates.get(&pkg).into_iter().flatten() {
+                    for &(from, kind) in pkg_crates.get(&pkg).into_iter().flatten() {
                         if let Some(to) = lib_tgt {
+                            // Build scripts are built before, and can't use, the library
                             if to != from
+                                && kind != TargetKind::BuildScript
                                 && crate_graph
                                     .add_dep(
                                         from,
@@ -352,11 +432,15 @@ impl ProjectWorkspace {
                 }
 
                 // Now add a dep edge from all targets of upstream to the lib
-                // target of downstream.
+                // target of downstream. Which dependencies a target can use,
+                // and under which name, depends on the kind of the target.
                 for pkg in cargo.packages() {
                     for dep in pkg.dependencies(&cargo) {
                         if let Some(&to) = pkg_to_lib_crate.get(&dep.pkg) {
-                            for &from in pkg_crates.get(&pkg).into_iter().flatten() {
+                            for &(from, kind) in pkg_crates.get(&pkg).into_iter().flatten() {
+                                if !dep.is_used_by(kind) {
+                                    continue;
+                                }
                                 if crate_graph
                                     .add_dep(from, CrateName::new(&dep.name).unwrap(), to)
                                     .is_err()
@@ -373,7 +457,7 @@ impl ProjectWorkspace {
                 }
             }
         }
-        (crate_graph, names)
+        WorkspaceCrateGraph { crate_graph, crate_names: names, crate_roots }
     }
 
     pub fn workspace_root_for(&self, path: &Path) -> Option<&Path> {
@@ -387,6 +471,104 @@ impl ProjectWorkspace {
                 .map(|root| root.path.as_ref()),
         }
     }
+
+    /// Checks whether `path` belongs to a dependency or the sysroot rather than
+    /// to a member of the workspace. If package roots are nested, the innermost
+    /// one decides. Files outside of all roots aren't considered library files.
+    pub fn is_library_file(&self, path: &Path) -> bool {
+        self.to_roots()
+            .into_iter()
+            .filter(|root| path.starts_with(root.path()))
+            .max_by_key(|root| root.path().components().count())
+            .map_or(false, |root| !root.is_member())
+    }
+
+    /// Finds the package that the crate `from` refers to as `dep_name`, taking
+    /// renamed dependencies into account. The workspace doesn't know about
+    /// `CrateId`s, so `crate_root` has to map them back to their root file.
+    pub fn resolve_dep(
+        &self,
+        from: CrateId,
+        dep_name: &str,
+        crate_root: &dyn Fn(CrateId) -> Option<PathBuf>,
+    ) -> Option<Package> {
+        match self {
+            ProjectWorkspace::Cargo { cargo, .. } => {
+                let tgt = cargo.target_by_root(&crate_root(from)?)?;
+                let dep_name = dep_name.replace('-', "_");
+                tgt.package(cargo)
+                    .dependencies(cargo)
+                    .find(|dep| dep.name == dep_name && dep.is_used_by(tgt.kind(cargo)))
+                    .map(|dep| dep.pkg)
+            }
+            ProjectWorkspace::Json { .. } => None,
+        }
+    }
+
+    /// Computes which packages and dependency edges were added or removed
+    /// between `self` and `other`, e.g. after `Cargo.toml` was edited.
+    pub fn diff(&self, other: &ProjectWorkspace) -> WorkspaceDiff {
+        let (old_packages, old_deps) = self.package_graph();
+        let (new_packages, new_deps) = other.package_graph();
+        WorkspaceDiff {
+            added_packages: new_packages.difference(&old_packages).cloned().collect(),
+            removed_packages: old_packages.difference(&new_packages).cloned().collect(),
+            added_deps: new_deps.difference(&old_deps).cloned().collect(),
+            removed_deps: old_deps.difference(&new_deps).cloned().collect(),
+        }
+    }
+
+    /// Packages are keyed by manifest path for Cargo workspaces, as several
+    /// packages can share a name, and by root module for `rust-project.json`.
+    /// The sysroot is ignored, as it doesn't depend on the project's manifests.
+    fn package_graph(&self) -> (BTreeSet<String>, BTreeSet<(String, String)>) {
+        let mut packages = BTreeSet::new();
+        let mut deps = BTreeSet::new();
+        match self {
+            ProjectWorkspace::Cargo { cargo, .. } => {
+                let key = |pkg: Package| pkg.manifest(cargo).display().to_string();
+                for pkg in cargo.packages() {
+                    packages.insert(key(pkg));
+                    for dep in pkg.dependencies(cargo) {
+                        deps.insert((key(pkg), key(dep.pkg)));
+                    }
+                }
+            }
+            ProjectWorkspace::Json { project } => {
+                let key = |krate: &json_project::Crate| krate.root_module.display().to_string();
+                for krate in project.crates.iter() {
+                    packages.insert(key(krate));
+                    for dep in krate.deps.iter() {
+                        if let Some(to) = project.crates.get(dep.krate.0) {
+                            deps.insert((key(krate), key(to)));
+                        }
+                    }
+                }
+            }
+        }
+        (packages, deps)
+    }
+}
+
+/// The result of `ProjectWorkspace::diff`. Packages are identified by their
+/// manifest path, or their root module for `rust-project.json`. All lists are
+/// sorted.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct WorkspaceDiff {
+    pub added_packages: Vec<String>,
+    pub removed_packages: Vec<String>,
+    /// Dependency edges, as `(package, dependency)` pairs.
+    pub added_deps: Vec<(String, String)>,
+    pub removed_deps: Vec<(String, String)>,
+}
+
+impl WorkspaceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added_packages.is_empty()
+            && self.removed_packages.is_empty()
+            && self.added_deps.is_empty()
+            && self.removed_deps.is_empty()
+    }
 }
 
 fn find_rust_project_json(path: &Path) -> Option<PathBuf> {
@@ -421,7 +603,66 @@ fn find_cargo_toml(path: &Path) -> Result<PathBuf> {
     Err(CargoTomlNotFoundError(path.to_path_buf()).into())
 }
 
-pub fn get_rustc_cfg_options() -> CfgOptions {
+/// Returns the default target triple configured for the package at
+/// `cargo_toml`, via `CARGO_BUILD_TARGET` or `build.target` in a
+/// `.cargo/config.toml` (or `.cargo/config`) in one of its ancestor
+/// directories. `None` means the host target.
+pub fn cargo_config_target(cargo_toml: &Path) -> Option<String> {
+    if let Ok(target) = env::var("CARGO_BUILD_TARGET") {
+        return Some(target);
+    }
+    find_in_cargo_configs(cargo_toml, parse_build_target)
+}
+
+/// Checks whether the package at `cargo_toml` is built with `-Z build-std`,
+/// via `CARGO_UNSTABLE_BUILD_STD` or `unstable.build-std` in a cargo config
+/// file in one of its ancestor directories.
+pub fn cargo_config_build_std(cargo_toml: &Path) -> bool {
+    if env::var_os("CARGO_UNSTABLE_BUILD_STD").is_some() {
+        return true;
+    }
+    find_in_cargo_configs(cargo_toml, parse_build_std).unwrap_or(false)
+}
+
+/// Looks through the `.cargo/config.toml` (or `.cargo/config`) files from the
+/// directory of `cargo_toml` upwards and returns the first value `parse` finds.
+fn find_in_cargo_configs<T>(cargo_toml: &Path, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+    let mut curr = cargo_toml.parent();
+    while let Some(dir) = curr {
+        for name in &["config.toml", "config"] {
+            let config = dir.join(".cargo").join(name);
+            if let Some(it) = fs::read_to_string(&config).ok().and_then(|it| parse(&it)) {
+                return Some(it);
+            }
+        }
+        curr = dir.parent();
+    }
+    None
+}
+
+/// Extracts `build.target` from the text of a cargo config file.
+fn parse_build_target(config: &str) -> Option<String> {
+    let config: toml::Value = config.parse().ok()?;
+    config.get("build")?.get("target")?.as_str().map(|it| it.to_string())
+}
+
+/// Checks the text of a cargo config file for `unstable.build-std`. It lists
+/// the crates to build, so any value enables it.
+fn parse_build_std(config: &str) -> Option<bool> {
+    let config: toml::Value = config.parse().ok()?;
+    config.get("unstable")?.get("build-std").map(|_| true)
+}
+
+fn rustc_cfg_args(target: Option<&str>) -> Vec<&str> {
+    let mut args = vec!["--print", "cfg", "-O"];
+    if let Some(target) = target {
+        args.push("--target");
+        args.push(target);
+    }
+    args
+}
+
+pub fn get_rustc_cfg_options(target: Option<&str>) -> CfgOptions {
     let mut cfg_options = CfgOptions::default();
 
     // Some nightly-only cfgs, which are required for stdlib
@@ -436,13 +677,15 @@ pub fn get_rustc_cfg_options() -> CfgOptions {
 
     match (|| -> Result<String> {
         // `cfg(test)` and `cfg(debug_assertion)` are handled outside, so we suppress them here.
+        let args = rustc_cfg_args(target);
         let output = Command::new("rustc")
-            .args(&["--print", "cfg", "-O"])
+            .args(&args)
             .output()
-            .context("Failed to get output from rustc --print cfg -O")?;
+            .with_context(|| format!("Failed to get output from rustc {}", args.join(" ")))?;
         if !output.status.success() {
             bail!(
-                "rustc --print cfg -O exited with exit code ({})",
+                "rustc {} exited with exit code ({})",
+                args.join(" "),
                 output
                     .status
                     .code()
@@ -451,20 +694,152 @@ pub fn get_rustc_cfg_options() -> CfgOptions {
         }
         Ok(String::from_utf8(output.stdout)?)
     })() {
-        Ok(rustc_cfgs) => {
-            for line in rustc_cfgs.lines() {
-                match line.find('=') {
-                    None => cfg_options.insert_atom(line.into()),
-                    Some(pos) => {
-                        let key = &line[..pos];
-                        let value = line[pos + 1..].trim_matches('"');
-                        cfg_options.insert_key_value(key.into(), value.into());
-                    }
-                }
-            }
-        }
+        Ok(rustc_cfgs) => add_rustc_cfgs(&mut cfg_options, &rustc_cfgs),
         Err(e) => log::error!("failed to get rustc cfgs: {}", e),
     }
 
     cfg_options
 }
+
+fn add_rustc_cfgs(cfg_options: &mut CfgOptions, rustc_cfgs: &str) {
+    for line in rustc_cfgs.lines() {
+        match line.find('=') {
+            None => cfg_options.insert_atom(line.into()),
+            Some(pos) => {
+                let key = &line[..pos];
+                let value = line[pos + 1..].trim_matches('"');
+                cfg_options.insert_key_value(key.into(), value.into());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ra_cfg::CfgExpr;
+
+    use super::*;
+
+    #[test]
+    fn build_target_is_read_from_cargo_config() {
+        assert_eq!(parse_build_target(""), None);
+        assert_eq!(
+            parse_build_target(
+                r#"
+[target.thumbv7em-none-eabihf]
+runner = "probe-run"
+
+[build]
+# cross-compile by default
+target = "thumbv7em-none-eabihf" # Cortex-M4F
+"#
+            ),
+            Some("thumbv7em-none-eabihf".to_string())
+        );
+        assert_eq!(
+            parse_build_target(r#"build.target = "wasm32-unknown-unknown""#),
+            Some("wasm32-unknown-unknown".to_string())
+        );
+        assert_eq!(parse_build_target("[target.x86_64-pc-windows-gnu]\nlinker = \"gcc\""), None);
+    }
+
+    #[test]
+    fn build_std_is_read_from_cargo_config() {
+        assert_eq!(parse_build_std(""), None);
+        assert_eq!(parse_build_std("[unstable]\nbuild-std = [\"core\", \"alloc\"]\n"), Some(true));
+        assert_eq!(parse_build_std(r#"unstable.build-std = ["core"]"#), Some(true));
+        assert_eq!(parse_build_std("[build]\nbuild-std = true\n"), None);
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let cargo_toml = tmp_dir.path().join("krate/Cargo.toml");
+        fs::create_dir_all(cargo_toml.parent().unwrap()).unwrap();
+        let config = tmp_dir.path().join(".cargo/config.toml");
+        fs::create_dir_all(config.parent().unwrap()).unwrap();
+        fs::write(&config, "[unstable]\nbuild-std = [\"core\"]\n").unwrap();
+        assert_eq!(find_in_cargo_configs(&cargo_toml, parse_build_std), Some(true));
+    }
+
+    #[test]
+    fn workspace_sysroot() {
+        let project: JsonProject =
+            serde_json::from_str(r#"{ "roots": [], "crates": [] }"#).unwrap();
+        assert!(ProjectWorkspace::Json { project }.sysroot().is_none());
+
+        if test_utils::skip_slow_tests() {
+            return;
+        }
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let workspace = ProjectWorkspace::discover(&manifest, &CargoFeatures::default()).unwrap();
+        let sysroot = workspace.sysroot().unwrap();
+        assert!(sysroot.core().is_some());
+        assert!(sysroot.std().is_some());
+    }
+
+    #[test]
+    fn json_crates_belong_to_their_innermost_root() {
+        let project: JsonProject = serde_json::from_str(
+            r#"{
+                "roots": ["/project", "/project/vendor/dep"],
+                "crates": [
+                    {
+                        "root_module": "/project/vendor/dep/src/lib.rs",
+                        "edition": "2018",
+                        "deps": [],
+                        "atom_cfgs": [],
+                        "key_value_cfgs": {}
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let workspace = ProjectWorkspace::Json { project };
+
+        let WorkspaceCrateGraph { crate_graph, crate_roots, .. } =
+            workspace.to_crate_graph(&CfgOptions::default(), &Env::default(), &mut |_: &Path| {
+                Some(FileId(0))
+            });
+        let krate = crate_graph.crate_id_for_crate_root(FileId(0)).unwrap();
+        assert_eq!(crate_roots[&krate].path(), Path::new("/project/vendor/dep"));
+    }
+
+    #[test]
+    fn library_files() {
+        let project: JsonProject =
+            serde_json::from_str(r#"{ "roots": ["/project"], "crates": [] }"#).unwrap();
+        let workspace = ProjectWorkspace::Json { project };
+        assert!(!workspace.is_library_file(Path::new("/project/src/lib.rs")));
+        assert!(!workspace.is_library_file(Path::new("/elsewhere/lib.rs")));
+
+        if test_utils::skip_slow_tests() {
+            return;
+        }
+        let manifest = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.toml");
+        let workspace = ProjectWorkspace::discover(&manifest, &CargoFeatures::default()).unwrap();
+        let member_file = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/lib.rs");
+        assert!(!workspace.is_library_file(&member_file));
+        let sysroot = workspace.sysroot().unwrap();
+        let core = sysroot.core().unwrap().root(sysroot);
+        assert!(workspace.is_library_file(core));
+    }
+
+    #[test]
+    fn target_is_forwarded_to_rustc() {
+        assert_eq!(rustc_cfg_args(None), vec!["--print", "cfg", "-O"]);
+        assert_eq!(
+            rustc_cfg_args(Some("x86_64-pc-windows-msvc")),
+            vec!["--print", "cfg", "-O", "--target", "x86_64-pc-windows-msvc"]
+        );
+
+        // Stubbed output of `rustc --print cfg --target x86_64-pc-windows-msvc`
+        let mut cfg_options = CfgOptions::default();
+        add_rustc_cfgs(
+            &mut cfg_options,
+            "target_os=\"windows\"\ntarget_family=\"windows\"\nwindows",
+        );
+        let target_os = |os: &str| CfgExpr::KeyValue { key: "target_os".into(), value: os.into() };
+        assert_eq!(cfg_options.check(&target_os("windows")), Some(true));
+        assert_eq!(cfg_options.check(&target_os("linux")), Some(false));
+        assert_eq!(cfg_options.check(&CfgExpr::Atom("windows".into())), Some(true));
+        assert_eq!(cfg_options.check(&CfgExpr::Atom("unix".into())), Some(false));
+    }
+}
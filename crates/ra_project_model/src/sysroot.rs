@@ -2,12 +2,13 @@
 
 use anyhow::{bail, Context, Result};
 use std::{
-    env,
+    env, fs,
     path::{Path, PathBuf},
     process::{Command, Output},
 };
 
 use ra_arena::{impl_arena_id, Arena, RawId};
+use ra_db::Edition;
 
 #[derive(Default, Debug, Clone)]
 pub struct Sysroot {
@@ -22,6 +23,7 @@ impl_arena_id!(SysrootCrate);
 struct SysrootCrateData {
     name: String,
     root: PathBuf,
+    edition: Edition,
     deps: Vec<SysrootCrate>,
 }
 
@@ -46,15 +48,31 @@ impl Sysroot {
         self.crates.iter().map(|(id, _data)| id)
     }
 
-    pub fn discover(cargo_toml: &Path) -> Result<Sysroot> {
-        let src = get_or_install_rust_src(cargo_toml)?;
+    /// Like `crates`, but also returns the edition each crate is written in.
+    pub fn crates_with_edition<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (SysrootCrate, Edition)> + ExactSizeIterator + 'a {
+        self.crates.iter().map(|(id, data)| (id, data.edition))
+    }
+
+    /// With `build_std`, cargo compiles the standard library from the
+    /// toolchain's rust-src component, so those sources are loaded even if
+    /// `RUST_SRC_PATH` points elsewhere.
+    pub fn discover(cargo_toml: &Path, build_std: bool) -> Result<Sysroot> {
+        let src = get_or_install_rust_src(cargo_toml, build_std)?;
+        Ok(Sysroot::load(&src))
+    }
+
+    fn load(src: &Path) -> Sysroot {
         let mut sysroot = Sysroot { crates: Arena::default() };
         for name in SYSROOT_CRATES.trim().lines() {
-            let root = src.join(format!("lib{}", name)).join("lib.rs");
+            let dir = src.join(format!("lib{}", name));
+            let root = dir.join("lib.rs");
             if root.exists() {
                 sysroot.crates.alloc(SysrootCrateData {
                     name: name.into(),
                     root,
+                    edition: manifest_edition(&dir.join("Cargo.toml")),
                     deps: Vec::new(),
                 });
             }
@@ -71,7 +89,7 @@ impl Sysroot {
                 sysroot.crates[alloc].deps.push(core);
             }
         }
-        Ok(sysroot)
+        sysroot
     }
 
     fn by_name(&self, name: &str) -> Option<SysrootCrate> {
@@ -79,6 +97,27 @@ impl Sysroot {
     }
 }
 
+/// Reads the `package.edition` key from a sysroot crate's manifest. The
+/// sysroot crates are written in 2018 edition nowadays, so that's what we
+/// assume if the manifest can't be read.
+fn manifest_edition(cargo_toml: &Path) -> Edition {
+    let text = match fs::read_to_string(cargo_toml) {
+        Ok(text) => text,
+        Err(_) => return Edition::Edition2018,
+    };
+    let manifest: toml::Value = match text.parse() {
+        Ok(manifest) => manifest,
+        Err(_) => return Edition::Edition2018,
+    };
+    match manifest.get("package").and_then(|it| it.get("edition")) {
+        Some(edition) => {
+            edition.as_str().and_then(|it| it.parse().ok()).unwrap_or(Edition::Edition2018)
+        }
+        // Cargo's default when no edition is specified
+        None => Edition::Edition2015,
+    }
+}
+
 fn create_command_text(program: &str, args: &[&str]) -> String {
     format!("{} {}", program, args.join(" "))
 }
@@ -105,10 +144,29 @@ fn run_command_in_cargo_dir(cargo_toml: &Path, program: &str, args: &[&str]) ->
     Ok(output)
 }
 
-fn get_or_install_rust_src(cargo_toml: &Path) -> Result<PathBuf> {
-    if let Ok(path) = env::var("RUST_SRC_PATH") {
-        return Ok(path.into());
+fn get_or_install_rust_src(cargo_toml: &Path, build_std: bool) -> Result<PathBuf> {
+    let env_path = env::var_os("RUST_SRC_PATH").map(PathBuf::from);
+    select_rust_src(env_path, build_std, || rust_src_component(cargo_toml))
+}
+
+/// Picks between the `RUST_SRC_PATH` override and the rust-src component of
+/// the toolchain, which `build-std` compiles the standard library from.
+fn select_rust_src(
+    env_path: Option<PathBuf>,
+    build_std: bool,
+    rust_src_component: impl FnOnce() -> Result<PathBuf>,
+) -> Result<PathBuf> {
+    match env_path {
+        Some(path) if build_std => {
+            log::info!("ignoring RUST_SRC_PATH={}, build-std is enabled", path.display());
+            rust_src_component()
+        }
+        Some(path) => Ok(path),
+        None => rust_src_component(),
     }
+}
+
+fn rust_src_component(cargo_toml: &Path) -> Result<PathBuf> {
     let rustc_output = run_command_in_cargo_dir(cargo_toml, "rustc", &["--print", "sysroot"])?;
     let stdout = String::from_utf8(rustc_output.stdout)?;
     let sysroot_path = Path::new(stdout.trim());
@@ -185,3 +243,57 @@ rustc_lsan
 rustc_msan
 rustc_tsan
 build_helper";
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn build_std_uses_rust_src_component() {
+        // Stubs `rustc --print sysroot` and the rust-src component in it
+        let component = PathBuf::from("/toolchain/lib/rustlib/src/rust/src");
+        let env_path = Some(PathBuf::from("/checkout/src"));
+        let select = |env_path: Option<PathBuf>, build_std: bool| {
+            select_rust_src(env_path, build_std, || Ok(component.clone())).unwrap()
+        };
+
+        assert_eq!(select(env_path.clone(), false), PathBuf::from("/checkout/src"));
+        assert_eq!(select(env_path, true), component);
+        assert_eq!(select(None, false), component);
+        assert_eq!(select(None, true), component);
+    }
+
+    #[test]
+    fn sysroot_crate_editions() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let src = tmp_dir.path();
+        let add_crate = |name: &str, manifest: Option<&str>| {
+            let dir = src.join(format!("lib{}", name));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(dir.join("lib.rs"), "").unwrap();
+            if let Some(manifest) = manifest {
+                fs::write(dir.join("Cargo.toml"), manifest).unwrap();
+            }
+        };
+        add_crate("core", Some("[package]\nname = \"core\"\nedition = \"2018\"\n"));
+        add_crate("alloc", Some("[package]\nname = \"alloc\"\n\n[lib]\nedition = \"2018\"\n"));
+        add_crate("std", None);
+
+        let sysroot = Sysroot::load(src);
+
+        let editions = sysroot
+            .crates_with_edition()
+            .map(|(krate, edition)| (krate.name(&sysroot).to_string(), edition))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            editions,
+            vec![
+                ("std".to_string(), Edition::Edition2018),
+                ("core".to_string(), Edition::Edition2018),
+                ("alloc".to_string(), Edition::Edition2015),
+            ]
+        );
+    }
+}
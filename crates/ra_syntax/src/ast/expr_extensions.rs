@@ -362,6 +362,25 @@ impl ast::Literal {
             _ => unreachable!(),
         }
     }
+
+    /// The value of an integer literal, if it fits into a `u64`.
+    pub fn int_value(&self) -> Option<u64> {
+        let suffix = match self.kind() {
+            LiteralKind::IntNumber { suffix } => suffix,
+            _ => return None,
+        };
+        let token = self.token();
+        let text = token.text();
+        let text = &text[..text.len() - suffix.map_or(0, |it| it.len())];
+        let text = text.replace('_', "");
+        let (radix, digits) = match text.get(..2) {
+            Some("0x") => (16, &text[2..]),
+            Some("0o") => (8, &text[2..]),
+            Some("0b") => (2, &text[2..]),
+            _ => (10, &text[..]),
+        };
+        u64::from_str_radix(digits, radix).ok()
+    }
 }
 
 impl ast::BlockExpr {
@@ -382,6 +401,11 @@ impl ast::BlockExpr {
             _ => true,
         }
     }
+
+    /// true for `unsafe { ... }` blocks.
+    pub fn is_unsafe(&self) -> bool {
+        self.syntax().children_with_tokens().any(|it| it.kind() == T![unsafe])
+    }
 }
 
 #[test]
@@ -2498,6 +2498,9 @@ impl TypeParamList {
     pub fn type_params(&self) -> AstChildren<TypeParam> {
         AstChildren::new(&self.syntax)
     }
+    pub fn const_params(&self) -> AstChildren<ConstParam> {
+        AstChildren::new(&self.syntax)
+    }
     pub fn lifetime_params(&self) -> AstChildren<LifetimeParam> {
         AstChildren::new(&self.syntax)
     }
@@ -80,6 +80,16 @@ pub fn expr_match(expr: ast::Expr, match_arm_list: ast::MatchArmList) -> ast::Ex
 pub fn expr_if(condition: ast::Expr, then_branch: ast::BlockExpr) -> ast::Expr {
     expr_from_text(&format!("if {} {}", condition.syntax(), then_branch.syntax()))
 }
+pub fn expr_literal(text: &str) -> ast::Expr {
+    expr_from_text(text)
+}
+pub fn expr_ref(expr: ast::Expr, exclusive: bool) -> ast::Expr {
+    expr_from_text(&if exclusive {
+        format!("&mut {}", expr.syntax())
+    } else {
+        format!("&{}", expr.syntax())
+    })
+}
 pub fn expr_prefix(op: SyntaxKind, expr: ast::Expr) -> ast::Expr {
     let token = token(op);
     expr_from_text(&format!("{}{}", token, expr.syntax()))
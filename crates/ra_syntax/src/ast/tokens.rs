@@ -1,8 +1,10 @@
 //! There are many AstNodes, but only a few tokens, so we hand-write them here.
 
+use rustc_lexer::unescape;
+
 use crate::{
     ast::AstToken,
-    SyntaxKind::{COMMENT, RAW_STRING, STRING, WHITESPACE},
+    SyntaxKind::{BYTE, BYTE_STRING, CHAR, COMMENT, RAW_STRING, STRING, WHITESPACE},
     SyntaxToken, TextRange, TextUnit,
 };
 
@@ -148,6 +150,109 @@ impl String {
     }
 }
 
+/// Returns the ranges of the valid escape sequences (like `\n` or `\u{1F600}`)
+/// in a string, byte string, char or byte literal. Raw strings have none.
+pub fn literal_escape_ranges(token: &SyntaxToken) -> Vec<TextRange> {
+    let text = token.text().as_str();
+    let (prefix_len, quote) = match token.kind() {
+        STRING => (1, '"'),
+        BYTE_STRING => (2, '"'),
+        CHAR => (1, '\''),
+        BYTE => (2, '\''),
+        _ => return Vec::new(),
+    };
+    let inside = match text.rfind(quote).and_then(|end| text.get(prefix_len..end)) {
+        Some(it) => it,
+        None => return Vec::new(),
+    };
+
+    let inside_start = token.text_range().start() + TextUnit::from_usize(prefix_len);
+    let mut res = Vec::new();
+    let mut push_escape = |range: std::ops::Range<usize>| {
+        if inside[range.clone()].starts_with('\\') {
+            res.push(TextRange::from_to(
+                inside_start + TextUnit::from_usize(range.start),
+                inside_start + TextUnit::from_usize(range.end),
+            ));
+        }
+    };
+    match token.kind() {
+        STRING => unescape::unescape_str(inside, &mut |range, c| {
+            if c.is_ok() {
+                push_escape(range)
+            }
+        }),
+        BYTE_STRING => unescape::unescape_byte_str(inside, &mut |range, c| {
+            if c.is_ok() {
+                push_escape(range)
+            }
+        }),
+        CHAR => {
+            if unescape::unescape_char(inside).is_ok() {
+                push_escape(0..inside.len())
+            }
+        }
+        BYTE => {
+            if unescape::unescape_byte(inside).is_ok() {
+                push_escape(0..inside.len())
+            }
+        }
+        _ => (),
+    }
+    res
+}
+
+/// Returns the ranges of the placeholders (like `{}`, `{0}` or `{name:>8}`) in
+/// a format string. Escaped braces (`{{` and `}}`) aren't placeholders.
+pub fn format_specifier_ranges(token: &SyntaxToken) -> Vec<TextRange> {
+    let text = token.text().as_str();
+    let inside_range = match token.kind() {
+        STRING | RAW_STRING => match find_usual_string_range(text) {
+            Some(it) => it,
+            None => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    let inside_start = inside_range.start().to_usize() + 1;
+    let inside = &text[inside_start..inside_range.end().to_usize()];
+
+    // Format strings are parsed after unescaping, so we look at the unescaped
+    // characters together with the source ranges they come from.
+    let mut chars: Vec<(std::ops::Range<usize>, char)> = Vec::new();
+    if token.kind() == STRING {
+        unescape::unescape_str(inside, &mut |range, c| {
+            if let Ok(c) = c {
+                chars.push((range, c));
+            }
+        });
+    } else {
+        chars.extend(inside.char_indices().map(|(idx, c)| (idx..idx + c.len_utf8(), c)));
+    }
+
+    let offset = token.text_range().start() + TextUnit::from_usize(inside_start);
+    let mut res = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let next = chars.get(idx + 1).map(|(_, c)| *c);
+        match (chars[idx].1, next) {
+            ('{', Some('{')) | ('}', Some('}')) => idx += 2,
+            ('{', _) => {
+                let len = match chars[idx..].iter().position(|(_, c)| *c == '}') {
+                    Some(it) => it,
+                    None => break,
+                };
+                res.push(TextRange::from_to(
+                    offset + TextUnit::from_usize(chars[idx].0.start),
+                    offset + TextUnit::from_usize(chars[idx + len].0.end),
+                ));
+                idx += len + 1;
+            }
+            _ => idx += 1,
+        }
+    }
+    res
+}
+
 pub struct RawString(SyntaxToken);
 
 impl AstToken for RawString {
@@ -49,12 +49,32 @@ impl CargoTargetSpec {
                     spec.push_to(&mut res);
                 }
                 res.push("--".to_string());
-                res.push(test_id.to_string());
+                // An empty id runs the whole bench target
+                let filter = test_id.to_string();
+                if !filter.is_empty() {
+                    res.push(filter);
+                }
                 if let TestId::Path(_) = test_id {
                     res.push("--exact".to_string());
                 }
                 res.push("--nocapture".to_string());
             }
+            RunnableKind::DocTest { test_id } => {
+                res.push("test".to_string());
+                res.push("--doc".to_string());
+                // `--doc` can't be combined with other target selection flags
+                if let Some(spec) = spec {
+                    res.push("--package".to_string());
+                    res.push(spec.package);
+                }
+                res.push("--".to_string());
+                // The docs of the crate root have an empty id, matching all doctests
+                let filter = test_id.to_string();
+                if !filter.is_empty() {
+                    res.push(filter);
+                }
+                res.push("--nocapture".to_string());
+            }
             RunnableKind::Bin => {
                 res.push("run".to_string());
                 if let Some(spec) = spec {
@@ -89,6 +109,12 @@ impl CargoTargetSpec {
         Ok(res)
     }
 
+    /// Rustdoc only runs the doctests of library targets. Without a Cargo
+    /// target we can't tell, so we assume there are doctests.
+    pub(crate) fn has_doc_tests(spec: Option<&CargoTargetSpec>) -> bool {
+        spec.map_or(true, |spec| spec.target_kind == TargetKind::Lib)
+    }
+
     pub(crate) fn push_to(self, buf: &mut Vec<String>) {
         buf.push("--package".to_string());
         buf.push(self.package);
@@ -112,7 +138,37 @@ impl CargoTargetSpec {
             TargetKind::Lib => {
                 buf.push("--lib".to_string());
             }
-            TargetKind::Other => (),
+            TargetKind::BuildScript | TargetKind::Other => (),
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_in_integration_test_target() {
+        let spec = CargoTargetSpec {
+            package: "foo".to_string(),
+            target: "integration".to_string(),
+            target_kind: TargetKind::Test,
+        };
+        let kind = RunnableKind::Test { test_id: TestId::Path("it_works".to_string()) };
+        let args = CargoTargetSpec::runnable_args(Some(spec), &kind).unwrap();
+        assert_eq!(
+            args,
+            vec![
+                "test",
+                "--package",
+                "foo",
+                "--test",
+                "integration",
+                "--",
+                "it_works",
+                "--exact",
+                "--nocapture"
+            ]
+        );
+    }
+}
@@ -144,13 +144,7 @@ pub fn analysis_stats(
             if let Ty::Unknown = ty {
                 num_exprs_unknown += 1;
             } else {
-                let mut is_partially_unknown = false;
-                ty.walk(&mut |ty| {
-                    if let Ty::Unknown = ty {
-                        is_partially_unknown = true;
-                    }
-                });
-                if is_partially_unknown {
+                if ty.contains_unknown() {
                     num_exprs_partially_unknown += 1;
                 }
             }
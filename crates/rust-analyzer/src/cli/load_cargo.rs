@@ -5,7 +5,7 @@ use std::path::Path;
 
 use anyhow::Result;
 use crossbeam_channel::{unbounded, Receiver};
-use ra_db::{CrateGraph, FileId, SourceRootId};
+use ra_db::{CrateGraph, Env, FileId, SourceRootId};
 use ra_ide::{AnalysisChange, AnalysisHost, FeatureFlags};
 use ra_project_model::{get_rustc_cfg_options, PackageRoot, ProjectWorkspace};
 use ra_vfs::{RootEntry, Vfs, VfsChange, VfsTask, Watch};
@@ -46,18 +46,19 @@ pub(crate) fn load_cargo(
 
     // FIXME: cfg options?
     let default_cfg_options = {
-        let mut opts = get_rustc_cfg_options();
+        let mut opts = get_rustc_cfg_options(ws.target());
         opts.insert_atom("test".into());
         opts.insert_atom("debug_assertion".into());
         opts
     };
 
-    let (crate_graph, _crate_names) =
-        ws.to_crate_graph(&default_cfg_options, &mut |path: &Path| {
+    let crate_graph = ws
+        .to_crate_graph(&default_cfg_options, &Env::default(), &mut |path: &Path| {
             let vfs_file = vfs.load(path);
             log::debug!("vfs file {:?} -> {:?}", path, vfs_file);
             vfs_file.map(vfs_file_to_id)
-        });
+        })
+        .crate_graph;
     log::debug!("crate graph: {:?}", crate_graph);
 
     let source_roots = roots
@@ -36,6 +36,7 @@ pub struct ServerConfig {
     pub cargo_watch_args: Vec<String>,
     pub cargo_watch_command: String,
     pub cargo_watch_all_targets: bool,
+    pub cargo_watch_collapse_duplicates: bool,
 
     /// For internal usage to make integrated tests faster.
     #[serde(deserialize_with = "nullable_bool_true")]
@@ -62,6 +63,7 @@ impl Default for ServerConfig {
             cargo_watch_args: Vec::new(),
             cargo_watch_command: "check".to_string(),
             cargo_watch_all_targets: true,
+            cargo_watch_collapse_duplicates: false,
             with_sysroot: true,
             feature_flags: FxHashMap::default(),
             cargo_features: Default::default(),
@@ -102,4 +104,24 @@ mod test {
             serde_json::from_str(r#"{"publishDecorations":null, "lruCapacity":null}"#).unwrap()
         );
     }
+
+    #[test]
+    fn deserialize_cargo_features() {
+        let config: ServerConfig = serde_json::from_str(
+            r#"{"cargoFeatures": {"noDefaultFeatures": true, "allFeatures": false, "features": ["foo", "bar"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.cargo_features,
+            CargoFeatures {
+                no_default_features: true,
+                all_features: false,
+                features: vec!["foo".to_string(), "bar".to_string()],
+            }
+        );
+        assert_eq!(
+            config.cargo_features.cargo_args(),
+            ["--no-default-features", "--features", "foo bar"]
+        );
+    }
 }
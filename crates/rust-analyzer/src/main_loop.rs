@@ -177,6 +177,7 @@ pub fn main_loop(
                     args: config.cargo_watch_args,
                     command: config.cargo_watch_command,
                     all_targets: config.cargo_watch_all_targets,
+                    collapse_duplicates: config.cargo_watch_collapse_duplicates,
                 },
                 rustfmt_args: config.rustfmt_args,
             }
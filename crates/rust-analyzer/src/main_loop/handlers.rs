@@ -20,8 +20,8 @@ use lsp_types::{
     TextEdit, WorkspaceEdit,
 };
 use ra_ide::{
-    AssistId, FileId, FilePosition, FileRange, Query, RangeInfo, Runnable, RunnableKind,
-    SearchScope,
+    AssistId, FileId, FilePosition, FileRange, FindAllRefsConfig, Query, RangeInfo, Runnable,
+    RunnableKind, SearchScope,
 };
 use ra_prof::profile;
 use ra_syntax::{AstNode, SyntaxKind, TextRange, TextUnit};
@@ -348,7 +348,20 @@ pub fn handle_runnables(
     let offset = params.position.map(|it| it.conv_with(&line_index));
     let mut res = Vec::new();
     let workspace_root = world.workspace_root_for(file_id);
-    for runnable in world.analysis().runnables(file_id)? {
+    let mut runnables = world.analysis().runnables(file_id)?;
+    for crate_id in world.analysis().crate_for(file_id)? {
+        if world.analysis().crate_root(crate_id)? == file_id {
+            runnables.extend(world.analysis().crate_runnable(crate_id)?);
+        }
+    }
+    let has_doc_tests =
+        CargoTargetSpec::has_doc_tests(CargoTargetSpec::for_file(&world, file_id)?.as_ref());
+    for runnable in runnables {
+        if let RunnableKind::DocTest { .. } = runnable.kind {
+            if !has_doc_tests {
+                continue;
+            }
+        }
         if let Some(offset) = offset {
             if !runnable.range.contains_inclusive(offset) {
                 continue;
@@ -520,6 +533,26 @@ pub fn handle_rename(world: WorldSnapshot, params: RenameParams) -> Result<Optio
         .into());
     }
 
+    let conflicts = world.analysis().rename_conflicts(position, &*params.new_name)?;
+    if !conflicts.is_empty() {
+        let mut message =
+            format!("Renaming to `{}` would clash with existing names at:", params.new_name);
+        for conflict in conflicts {
+            let file_id = conflict.file_range.file_id;
+            let line_index = world.analysis().file_line_index(file_id)?;
+            let line_col = line_index.line_col(conflict.file_range.range.start());
+            write!(
+                message,
+                "\n{}:{}:{}",
+                world.file_id_to_path(file_id).display(),
+                line_col.line + 1,
+                line_col.col_utf16 + 1
+            )
+            .unwrap();
+        }
+        return Err(LspError::new(ErrorCode::InvalidParams as i32, message).into());
+    }
+
     let optional_change = world.analysis().rename(position, &*params.new_name)?;
     let change = match optional_change {
         None => return Ok(None),
@@ -538,7 +571,7 @@ pub fn handle_references(
     let _p = profile("handle_references");
     let position = params.text_document_position.try_conv_with(&world)?;
 
-    let refs = match world.analysis().find_all_refs(position, None)? {
+    let refs = match world.analysis().find_all_refs(position, FindAllRefsConfig::default())? {
         None => return Ok(None),
         Some(refs) => refs,
     };
@@ -758,10 +791,18 @@ pub fn handle_code_lens(
     let mut lenses: Vec<CodeLens> = Default::default();
 
     // Gather runnables
+    let has_doc_tests =
+        CargoTargetSpec::has_doc_tests(CargoTargetSpec::for_file(&world, file_id)?.as_ref());
     for runnable in world.analysis().runnables(file_id)? {
+        if let RunnableKind::DocTest { .. } = runnable.kind {
+            if !has_doc_tests {
+                continue;
+            }
+        }
         let title = match &runnable.kind {
             RunnableKind::Test { .. } | RunnableKind::TestMod { .. } => "▶️\u{fe0e}Run Test",
             RunnableKind::Bench { .. } => "Run Bench",
+            RunnableKind::DocTest { .. } => "▶️\u{fe0e}Run Doctest",
             RunnableKind::Bin => "Run",
         }
         .to_string();
@@ -864,10 +905,13 @@ pub fn handle_document_highlight(
     let file_id = params.text_document.try_conv_with(&world)?;
     let line_index = world.analysis().file_line_index(file_id)?;
 
-    let refs = match world
-        .analysis()
-        .find_all_refs(params.try_conv_with(&world)?, Some(SearchScope::single_file(file_id)))?
-    {
+    let refs = match world.analysis().find_all_refs(
+        params.try_conv_with(&world)?,
+        FindAllRefsConfig {
+            search_scope: Some(SearchScope::single_file(file_id)),
+            ..Default::default()
+        },
+    )? {
         None => return Ok(None),
         Some(refs) => refs,
     };
@@ -927,8 +971,10 @@ fn to_lsp_runnable(
     let line_index = world.analysis().file_line_index(file_id)?;
     let label = match &runnable.kind {
         RunnableKind::Test { test_id } => format!("test {}", test_id),
+        RunnableKind::TestMod { path } if path.is_empty() => "test crate".to_string(),
         RunnableKind::TestMod { path } => format!("test-mod {}", path),
         RunnableKind::Bench { test_id } => format!("bench {}", test_id),
+        RunnableKind::DocTest { test_id } => format!("doctest {}", test_id),
         RunnableKind::Bin => "run binary".to_string(),
     };
     Ok(req::Runnable {
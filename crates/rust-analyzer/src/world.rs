@@ -13,11 +13,12 @@ use lsp_server::ErrorCode;
 use lsp_types::Url;
 use parking_lot::RwLock;
 use ra_cargo_watch::{url_from_path_with_drive_lowercasing, CheckOptions, CheckWatcher};
+use ra_db::Env;
 use ra_ide::{
     Analysis, AnalysisChange, AnalysisHost, CrateGraph, FeatureFlags, FileId, LibraryData,
     SourceRootId,
 };
-use ra_project_model::{get_rustc_cfg_options, ProjectWorkspace};
+use ra_project_model::{get_rustc_cfg_options, ProjectWorkspace, WorkspaceCrateGraph};
 use ra_vfs::{LineEndings, RootEntry, Vfs, VfsChange, VfsFile, VfsRoot, VfsTask, Watch};
 use relative_path::RelativePathBuf;
 
@@ -109,14 +110,6 @@ impl WorldState {
             change.set_debug_root_path(SourceRootId(r.0), vfs_root_path.display().to_string());
         }
 
-        // FIXME: Read default cfgs from config
-        let default_cfg_options = {
-            let mut opts = get_rustc_cfg_options();
-            opts.insert_atom("test".into());
-            opts.insert_atom("debug_assertion".into());
-            opts
-        };
-
         // Create crate graph from all the workspaces
         let mut crate_graph = CrateGraph::default();
         let mut load = |path: &std::path::Path| {
@@ -124,8 +117,27 @@ impl WorldState {
             vfs_file.map(|f| FileId(f.0))
         };
         for ws in workspaces.iter() {
-            let (graph, crate_names) = ws.to_crate_graph(&default_cfg_options, &mut load);
+            // FIXME: Read default cfgs from config
+            let default_cfg_options = {
+                let mut opts = get_rustc_cfg_options(ws.target());
+                opts.insert_atom("test".into());
+                opts.insert_atom("debug_assertion".into());
+                opts
+            };
+            let WorkspaceCrateGraph { crate_graph: graph, crate_names, crate_roots } =
+                ws.to_crate_graph(&default_cfg_options, &Env::default(), &mut load);
+            // The ids of the workspace's crates are shifted by `extend`
             let shift = crate_graph.extend(graph);
+            // `rust-project.json` crates have no names, call them after their root
+            for (crate_id, root) in crate_roots.iter() {
+                if crate_names.contains_key(crate_id) {
+                    continue;
+                }
+                if let Some(name) = root.path().file_name() {
+                    let name = name.to_string_lossy().into_owned();
+                    change.set_debug_crate_name(crate_id.shift(shift), name)
+                }
+            }
             for (crate_id, name) in crate_names {
                 change.set_debug_crate_name(crate_id.shift(shift), name)
             }
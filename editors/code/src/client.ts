@@ -33,6 +33,7 @@ export async function createClient(config: Config, serverPath: string): Promise<
             cargoWatchArgs: cargoWatchOpts.arguments,
             cargoWatchCommand: cargoWatchOpts.command,
             cargoWatchAllTargets: cargoWatchOpts.allTargets,
+            cargoWatchCollapseDuplicates: cargoWatchOpts.collapseDuplicates,
             excludeGlobs: config.excludeGlobs,
             useClientWatching: config.useClientWatching,
             featureFlags: config.featureFlags,
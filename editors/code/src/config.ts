@@ -9,6 +9,7 @@ export interface CargoWatchOptions {
     arguments: string[];
     command: string;
     allTargets: boolean;
+    collapseDuplicates: boolean;
 }
 
 export interface CargoFeatures {
@@ -155,6 +156,7 @@ export class Config {
             enable: this.cfg.get("cargo-watch.enable") as boolean,
             arguments: this.cfg.get("cargo-watch.arguments") as string[],
             allTargets: this.cfg.get("cargo-watch.allTargets") as boolean,
+            collapseDuplicates: this.cfg.get("cargo-watch.collapseDuplicates") as boolean,
             command: this.cfg.get("cargo-watch.command") as string,
         };
     }
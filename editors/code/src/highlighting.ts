@@ -223,6 +223,8 @@ function createDecorationFromTextmate(
 const TAG_TO_SCOPES = new Map<string, string[]>([
     ["field", ["entity.name.field"]],
     ["function", ["entity.name.function"]],
+    ["function.method", ["entity.name.function.method", "entity.name.function"]],
+    ["function.unsafe", ["entity.name.function.unsafe", "entity.name.function"]],
     ["module", ["entity.name.module"]],
     ["constant", ["entity.name.constant"]],
     ["macro", ["entity.name.macro"]],
@@ -234,11 +236,14 @@ const TAG_TO_SCOPES = new Map<string, string[]>([
     ["type.builtin", ["entity.name.type", "support.type.primitive"]],
     ["type.self", ["entity.name.type.parameter.self"]],
     ["type.param", ["entity.name.type.parameter", "entity.name.type.param.rust"]],
+    ["type.trait", ["entity.name.type.trait"]],
     ["type.lifetime", ["entity.name.type.lifetime", "entity.name.lifetime.rust"]],
 
     ["literal.byte", ["constant.character.byte"]],
     ["literal.char", ["constant.character.rust"]],
+    ["literal.escape", ["constant.character.escape"]],
     ["literal.numeric", ["constant.numeric"]],
+    ["format_specifier", ["constant.other.placeholder"]],
 
     ["comment", ["comment"]],
     ["string", ["string.quoted"]],
@@ -246,6 +251,9 @@ const TAG_TO_SCOPES = new Map<string, string[]>([
 
     ["keyword", ["keyword"]],
     ["keyword.unsafe", ["keyword.other.unsafe"]],
+    ["keyword.self", ["variable.language.self"]],
+
+    ["operator.unsafe", ["keyword.operator.unsafe"]],
     ["keyword.control", ["keyword.control"]],
 ]);
 
@@ -460,6 +460,7 @@ pub(crate) const AST_SRC: AstSrc = AstSrc {
         struct TokenTree {}
         struct TypeParamList {
             type_params: [TypeParam],
+            const_params: [ConstParam],
             lifetime_params: [LifetimeParam],
         }
         struct TypeParam: NameOwner, AttrsOwner, TypeBoundsOwner {